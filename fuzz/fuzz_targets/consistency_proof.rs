@@ -0,0 +1,22 @@
+#![no_main]
+
+use anonymous_credentials::{ConsistencyProof, KeyLogCheckpoint, KEY_LOG_CHECKPOINT_SIZE};
+use anonymous_credentials_fuzz::assert_reencodes;
+use libfuzzer_sys::fuzz_target;
+
+// Input is the old and the new checkpoint followed by the proof
+fuzz_target!(|data: &[u8]| {
+    if data.len() < KEY_LOG_CHECKPOINT_SIZE * 2 {
+        return;
+    }
+    let (checkpoints, proof) = data.split_at(KEY_LOG_CHECKPOINT_SIZE * 2);
+    let (old, new) = checkpoints.split_at(KEY_LOG_CHECKPOINT_SIZE);
+    let old = KeyLogCheckpoint::try_from(old).unwrap();
+    let new = KeyLogCheckpoint::try_from(new).unwrap();
+    let Ok(proof) = ConsistencyProof::try_from(proof) else {
+        return;
+    };
+    assert_reencodes!(ConsistencyProof, proof);
+
+    let _ = proof.verify(&old, &new);
+});
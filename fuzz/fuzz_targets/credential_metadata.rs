@@ -0,0 +1,11 @@
+#![no_main]
+
+use anonymous_credentials::CredentialMetadata;
+use anonymous_credentials_fuzz::assert_reencodes;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(metadata) = CredentialMetadata::try_from(data) {
+        assert_reencodes!(CredentialMetadata, metadata);
+    }
+});
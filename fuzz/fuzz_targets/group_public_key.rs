@@ -0,0 +1,16 @@
+#![no_main]
+
+use anonymous_credentials::GroupPublicKey;
+use anonymous_credentials_fuzz::{assert_reencodes, gsk, join_response, manager};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(public_key) = GroupPublicKey::try_from(data) else {
+        return;
+    };
+    assert_reencodes!(GroupPublicKey, public_key);
+
+    // Exercises the key's proofs of knowledge and, if they pass, the
+    // pairing checks against an untrusted key
+    let _ = manager().finish_join(&public_key, &gsk(), join_response());
+});
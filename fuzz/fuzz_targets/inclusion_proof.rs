@@ -0,0 +1,21 @@
+#![no_main]
+
+use anonymous_credentials::{InclusionProof, KeyLogCheckpoint, KEY_LOG_CHECKPOINT_SIZE};
+use anonymous_credentials_fuzz::{assert_reencodes, logged_key};
+use libfuzzer_sys::fuzz_target;
+
+// Input is a checkpoint followed by a proof that the fixture key is in the
+// log at that checkpoint
+fuzz_target!(|data: &[u8]| {
+    if data.len() < KEY_LOG_CHECKPOINT_SIZE {
+        return;
+    }
+    let (checkpoint, proof) = data.split_at(KEY_LOG_CHECKPOINT_SIZE);
+    let checkpoint = KeyLogCheckpoint::try_from(checkpoint).unwrap();
+    let Ok(proof) = InclusionProof::try_from(proof) else {
+        return;
+    };
+    assert_reencodes!(InclusionProof, proof);
+
+    let _ = proof.verify(&logged_key(), &checkpoint);
+});
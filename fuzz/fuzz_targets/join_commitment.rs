@@ -0,0 +1,11 @@
+#![no_main]
+
+use anonymous_credentials::JoinCommitment;
+use anonymous_credentials_fuzz::assert_reencodes;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(commitment) = JoinCommitment::try_from(data) {
+        assert_reencodes!(JoinCommitment, commitment);
+    }
+});
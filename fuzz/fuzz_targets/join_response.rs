@@ -0,0 +1,14 @@
+#![no_main]
+
+use anonymous_credentials::JoinResponse;
+use anonymous_credentials_fuzz::{assert_reencodes, group_public_key, gsk, manager};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(response) = JoinResponse::try_from(data) else {
+        return;
+    };
+    assert_reencodes!(JoinResponse, response);
+
+    let _ = manager().finish_join(&group_public_key(), &gsk(), response);
+});
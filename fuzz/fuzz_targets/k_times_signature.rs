@@ -0,0 +1,22 @@
+#![no_main]
+
+use anonymous_credentials::{verify_k_times, Ciphersuite, KTimesSignature};
+use anonymous_credentials_fuzz::{assert_reencodes, group_public_key, EPOCH};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(signature) = KTimesSignature::try_from(data) else {
+        return;
+    };
+    assert_reencodes!(KTimesSignature, signature);
+
+    let _ = verify_k_times(
+        Ciphersuite::default(),
+        &group_public_key(),
+        &signature,
+        b"message",
+        b"context",
+        EPOCH,
+        signature.k(),
+    );
+});
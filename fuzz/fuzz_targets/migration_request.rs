@@ -0,0 +1,12 @@
+#![no_main]
+
+use anonymous_credentials::{JoinRequest, MigrationRequest};
+use anonymous_credentials_fuzz::assert_reencodes;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(request) = MigrationRequest::try_from(data) {
+        assert_reencodes!(MigrationRequest, request);
+        assert_reencodes!(JoinRequest, request.join_request());
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use anonymous_credentials::Signature;
+use anonymous_credentials_fuzz::assert_reencodes;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(signature) = Signature::try_from(data) {
+        assert_reencodes!(Signature, signature);
+        let _ = signature.nym();
+    }
+});
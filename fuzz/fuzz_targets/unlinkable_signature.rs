@@ -0,0 +1,19 @@
+#![no_main]
+
+use anonymous_credentials::{verify_unlinkable, Ciphersuite, UnlinkableSignature};
+use anonymous_credentials_fuzz::{assert_reencodes, group_public_key};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(signature) = UnlinkableSignature::try_from(data) else {
+        return;
+    };
+    assert_reencodes!(UnlinkableSignature, signature);
+
+    let _ = verify_unlinkable(
+        Ciphersuite::default(),
+        &group_public_key(),
+        &signature,
+        b"message",
+    );
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use anonymous_credentials::{verify, Ciphersuite, UserCredentials};
+use anonymous_credentials_fuzz::{assert_reencodes, group_public_key, gsk, manager, BASENAME};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(credentials) = UserCredentials::try_from(data) else {
+        return;
+    };
+    assert_reencodes!(UserCredentials, credentials);
+
+    // Credentials restored from storage are not validated, so signing with
+    // arbitrary ones must not panic either
+    let mut manager = manager();
+    manager.set_gsk_and_credentials(gsk(), credentials);
+    let signature = manager.sign(b"message", BASENAME).unwrap();
+    let _ = verify(
+        Ciphersuite::default(),
+        &group_public_key(),
+        &signature,
+        b"message",
+        BASENAME,
+    );
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use anonymous_credentials::{verify, Curve, Signature, SIGNATURE_SIZE};
+use anonymous_credentials_fuzz::{group_public_key, BASENAME};
+use libfuzzer_sys::fuzz_target;
+
+// Input is a signature followed by the signed message
+fuzz_target!(|data: &[u8]| {
+    if data.len() < SIGNATURE_SIZE {
+        return;
+    }
+    let (signature, msg) = data.split_at(SIGNATURE_SIZE);
+    let signature = Signature::try_from(signature).unwrap();
+
+    for suite in Curve::Bn254.suites() {
+        let _ = verify(suite, &group_public_key(), &signature, msg, BASENAME);
+    }
+});
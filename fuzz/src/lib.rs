@@ -0,0 +1,80 @@
+//! Fixtures shared by the fuzz targets: the original C library's group key,
+//! member key and join response, as used by the crate's tests.
+
+use anonymous_credentials::{
+    CredentialBIG, CredentialManager, GroupPublicKey, JoinResponse, KeyLogEntry,
+};
+
+const GROUP_PUBLIC_KEY: &str = "0477ce930400ab04a6e1caa46601dbd1b1ba5d24f0577834a960285a0512e7ed\
+     0174121707ea5d80e083d2e992236864608998a4d08cb3a41dde1fc6b7eaad5b\
+     2125310b44ca712bf63f62c39cb44917de0772fefd876e170729428142c21d4f\
+     179f72fcdc1c1ff5f13e272449ac9ff01a74e95bb011045b12bdac942b46168d\
+     0511ecbb4651d9ddd6491437a8d6b6e6e6877038ea4317a5de863e237ff64720\
+     14d22c88863b6d8de3eb1b73bb46ab12553c2765bcde905487c518936887ba83\
+     1dc42ca4862bf60b7cccf08ae579f14699fcff5ec8366af5562a2117095dc066\
+     105c17714dadae0b2110b91d0f19f062e9ab410f59e4515cb027e268435502cf\
+     12d4a2dde1c5b711619507485e54e6e6bb1b279e7f42067c47b124e7b1e044de\
+     0345f28cea642eef79e0da60dad085b9bec8b73c61a4eee59ec4f024fc83366e\
+     1efc63762b2c1c214ad151dd01f1a5f16d5a238187f1afdab361dfea2e0956be\
+     24b1bcdba7c9a6a5e0296377bd1cf1b722bc4d375ae8aa4761b7aac5a50e9871";
+
+const GSK: &str = "0198c86f99ede0ca2ed30b8e4ae6cac831c9b398445422a41d95abb4d3f03499";
+
+const JOIN_RESPONSE: &str = "04246220e5a9d48d359178c9e0994cc10f7288b50cd059c24c5a26fc5919682e\
+     8017b66ca6185d62bf2bed7cf02503157ab93ff79d8d34ab3c48669954b7e2b6\
+     9c041d98fde59abcd8c0f22790e8d40e253c1240f3697c161d18a9d04ca24ba2\
+     b01f0d100b28b3d529939ec717f4f39e114337878f03c9066afc225033276f16\
+     2b4904248822cb548ccb8167480e23f0198134d1547b005ac84c2a7101a4d39c\
+     924ee50298022d7dd7c9f0006eab2576635a36af81e0f781437c4ee35b867251\
+     1089830401074ad73c4e9e9aed541bdc5a2df2ee815a3ac4f6297b73da35db2a\
+     646e19720475cfe50eb2465833b50758f6c8f09fdf645643a4b3ef5bd494be6a\
+     551768c81677932196184249f179d319feba43b32da42501daa355d3cde30615\
+     a08ac687188a8c6e3b8a330f76c233e900acd6ef31c50796b91929cfc16b4fca\
+     d40b5309";
+
+/// Basename that the fixture signatures in the seed corpus were made under.
+pub const BASENAME: &[u8] = b"basename";
+
+/// Epoch that the fixture k-times signatures in the seed corpus were made
+/// for, with message `b"message"` and context `b"context"`.
+pub const EPOCH: u64 = 1;
+
+pub fn group_public_key() -> GroupPublicKey {
+    hex::decode(GROUP_PUBLIC_KEY)
+        .unwrap()
+        .as_slice()
+        .try_into()
+        .unwrap()
+}
+
+pub fn gsk() -> CredentialBIG {
+    hex::decode(GSK).unwrap().as_slice().try_into().unwrap()
+}
+
+pub fn join_response() -> JoinResponse {
+    hex::decode(JOIN_RESPONSE)
+        .unwrap()
+        .as_slice()
+        .try_into()
+        .unwrap()
+}
+
+/// Entry whose inclusion the key-log proofs in the seed corpus prove.
+pub fn logged_key() -> KeyLogEntry {
+    KeyLogEntry::GroupKey(group_public_key())
+}
+
+pub fn manager() -> CredentialManager {
+    CredentialManager::new_with_seed(&[0u8; 1])
+}
+
+/// Asserts that re-encoding a parsed value is stable. Off-curve points
+/// decode to infinity, so only the second encoding must equal the first.
+#[macro_export]
+macro_rules! assert_reencodes {
+    ($ty:ty, $value:expr) => {{
+        let bytes = $value.to_bytes();
+        let reparsed = <$ty>::try_from(&bytes[..]).expect("re-encoded value must parse");
+        assert_eq!(reparsed.to_bytes(), bytes);
+    }};
+}
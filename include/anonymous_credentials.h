@@ -0,0 +1,338 @@
+#ifndef ANONYMOUS_CREDENTIALS_H
+#define ANONYMOUS_CREDENTIALS_H
+
+/* Generated with cbindgen from src/capi.rs. Do not edit manually. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define AC_BIG_SIZE 32
+
+#define AC_ECP_SIZE 65
+
+#define AC_ECP2_SIZE 128
+
+#define AC_ECP_PROOF_SIZE (AC_BIG_SIZE * 2)
+
+#define AC_JOIN_REQUEST_SIZE (AC_ECP_SIZE + AC_ECP_PROOF_SIZE)
+
+#define AC_USER_CREDENTIALS_SIZE (AC_ECP_SIZE * 4)
+
+#define AC_JOIN_RESPONSE_SIZE (AC_USER_CREDENTIALS_SIZE + AC_ECP_PROOF_SIZE)
+
+#define AC_GROUP_PUBLIC_KEY_SIZE ((AC_ECP2_SIZE * 2) + (AC_BIG_SIZE * 4))
+
+#define AC_SIGNATURE_SIZE ((AC_ECP_SIZE * 5) + AC_ECP_PROOF_SIZE)
+
+/**
+ * gsk followed by the credentials, as written by `GS_finishJoin`.
+ */
+#define GS_USER_CREDENTIALS_SIZE (AC_BIG_SIZE + AC_USER_CREDENTIALS_SIZE)
+
+/**
+ * Result code returned by every fallible function. Apart from `Ok`,
+ * `NullPointer` and `BufferTooSmall`, each code corresponds to a
+ * `CredentialError` variant.
+ */
+typedef enum AcResult {
+  AC_RESULT_OK = 0,
+  AC_RESULT_BAD_ECP = 1,
+  AC_RESULT_BAD_ECP2 = 2,
+  AC_RESULT_BAD_BIG = 3,
+  AC_RESULT_BAD_ECP_PROOF = 4,
+  AC_RESULT_BAD_USER_CREDENTIALS = 5,
+  AC_RESULT_BAD_JOIN_RESPONSE = 6,
+  AC_RESULT_GROUP_PUBLIC_KEY_LENGTH = 7,
+  AC_RESULT_JOIN_RESPONSE_VALIDATION = 8,
+  AC_RESULT_CREDENTIALS_NOT_SET = 9,
+  AC_RESULT_BAD_GROUP_PUBLIC_KEY = 10,
+  AC_RESULT_UNKNOWN_CIPHERSUITE = 11,
+  AC_RESULT_BAD_SIGNATURE = 12,
+  AC_RESULT_SIGNATURE_VALIDATION = 13,
+  AC_RESULT_BAD_JOIN_REQUEST = 14,
+  AC_RESULT_BAD_ATTRIBUTES = 15,
+  AC_RESULT_ATTRIBUTE_OUT_OF_RANGE = 16,
+  AC_RESULT_COUNTER_OUT_OF_RANGE = 17,
+  AC_RESULT_BAD_K_TIMES_SIGNATURE = 18,
+  AC_RESULT_BAD_BASENAME = 19,
+  AC_RESULT_BAD_UNLINKABLE_SIGNATURE = 20,
+  AC_RESULT_CREDENTIALS_VALIDATION = 21,
+  AC_RESULT_BAD_MIGRATION_REQUEST = 22,
+  AC_RESULT_MIGRATION_VALIDATION = 23,
+  AC_RESULT_BAD_CREDENTIAL_METADATA = 24,
+  AC_RESULT_CREDENTIALS_EXPIRED = 25,
+  AC_RESULT_BAD_THRESHOLD_MESSAGE = 26,
+  AC_RESULT_THRESHOLD_ISSUANCE = 27,
+  AC_RESULT_KEY_GENERATION = 28,
+  AC_RESULT_BAD_KEY_LOG_MESSAGE = 29,
+  AC_RESULT_KEY_LOG_VERIFICATION = 30,
+  AC_RESULT_NULL_POINTER = 100,
+  /**
+   * An output buffer's length, passed in through a pointer, is too small.
+   */
+  AC_RESULT_BUFFER_TOO_SMALL = 101,
+} AcResult;
+
+/**
+ * Opaque handle to a `CredentialManager`.
+ */
+typedef struct AcCredentialManager AcCredentialManager;
+
+/**
+ * State of the `GS_*` functions: a manager, the group public key and the
+ * gsk of a join in progress. Lengths are `int`s, output lengths pass the
+ * buffer's capacity in and the bytes written out, and functions return an
+ * `int` that is 0 on success and otherwise the `AcResult` code.
+ */
+typedef struct GsState GsState;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a manager seeded from the operating system's entropy source.
+ * Must be released with `ac_credential_manager_free`.
+ */
+struct AcCredentialManager *ac_credential_manager_new(void);
+
+/**
+ * Creates a manager seeded with caller-supplied entropy, or returns NULL
+ * if `entropy` is NULL. Must be released with `ac_credential_manager_free`.
+ *
+ * # Safety
+ *
+ * `entropy` must point to `entropy_len` readable bytes.
+ */
+struct AcCredentialManager *ac_credential_manager_new_with_seed(const uint8_t *entropy,
+                                                                size_t entropy_len);
+
+/**
+ * # Safety
+ *
+ * `manager` must be NULL or a pointer returned by one of the
+ * `ac_credential_manager_new*` functions that has not been freed yet.
+ */
+void ac_credential_manager_free(struct AcCredentialManager *manager);
+
+/**
+ * Selects the ciphersuite by its `Ciphersuite::id`, which must be one on
+ * BN254.
+ *
+ * # Safety
+ *
+ * `manager` must be a live pointer returned by `ac_credential_manager_new*`.
+ */
+enum AcResult ac_set_ciphersuite(struct AcCredentialManager *manager, uint8_t id);
+
+/**
+ * Writes a new gsk to `gsk_out` (`AC_BIG_SIZE` bytes) and the join request
+ * to `join_msg_out` (`AC_JOIN_REQUEST_SIZE` bytes).
+ *
+ * # Safety
+ *
+ * `manager` must be a live pointer returned by `ac_credential_manager_new*`,
+ * `challenge` must point to `challenge_len` readable bytes and the output
+ * buffers must be writable for their documented sizes.
+ */
+enum AcResult ac_start_join(struct AcCredentialManager *manager,
+                            const uint8_t *challenge,
+                            size_t challenge_len,
+                            uint8_t *gsk_out,
+                            uint8_t *join_msg_out);
+
+/**
+ * Validates the issuer's join response and writes the credentials to
+ * `credentials_out` (`AC_USER_CREDENTIALS_SIZE` bytes).
+ *
+ * # Safety
+ *
+ * `manager` must be a live pointer returned by `ac_credential_manager_new*`,
+ * every input must point to its given number of readable bytes and
+ * `credentials_out` must be writable for `AC_USER_CREDENTIALS_SIZE` bytes.
+ */
+enum AcResult ac_finish_join(struct AcCredentialManager *manager,
+                             const uint8_t *public_key,
+                             size_t public_key_len,
+                             const uint8_t *gsk,
+                             size_t gsk_len,
+                             const uint8_t *join_response,
+                             size_t join_response_len,
+                             uint8_t *credentials_out);
+
+/**
+ * # Safety
+ *
+ * `manager` must be a live pointer returned by `ac_credential_manager_new*`
+ * and every input must point to its given number of readable bytes.
+ */
+enum AcResult ac_set_gsk_and_credentials(struct AcCredentialManager *manager,
+                                         const uint8_t *gsk,
+                                         size_t gsk_len,
+                                         const uint8_t *credentials,
+                                         size_t credentials_len);
+
+/**
+ * Signs `msg` under `basename` and writes the signature to `signature_out`
+ * (`AC_SIGNATURE_SIZE` bytes).
+ *
+ * # Safety
+ *
+ * `manager` must be a live pointer returned by `ac_credential_manager_new*`,
+ * every input must point to its given number of readable bytes and
+ * `signature_out` must be writable for `AC_SIGNATURE_SIZE` bytes.
+ */
+enum AcResult ac_sign(struct AcCredentialManager *manager,
+                      const uint8_t *msg,
+                      size_t msg_len,
+                      const uint8_t *basename,
+                      size_t basename_len,
+                      uint8_t *signature_out);
+
+/**
+ * Verifies a signature on `msg` under `basename` against the group public
+ * key, using the ciphersuite with the given `Ciphersuite::id`.
+ *
+ * # Safety
+ *
+ * Every input must point to its given number of readable bytes.
+ */
+enum AcResult ac_verify(uint8_t ciphersuite,
+                        const uint8_t *public_key,
+                        size_t public_key_len,
+                        const uint8_t *msg,
+                        size_t msg_len,
+                        const uint8_t *basename,
+                        size_t basename_len,
+                        const uint8_t *signature,
+                        size_t signature_len);
+
+/**
+ * Bytes to allocate for a `GsState`.
+ */
+int GS_getStateSize(void);
+
+/**
+ * Initializes `state`, seeding it from the operating system's entropy
+ * source. Must be released with `GS_destroyState`.
+ *
+ * # Safety
+ *
+ * `state` must point to `GS_getStateSize()` writable bytes, aligned as
+ * `malloc` aligns them.
+ */
+int GS_initState(struct GsState *state);
+
+/**
+ * # Safety
+ *
+ * `state` must be NULL or initialized by `GS_initState` and not destroyed
+ * yet. Its memory may be freed afterwards.
+ */
+void GS_destroyState(struct GsState *state);
+
+/**
+ * Reseeds the state's random number generator, keeping any loaded key and
+ * credentials. Does nothing if `state` or `seed` is NULL.
+ *
+ * # Safety
+ *
+ * `state` must be initialized by `GS_initState` and `seed` must point to
+ * `seed_len` readable bytes.
+ */
+void GS_seed(struct GsState *state, const uint8_t *seed, int seed_len);
+
+/**
+ * # Safety
+ *
+ * `state` must be initialized by `GS_initState` and `public_key` must
+ * point to `public_key_len` readable bytes.
+ */
+int GS_loadGroupPubKey(struct GsState *state,
+                       const uint8_t *public_key,
+                       int public_key_len);
+
+/**
+ * Starts a join, keeping the new gsk in `state` for `GS_finishJoin`, and
+ * writes the join request to `join_msg`.
+ *
+ * # Safety
+ *
+ * `state` must be initialized by `GS_initState`, `challenge` must point
+ * to `challenge_len` readable bytes and `join_msg` must be writable for
+ * `*join_msg_len` bytes.
+ */
+int GS_startJoin(struct GsState *state,
+                 const uint8_t *challenge,
+                 int challenge_len,
+                 uint8_t *join_msg,
+                 int *join_msg_len);
+
+/**
+ * Validates the join response against the loaded group public key, loads
+ * the credentials and writes them to `credentials` in the format read by
+ * `GS_loadUserCredentials` (`GS_USER_CREDENTIALS_SIZE` bytes).
+ *
+ * # Safety
+ *
+ * `state` must be initialized by `GS_initState`, `join_response` must
+ * point to `join_response_len` readable bytes and `credentials` must be
+ * writable for `*credentials_len` bytes.
+ */
+int GS_finishJoin(struct GsState *state,
+                  const uint8_t *join_response,
+                  int join_response_len,
+                  uint8_t *credentials,
+                  int *credentials_len);
+
+/**
+ * Loads the gsk and credentials written by `GS_finishJoin`.
+ *
+ * # Safety
+ *
+ * `state` must be initialized by `GS_initState` and `credentials` must
+ * point to `credentials_len` readable bytes.
+ */
+int GS_loadUserCredentials(struct GsState *state,
+                           const uint8_t *credentials,
+                           int credentials_len);
+
+/**
+ * Signs `msg` under `basename` and writes the signature to `signature`
+ * (`AC_SIGNATURE_SIZE` bytes).
+ *
+ * # Safety
+ *
+ * `state` must be initialized by `GS_initState`, every input must point
+ * to its given number of readable bytes and `signature` must be writable
+ * for `*signature_len` bytes.
+ */
+int GS_sign(struct GsState *state,
+            const uint8_t *msg,
+            int msg_len,
+            const uint8_t *basename,
+            int basename_len,
+            uint8_t *signature,
+            int *signature_len);
+
+/**
+ * Verifies a signature against the loaded group public key.
+ *
+ * # Safety
+ *
+ * `state` must be initialized by `GS_initState` and every input must
+ * point to its given number of readable bytes.
+ */
+int GS_verify(struct GsState *state,
+              const uint8_t *msg,
+              int msg_len,
+              const uint8_t *basename,
+              int basename_len,
+              const uint8_t *signature,
+              int signature_len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* ANONYMOUS_CREDENTIALS_H */
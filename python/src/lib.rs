@@ -0,0 +1,318 @@
+use std::borrow::Cow;
+
+use ac::{Ciphersuite, CredentialBIG, JoinResponse, UserCredentials};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+create_exception!(anonymous_credentials, CredentialError, PyException);
+create_exception!(anonymous_credentials, BadECP, CredentialError);
+create_exception!(anonymous_credentials, BadECP2, CredentialError);
+create_exception!(anonymous_credentials, BadBIG, CredentialError);
+create_exception!(anonymous_credentials, BadECPProof, CredentialError);
+create_exception!(anonymous_credentials, BadUserCredentials, CredentialError);
+create_exception!(anonymous_credentials, BadJoinResponse, CredentialError);
+create_exception!(anonymous_credentials, GroupPublicKeyLength, CredentialError);
+create_exception!(
+    anonymous_credentials,
+    JoinResponseValidation,
+    CredentialError
+);
+create_exception!(anonymous_credentials, CredentialsNotSet, CredentialError);
+create_exception!(anonymous_credentials, BadGroupPublicKey, CredentialError);
+create_exception!(anonymous_credentials, UnknownCiphersuite, CredentialError);
+create_exception!(anonymous_credentials, BadSignature, CredentialError);
+create_exception!(anonymous_credentials, SignatureValidation, CredentialError);
+create_exception!(anonymous_credentials, BadJoinRequest, CredentialError);
+create_exception!(anonymous_credentials, BadAttributes, CredentialError);
+create_exception!(anonymous_credentials, AttributeOutOfRange, CredentialError);
+create_exception!(anonymous_credentials, CounterOutOfRange, CredentialError);
+create_exception!(anonymous_credentials, BadKTimesSignature, CredentialError);
+create_exception!(anonymous_credentials, BadBasename, CredentialError);
+create_exception!(
+    anonymous_credentials,
+    BadUnlinkableSignature,
+    CredentialError
+);
+create_exception!(
+    anonymous_credentials,
+    CredentialsValidation,
+    CredentialError
+);
+create_exception!(anonymous_credentials, BadMigrationRequest, CredentialError);
+create_exception!(anonymous_credentials, MigrationValidation, CredentialError);
+create_exception!(
+    anonymous_credentials,
+    BadCredentialMetadata,
+    CredentialError
+);
+create_exception!(anonymous_credentials, CredentialsExpired, CredentialError);
+create_exception!(anonymous_credentials, BadThresholdMessage, CredentialError);
+create_exception!(anonymous_credentials, ThresholdIssuance, CredentialError);
+create_exception!(anonymous_credentials, KeyGeneration, CredentialError);
+create_exception!(anonymous_credentials, BadKeyLogMessage, CredentialError);
+create_exception!(anonymous_credentials, KeyLogVerification, CredentialError);
+
+fn to_py_err(err: ac::CredentialError) -> PyErr {
+    let msg = err.to_string();
+    match err {
+        ac::CredentialError::BadECP => BadECP::new_err(msg),
+        ac::CredentialError::BadECP2 => BadECP2::new_err(msg),
+        ac::CredentialError::BadBIG => BadBIG::new_err(msg),
+        ac::CredentialError::BadECPProof => BadECPProof::new_err(msg),
+        ac::CredentialError::BadUserCredentials => BadUserCredentials::new_err(msg),
+        ac::CredentialError::BadJoinResponse => BadJoinResponse::new_err(msg),
+        ac::CredentialError::GroupPublicKeyLength => GroupPublicKeyLength::new_err(msg),
+        ac::CredentialError::JoinResponseValidation => JoinResponseValidation::new_err(msg),
+        ac::CredentialError::CredentialsNotSet => CredentialsNotSet::new_err(msg),
+        ac::CredentialError::BadGroupPublicKey => BadGroupPublicKey::new_err(msg),
+        ac::CredentialError::UnknownCiphersuite => UnknownCiphersuite::new_err(msg),
+        ac::CredentialError::BadSignature => BadSignature::new_err(msg),
+        ac::CredentialError::SignatureValidation => SignatureValidation::new_err(msg),
+        ac::CredentialError::BadJoinRequest => BadJoinRequest::new_err(msg),
+        ac::CredentialError::BadAttributes => BadAttributes::new_err(msg),
+        ac::CredentialError::AttributeOutOfRange => AttributeOutOfRange::new_err(msg),
+        ac::CredentialError::CounterOutOfRange => CounterOutOfRange::new_err(msg),
+        ac::CredentialError::BadKTimesSignature => BadKTimesSignature::new_err(msg),
+        ac::CredentialError::BadBasename => BadBasename::new_err(msg),
+        ac::CredentialError::BadUnlinkableSignature => BadUnlinkableSignature::new_err(msg),
+        ac::CredentialError::CredentialsValidation => CredentialsValidation::new_err(msg),
+        ac::CredentialError::BadMigrationRequest => BadMigrationRequest::new_err(msg),
+        ac::CredentialError::MigrationValidation => MigrationValidation::new_err(msg),
+        ac::CredentialError::BadCredentialMetadata => BadCredentialMetadata::new_err(msg),
+        ac::CredentialError::CredentialsExpired => CredentialsExpired::new_err(msg),
+        ac::CredentialError::BadThresholdMessage => BadThresholdMessage::new_err(msg),
+        ac::CredentialError::ThresholdIssuance => ThresholdIssuance::new_err(msg),
+        ac::CredentialError::KeyGeneration => KeyGeneration::new_err(msg),
+        ac::CredentialError::BadKeyLogMessage => BadKeyLogMessage::new_err(msg),
+        ac::CredentialError::KeyLogVerification => KeyLogVerification::new_err(msg),
+    }
+}
+
+fn ciphersuite(id: u8) -> PyResult<Ciphersuite> {
+    Ciphersuite::try_from(id).map_err(to_py_err)
+}
+
+#[pyclass(module = "anonymous_credentials")]
+struct GroupPublicKey(ac::GroupPublicKey);
+
+#[pymethods]
+impl GroupPublicKey {
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        Ok(Self(data.try_into().map_err(to_py_err)?))
+    }
+
+    fn to_bytes(&self) -> Cow<'static, [u8]> {
+        Cow::Owned(self.0.to_bytes().to_vec())
+    }
+
+    fn __bytes__(&self) -> Cow<'static, [u8]> {
+        self.to_bytes()
+    }
+}
+
+#[pyclass(module = "anonymous_credentials")]
+struct Signature(ac::Signature);
+
+#[pymethods]
+impl Signature {
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        Ok(Self(data.try_into().map_err(to_py_err)?))
+    }
+
+    fn to_bytes(&self) -> Cow<'static, [u8]> {
+        Cow::Owned(self.0.to_bytes().to_vec())
+    }
+
+    fn __bytes__(&self) -> Cow<'static, [u8]> {
+        self.to_bytes()
+    }
+
+    /// Pseudonym of the signer, equal across signatures with the same
+    /// basename by the same member.
+    #[getter]
+    fn nym(&self) -> Cow<'static, [u8]> {
+        Cow::Owned(self.0.nym().to_vec())
+    }
+}
+
+#[pyclass(module = "anonymous_credentials")]
+struct CredentialManager(ac::CredentialManager);
+
+#[pymethods]
+impl CredentialManager {
+    /// Seeds from the operating system unless `seed` is given, which is
+    /// only meant for reproducing test vectors.
+    #[new]
+    #[pyo3(signature = (seed=None))]
+    fn new(seed: Option<&[u8]>) -> Self {
+        match seed {
+            Some(seed) => Self(ac::CredentialManager::new_with_seed(seed)),
+            None => Self(ac::CredentialManager::new()),
+        }
+    }
+
+    fn set_ciphersuite(&mut self, id: u8) -> PyResult<()> {
+        self.0.set_ciphersuite(ciphersuite(id)?).map_err(to_py_err)
+    }
+
+    /// Returns `(gsk, join_msg)`.
+    fn start_join(&mut self, challenge: &[u8]) -> (Cow<'static, [u8]>, Cow<'static, [u8]>) {
+        let result = self.0.start_join(challenge);
+        (
+            Cow::Owned(result.gsk.to_bytes().to_vec()),
+            Cow::Owned(result.join_msg.to_bytes().to_vec()),
+        )
+    }
+
+    /// Validates the issuer's join response and returns the credentials.
+    fn finish_join(
+        &mut self,
+        public_key: &GroupPublicKey,
+        gsk: &[u8],
+        join_response: &[u8],
+    ) -> PyResult<Cow<'static, [u8]>> {
+        let gsk: CredentialBIG = gsk.try_into().map_err(to_py_err)?;
+        let join_response: JoinResponse = join_response.try_into().map_err(to_py_err)?;
+        let credentials = self
+            .0
+            .finish_join(&public_key.0, &gsk, join_response)
+            .map_err(to_py_err)?;
+        Ok(Cow::Owned(credentials.to_bytes().to_vec()))
+    }
+
+    fn set_gsk_and_credentials(&mut self, gsk: &[u8], credentials: &[u8]) -> PyResult<()> {
+        let gsk: CredentialBIG = gsk.try_into().map_err(to_py_err)?;
+        let credentials: UserCredentials = credentials.try_into().map_err(to_py_err)?;
+        self.0.set_gsk_and_credentials(gsk, credentials);
+        Ok(())
+    }
+
+    fn sign(&mut self, msg: &[u8], basename: &[u8]) -> PyResult<Signature> {
+        Ok(Signature(self.0.sign(msg, basename).map_err(to_py_err)?))
+    }
+}
+
+/// Raises `SignatureValidation` if `signature` is not a valid signature of
+/// `msg` under `basename` by a member of the group.
+#[pyfunction]
+#[pyo3(signature = (public_key, signature, msg, basename, ciphersuite_id=0))]
+fn verify(
+    public_key: &GroupPublicKey,
+    signature: &Signature,
+    msg: &[u8],
+    basename: &[u8],
+    ciphersuite_id: u8,
+) -> PyResult<()> {
+    ac::verify(
+        ciphersuite(ciphersuite_id)?,
+        &public_key.0,
+        &signature.0,
+        msg,
+        basename,
+    )
+    .map_err(to_py_err)
+}
+
+#[pymodule]
+#[pyo3(name = "anonymous_credentials")]
+fn anonymous_credentials(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+
+    m.add_class::<CredentialManager>()?;
+    m.add_class::<GroupPublicKey>()?;
+    m.add_class::<Signature>()?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+
+    m.add("CredentialError", py.get_type_bound::<CredentialError>())?;
+    m.add("BadECP", py.get_type_bound::<BadECP>())?;
+    m.add("BadECP2", py.get_type_bound::<BadECP2>())?;
+    m.add("BadBIG", py.get_type_bound::<BadBIG>())?;
+    m.add("BadECPProof", py.get_type_bound::<BadECPProof>())?;
+    m.add(
+        "BadUserCredentials",
+        py.get_type_bound::<BadUserCredentials>(),
+    )?;
+    m.add("BadJoinResponse", py.get_type_bound::<BadJoinResponse>())?;
+    m.add(
+        "GroupPublicKeyLength",
+        py.get_type_bound::<GroupPublicKeyLength>(),
+    )?;
+    m.add(
+        "JoinResponseValidation",
+        py.get_type_bound::<JoinResponseValidation>(),
+    )?;
+    m.add(
+        "CredentialsNotSet",
+        py.get_type_bound::<CredentialsNotSet>(),
+    )?;
+    m.add(
+        "BadGroupPublicKey",
+        py.get_type_bound::<BadGroupPublicKey>(),
+    )?;
+    m.add(
+        "UnknownCiphersuite",
+        py.get_type_bound::<UnknownCiphersuite>(),
+    )?;
+    m.add("BadSignature", py.get_type_bound::<BadSignature>())?;
+    m.add(
+        "SignatureValidation",
+        py.get_type_bound::<SignatureValidation>(),
+    )?;
+    m.add("BadJoinRequest", py.get_type_bound::<BadJoinRequest>())?;
+    m.add("BadAttributes", py.get_type_bound::<BadAttributes>())?;
+    m.add(
+        "AttributeOutOfRange",
+        py.get_type_bound::<AttributeOutOfRange>(),
+    )?;
+    m.add(
+        "CounterOutOfRange",
+        py.get_type_bound::<CounterOutOfRange>(),
+    )?;
+    m.add(
+        "BadKTimesSignature",
+        py.get_type_bound::<BadKTimesSignature>(),
+    )?;
+    m.add("BadBasename", py.get_type_bound::<BadBasename>())?;
+    m.add(
+        "BadUnlinkableSignature",
+        py.get_type_bound::<BadUnlinkableSignature>(),
+    )?;
+    m.add(
+        "CredentialsValidation",
+        py.get_type_bound::<CredentialsValidation>(),
+    )?;
+    m.add(
+        "BadMigrationRequest",
+        py.get_type_bound::<BadMigrationRequest>(),
+    )?;
+    m.add(
+        "MigrationValidation",
+        py.get_type_bound::<MigrationValidation>(),
+    )?;
+    m.add(
+        "BadCredentialMetadata",
+        py.get_type_bound::<BadCredentialMetadata>(),
+    )?;
+    m.add(
+        "CredentialsExpired",
+        py.get_type_bound::<CredentialsExpired>(),
+    )?;
+    m.add(
+        "BadThresholdMessage",
+        py.get_type_bound::<BadThresholdMessage>(),
+    )?;
+    m.add(
+        "ThresholdIssuance",
+        py.get_type_bound::<ThresholdIssuance>(),
+    )?;
+    m.add("KeyGeneration", py.get_type_bound::<KeyGeneration>())?;
+    m.add("BadKeyLogMessage", py.get_type_bound::<BadKeyLogMessage>())?;
+    m.add(
+        "KeyLogVerification",
+        py.get_type_bound::<KeyLogVerification>(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use alloc::vec::Vec;
+use brave_miracl::rand::RAND;
+
+use super::verify::attribute_points_are_valid;
+use super::{AttributeCredentials, AttributeGroupPublicKey, AttributeJoinResponse};
+use crate::backend::{PairingBackend, BIG_SIZE};
+use crate::ciphersuite::Ciphersuite;
+use crate::data::CredentialBIG;
+use crate::join::{
+    join_response_transcript, verify_aux_fast, verify_ecp2_proof, verify_group_public_key,
+};
+use crate::util::verify_ecp_proof_equals;
+use crate::{CredentialError, Result};
+
+fn verify_attribute_group_public_key<B: PairingBackend>(
+    suite: Ciphersuite,
+    key: &AttributeGroupPublicKey<B>,
+) -> Result<()> {
+    verify_group_public_key::<B>(suite, &key.base)?;
+    match key
+        .attributes
+        .iter()
+        .all(|attribute| verify_ecp2_proof::<B>(suite, &attribute.z, &attribute.c, &attribute.s))
+    {
+        true => Ok(()),
+        false => Err(CredentialError::BadGroupPublicKey),
+    }
+}
+
+/// Validates a join response certifying attributes, in addition to
+/// everything `finish_join` checks.
+pub fn finish_join_with_attributes<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &AttributeGroupPublicKey<B>,
+    gsk: &CredentialBIG<B>,
+    resp: AttributeJoinResponse<B>,
+) -> Result<AttributeCredentials<B>> {
+    verify_attribute_group_public_key(suite, pub_key)?;
+    if resp.attributes.len() != pub_key.attributes.len() {
+        return Err(CredentialError::BadAttributes);
+    }
+
+    let cred = &resp.base.cred;
+    let g1 = B::g1_generator();
+    let q = B::g1_mul(&g1, &gsk.0);
+
+    let mut rng = RAND::new();
+    rng.seed(BIG_SIZE, &gsk.to_bytes());
+
+    if !verify_ecp_proof_equals(
+        join_response_transcript::<B>(suite, None),
+        &g1,
+        &q,
+        &cred.b,
+        &cred.d,
+        &resp.base.proof,
+    ) {
+        return Err(CredentialError::JoinResponseValidation);
+    }
+
+    if !attribute_points_are_valid::<B>(
+        pub_key,
+        &cred.a,
+        resp.attributes
+            .iter()
+            .map(|attribute| (&attribute.a, &attribute.b)),
+    ) {
+        return Err(CredentialError::JoinResponseValidation);
+    }
+
+    // C = (A + D + sum(m_i * B_i)) ** x
+    let d = resp.attributes.iter().fold(cred.d.clone(), |d, attribute| {
+        B::g1_add(&d, &B::g1_mul(&attribute.b, &attribute.value))
+    });
+    if !verify_aux_fast::<B>(
+        &cred.a,
+        &cred.b,
+        &cred.c,
+        &d,
+        &pub_key.base.x,
+        &pub_key.base.y,
+        &mut rng,
+    ) {
+        return Err(CredentialError::JoinResponseValidation);
+    }
+
+    Ok(AttributeCredentials {
+        base: resp.base.cred,
+        attributes: resp.attributes.into_iter().collect::<Vec<_>>(),
+    })
+}
@@ -0,0 +1,414 @@
+//! Credentials that certify attribute values, such as a client version or a
+//! cohort, alongside the member key.
+//!
+//! The issuer's key gains a G2 element Z_i = G2 ** z_i per attribute, and
+//! each attribute m_i is certified by A_i = A ** z_i and B_i = A_i ** y, with
+//! C = (A + D + sum(m_i * B_i)) ** x. A signature reveals a chosen subset of
+//! the attributes and proves knowledge of the others in zero knowledge. With
+//! no attributes, everything reduces to the plain credentials. Hidden
+//! attributes can also be proven to lie in a range, see `AttributeRange`.
+
+mod join;
+mod range;
+mod sign;
+#[cfg(test)]
+mod tests;
+mod verify;
+
+use alloc::vec::Vec;
+
+use super::backend::{PairingBackend, BIG_SIZE, MAX_ECP2_SIZE};
+use super::data::{
+    big_from_bytes, ecp2_from_compat_bytes, ecp_from_bytes, impl_fixed_size, push_ecp,
+    push_encoded, push_scalar, EncodingSizes, GroupPublicKey, JoinResponse, Signature,
+    UserCredentials,
+};
+use super::util::scalar_from_u64;
+use super::CredentialError;
+
+pub use self::join::finish_join_with_attributes;
+use self::range::RangeProof;
+pub use self::range::{AttributeRange, MAX_RANGE_BITS};
+pub use self::sign::{sign_with_attribute_ranges, sign_with_attributes};
+pub use self::verify::verify_with_attributes;
+
+/// Most attributes a key or credential may carry, as counts are encoded in
+/// a single byte.
+pub const MAX_ATTRIBUTES: usize = u8::MAX as usize;
+
+/// Attribute value certified by the issuer.
+#[derive(Clone, Copy)]
+pub struct Attribute<B: PairingBackend>(pub(crate) B::Scalar);
+
+impl<B: PairingBackend> From<u64> for Attribute<B> {
+    fn from(value: u64) -> Self {
+        Self(scalar_from_u64::<B>(value))
+    }
+}
+
+impl<B: PairingBackend> Attribute<B> {
+    pub fn to_bytes(self) -> [u8; BIG_SIZE] {
+        let mut bytes = [0u8; BIG_SIZE];
+        B::scalar_to_bytes(&self.0, &mut bytes);
+        bytes
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for Attribute<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(big_from_bytes::<B>(bytes)?))
+    }
+}
+
+impl<B: PairingBackend> PartialEq for Attribute<B> {
+    fn eq(&self, other: &Self) -> bool {
+        B::scalar_equals(&self.0, &other.0)
+    }
+}
+
+impl<B: PairingBackend> core::fmt::Debug for Attribute<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Attribute(")?;
+        for byte in self.to_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Z_i = G2 ** z_i with a proof of knowledge of z_i.
+#[derive(Clone)]
+pub(crate) struct AttributeKey<B: PairingBackend> {
+    pub(crate) z: B::G2,
+    pub(crate) c: B::Scalar,
+    pub(crate) s: B::Scalar,
+}
+
+#[derive(Clone)]
+pub struct AttributeGroupPublicKey<B: PairingBackend> {
+    pub(crate) base: GroupPublicKey<B>,
+    pub(crate) attributes: Vec<AttributeKey<B>>,
+}
+
+/// A_i, B_i and m_i of one certified attribute.
+pub(crate) struct CertifiedAttribute<B: PairingBackend> {
+    pub(crate) a: B::G1,
+    pub(crate) b: B::G1,
+    pub(crate) value: B::Scalar,
+}
+
+pub struct AttributeJoinResponse<B: PairingBackend> {
+    pub(crate) base: JoinResponse<B>,
+    pub(crate) attributes: Vec<CertifiedAttribute<B>>,
+}
+
+pub struct AttributeCredentials<B: PairingBackend> {
+    pub(crate) base: UserCredentials<B>,
+    pub(crate) attributes: Vec<CertifiedAttribute<B>>,
+}
+
+/// Randomized A_i and B_i of one attribute, with m_i if it is disclosed.
+pub(crate) struct SignatureAttribute<B: PairingBackend> {
+    pub(crate) a: B::G1,
+    pub(crate) b: B::G1,
+    pub(crate) disclosed: Option<B::Scalar>,
+}
+
+pub struct AttributeSignature<B: PairingBackend> {
+    pub(crate) base: Signature<B>,
+    pub(crate) attributes: Vec<SignatureAttribute<B>>,
+    // sum(m_i * B_i) over the hidden attributes
+    pub(crate) hidden: B::G1,
+    // Proof of knowledge of the hidden m_i, one response per hidden attribute
+    pub(crate) c: B::Scalar,
+    pub(crate) s: Vec<B::Scalar>,
+    pub(crate) ranges: Vec<RangeProof<B>>,
+}
+
+/// Sizes of the encodings of one attribute key and one certified attribute.
+trait AttributeSizes: PairingBackend {
+    const ATTRIBUTE_KEY_SIZE: usize = Self::G2_SIZE + BIG_SIZE * 2;
+    const CERTIFIED_ATTRIBUTE_SIZE: usize = Self::G1_SIZE * 2 + BIG_SIZE;
+}
+
+impl<B: PairingBackend> AttributeSizes for B {}
+
+/// Splits `bytes` into a fixed-size base encoding, an attribute count and
+/// the remaining attribute encodings.
+fn split_attributes(
+    bytes: &[u8],
+    base_size: usize,
+) -> Result<(&[u8], usize, &[u8]), CredentialError> {
+    if bytes.len() <= base_size {
+        return Err(CredentialError::BadAttributes);
+    }
+    Ok((
+        &bytes[..base_size],
+        bytes[base_size] as usize,
+        &bytes[base_size + 1..],
+    ))
+}
+
+fn certified_attributes_from_bytes<B: PairingBackend>(
+    count: usize,
+    bytes: &[u8],
+) -> Result<Vec<CertifiedAttribute<B>>, CredentialError> {
+    if bytes.len() != count * B::CERTIFIED_ATTRIBUTE_SIZE {
+        return Err(CredentialError::BadAttributes);
+    }
+    bytes
+        .chunks(B::CERTIFIED_ATTRIBUTE_SIZE)
+        .map(|chunk| {
+            Ok(CertifiedAttribute {
+                a: ecp_from_bytes::<B>(&chunk[..B::G1_SIZE])?,
+                b: ecp_from_bytes::<B>(&chunk[B::G1_SIZE..B::G1_SIZE * 2])?,
+                value: big_from_bytes::<B>(&chunk[B::G1_SIZE * 2..])?,
+            })
+        })
+        .collect()
+}
+
+fn push_certified_attributes<B: PairingBackend>(
+    out: &mut Vec<u8>,
+    attributes: &[CertifiedAttribute<B>],
+) {
+    out.push(attributes.len() as u8);
+    for attribute in attributes {
+        push_ecp::<B>(out, &attribute.a);
+        push_ecp::<B>(out, &attribute.b);
+        push_scalar::<B>(out, &attribute.value);
+    }
+}
+
+impl<B: PairingBackend> AttributeGroupPublicKey<B> {
+    pub fn base(&self) -> &GroupPublicKey<B> {
+        &self.base
+    }
+
+    pub fn attribute_count(&self) -> usize {
+        self.attributes.len()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            B::GROUP_PUBLIC_KEY_SIZE + 1 + self.attributes.len() * B::ATTRIBUTE_KEY_SIZE,
+        );
+        push_encoded(&mut out, B::GROUP_PUBLIC_KEY_SIZE, |bytes| {
+            self.base.write_bytes(bytes)
+        });
+        out.push(self.attributes.len() as u8);
+        for attribute in &self.attributes {
+            let mut bytes = [0u8; MAX_ECP2_SIZE];
+            B::g2_to_bytes(&attribute.z, &mut bytes[..B::G2_SIZE]);
+            out.extend_from_slice(&bytes[..B::G2_SIZE]);
+            push_scalar::<B>(&mut out, &attribute.c);
+            push_scalar::<B>(&mut out, &attribute.s);
+        }
+        out
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for AttributeGroupPublicKey<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let (base, count, rest) = split_attributes(bytes, B::GROUP_PUBLIC_KEY_SIZE)?;
+        if rest.len() != count * B::ATTRIBUTE_KEY_SIZE {
+            return Err(CredentialError::BadAttributes);
+        }
+        let attributes = rest
+            .chunks(B::ATTRIBUTE_KEY_SIZE)
+            .map(|chunk| {
+                Ok(AttributeKey {
+                    z: ecp2_from_compat_bytes::<B>(&chunk[..B::G2_SIZE])?,
+                    c: big_from_bytes::<B>(&chunk[B::G2_SIZE..B::G2_SIZE + BIG_SIZE])?,
+                    s: big_from_bytes::<B>(&chunk[B::G2_SIZE + BIG_SIZE..])?,
+                })
+            })
+            .collect::<Result<_, CredentialError>>()?;
+
+        Ok(Self {
+            base: base.try_into()?,
+            attributes,
+        })
+    }
+}
+
+impl<B: PairingBackend> AttributeJoinResponse<B> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            B::JOIN_RESPONSE_SIZE + 1 + self.attributes.len() * B::CERTIFIED_ATTRIBUTE_SIZE,
+        );
+        push_encoded(&mut out, B::JOIN_RESPONSE_SIZE, |bytes| {
+            self.base.write_bytes(bytes)
+        });
+        push_certified_attributes(&mut out, &self.attributes);
+        out
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for AttributeJoinResponse<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let (base, count, rest) = split_attributes(bytes, B::JOIN_RESPONSE_SIZE)?;
+        Ok(Self {
+            base: base.try_into()?,
+            attributes: certified_attributes_from_bytes(count, rest)?,
+        })
+    }
+}
+
+impl<B: PairingBackend> AttributeCredentials<B> {
+    /// Values of the certified attributes, in the order of the issuer's key.
+    pub fn attributes(&self) -> Vec<Attribute<B>> {
+        self.attributes
+            .iter()
+            .map(|attribute| Attribute(attribute.value))
+            .collect()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            B::USER_CREDENTIALS_SIZE + 1 + self.attributes.len() * B::CERTIFIED_ATTRIBUTE_SIZE,
+        );
+        push_encoded(&mut out, B::USER_CREDENTIALS_SIZE, |bytes| {
+            self.base.write_bytes(bytes)
+        });
+        push_certified_attributes(&mut out, &self.attributes);
+        out
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for AttributeCredentials<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let (base, count, rest) = split_attributes(bytes, B::USER_CREDENTIALS_SIZE)?;
+        Ok(Self {
+            base: base.try_into()?,
+            attributes: certified_attributes_from_bytes(count, rest)?,
+        })
+    }
+}
+
+impl<B: PairingBackend> AttributeSignature<B> {
+    fn write_nym(&self, bytes: &mut [u8]) {
+        self.base.write_nym(bytes)
+    }
+
+    /// Indices and values of the attributes the signer chose to reveal.
+    pub fn disclosed(&self) -> Vec<(usize, Attribute<B>)> {
+        self.attributes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, attribute)| attribute.disclosed.map(|value| (i, Attribute(value))))
+            .collect()
+    }
+
+    /// Range statements proven about hidden attributes. Verifiers must check
+    /// that the ranges they require are among them.
+    pub fn ranges(&self) -> Vec<AttributeRange> {
+        self.ranges.iter().map(|proof| proof.range).collect()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_encoded(&mut out, B::SIGNATURE_SIZE, |bytes| {
+            self.base.write_bytes(bytes)
+        });
+        out.push(self.attributes.len() as u8);
+        for attribute in &self.attributes {
+            push_ecp::<B>(&mut out, &attribute.a);
+            push_ecp::<B>(&mut out, &attribute.b);
+            match &attribute.disclosed {
+                Some(value) => {
+                    out.push(1);
+                    push_scalar::<B>(&mut out, value);
+                }
+                None => out.push(0),
+            }
+        }
+        push_ecp::<B>(&mut out, &self.hidden);
+        push_scalar::<B>(&mut out, &self.c);
+        for s in &self.s {
+            push_scalar::<B>(&mut out, s);
+        }
+        out.reserve(
+            1 + self
+                .ranges
+                .iter()
+                .map(RangeProof::encoded_size)
+                .sum::<usize>(),
+        );
+        out.push(self.ranges.len() as u8);
+        for range in &self.ranges {
+            range.push_to(&mut out);
+        }
+        out
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for AttributeSignature<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let (base, count, mut rest) = split_attributes(bytes, B::SIGNATURE_SIZE)?;
+
+        let mut take = |len: usize| {
+            if rest.len() < len {
+                return Err(CredentialError::BadAttributes);
+            }
+            let (head, tail) = rest.split_at(len);
+            rest = tail;
+            Ok(head)
+        };
+
+        let mut attributes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let a = ecp_from_bytes::<B>(take(B::G1_SIZE)?)?;
+            let b = ecp_from_bytes::<B>(take(B::G1_SIZE)?)?;
+            let disclosed = match take(1)?[0] {
+                0 => None,
+                1 => Some(big_from_bytes::<B>(take(BIG_SIZE)?)?),
+                _ => return Err(CredentialError::BadAttributes),
+            };
+            attributes.push(SignatureAttribute { a, b, disclosed });
+        }
+        let hidden = ecp_from_bytes::<B>(take(B::G1_SIZE)?)?;
+        let c = big_from_bytes::<B>(take(BIG_SIZE)?)?;
+
+        let hidden_count = attributes.iter().filter(|a| a.disclosed.is_none()).count();
+        let s = (0..hidden_count)
+            .map(|_| big_from_bytes::<B>(take(BIG_SIZE)?))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let range_count = take(1)?[0];
+        let mut ranges = Vec::with_capacity(range_count as usize);
+        for _ in 0..range_count {
+            let (range, tail) = RangeProof::parse(rest)?;
+            ranges.push(range);
+            rest = tail;
+        }
+        if !rest.is_empty() {
+            return Err(CredentialError::BadAttributes);
+        }
+
+        Ok(Self {
+            base: base.try_into()?,
+            attributes,
+            hidden,
+            c,
+            s,
+            ranges,
+        })
+    }
+}
+
+impl_fixed_size! {
+    /// Pseudonym of the signer for the signature's basename, as for plain
+    /// signatures.
+    AttributeSignature::nym(write_nym) -> ECP_SIZE;
+}
@@ -0,0 +1,677 @@
+//! Range proofs over hidden attributes.
+//!
+//! The offset v = m_i - min is committed as V = v * G + gamma * H and proven
+//! to lie in [0, 2^bits) with a Bulletproofs range proof (Bünz et al., 2018).
+//! Its inner product argument halves the vectors in every round, so a proof
+//! holds 2 * log2(bits) + 4 points and 5 scalars, rounding `bits` up to a
+//! power of two. The range proof draws its own challenges; afterwards
+//! min * G + V = m_i * G + gamma * H is opened in zero knowledge under the
+//! signature's challenge c, reusing the nonce of m_i from the proof over the
+//! hidden attributes, so both proofs are about the same value.
+
+use alloc::vec::Vec;
+use brave_miracl::rand::RAND;
+
+use core::marker::PhantomData;
+
+use super::{push_ecp, push_scalar};
+use crate::backend::{PairingBackend, BIG_SIZE};
+use crate::ciphersuite::Ciphersuite;
+use crate::data::{big_from_bytes, ecp_from_bytes};
+use crate::transcript::{Transcript, PROTOCOL_RANGE_PROOF};
+use crate::util::{g1_equals, scalar_from_u64};
+use crate::CredentialError;
+
+/// Most bits a range may span, which covers any `u64` offset.
+pub const MAX_RANGE_BITS: u8 = 64;
+
+const RANGE_GENERATOR_SEED: &[u8] = b"anonymous-credentials range proof generator";
+
+trait RangeSizes: PairingBackend {
+    // Statement, V, A, S, T1, T2, tau_x, mu, t, a, b and s_gamma, without the
+    // rounds of the inner product argument
+    const RANGE_PROOF_BASE_SIZE: usize = 10 + Self::G1_SIZE * 5 + BIG_SIZE * 6;
+    // L and R of one round
+    const RANGE_ROUND_SIZE: usize = Self::G1_SIZE * 2;
+}
+
+impl<B: PairingBackend> RangeSizes for B {}
+
+/// Statement that the hidden attribute at `index` lies in
+/// [min, min + 2^bits). For a lower bound such as "version >= N", use
+/// `bits` wide enough for any plausible value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttributeRange {
+    pub index: usize,
+    pub min: u64,
+    pub bits: u8,
+}
+
+impl AttributeRange {
+    pub fn new(index: usize, min: u64, bits: u8) -> Self {
+        Self { index, min, bits }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.bits > 0 && self.bits <= MAX_RANGE_BITS && self.index <= u8::MAX as usize
+    }
+
+    /// Rounds of the inner product argument.
+    fn rounds(&self) -> usize {
+        (self.bits as usize).next_power_of_two().trailing_zeros() as usize
+    }
+
+    fn to_bytes(self) -> [u8; 10] {
+        let mut statement = [0u8; 10];
+        statement[0] = self.index as u8;
+        statement[1..9].copy_from_slice(&self.min.to_be_bytes());
+        statement[9] = self.bits;
+        statement
+    }
+
+    /// Offset of `value` from `min`, if `value` lies in the range.
+    fn offset<B: PairingBackend>(&self, value: &B::Scalar) -> Option<u64> {
+        let mut bytes = [0u8; BIG_SIZE];
+        B::scalar_to_bytes(value, &mut bytes);
+        let (high, low) = bytes.split_at(BIG_SIZE - 8);
+        if high.iter().any(|&byte| byte != 0) {
+            return None;
+        }
+        let offset = u64::from_be_bytes(low.try_into().unwrap()).checked_sub(self.min)?;
+        match self.bits {
+            MAX_RANGE_BITS => Some(offset),
+            bits => (offset >> bits == 0).then_some(offset),
+        }
+    }
+}
+
+/// Bulletproofs range proof that V commits to a value in [0, 2^bits).
+#[derive(Clone)]
+struct Bulletproof<B: PairingBackend> {
+    a: B::G1,
+    s: B::G1,
+    t1: B::G1,
+    t2: B::G1,
+    tau_x: B::Scalar,
+    mu: B::Scalar,
+    t: B::Scalar,
+    // L and R of each round of the inner product argument
+    rounds: Vec<(B::G1, B::G1)>,
+    a_final: B::Scalar,
+    b_final: B::Scalar,
+}
+
+pub(crate) struct RangeProof<B: PairingBackend> {
+    pub(crate) range: AttributeRange,
+    v: B::G1,
+    bulletproof: Bulletproof<B>,
+    s_gamma: B::Scalar,
+}
+
+/// Points of a range proof absorbed into the signature's transcript.
+pub(crate) struct RangeCommitment<B: PairingBackend> {
+    range: AttributeRange,
+    v: B::G1,
+    bulletproof: Bulletproof<B>,
+    opening: B::G1,
+}
+
+impl<B: PairingBackend> RangeCommitment<B> {
+    pub(crate) fn append_to(&self, transcript: &mut Transcript<B>) {
+        transcript.append_bytes(b"range", &self.range.to_bytes());
+        transcript.append_ecp(b"v", &self.v);
+        let proof = &self.bulletproof;
+        for (label, point) in [
+            (&b"a"[..], &proof.a),
+            (b"s", &proof.s),
+            (b"t1", &proof.t1),
+            (b"t2", &proof.t2),
+        ] {
+            transcript.append_ecp(label, point);
+        }
+        for (l, r) in &proof.rounds {
+            transcript.append_ecp(b"l", l);
+            transcript.append_ecp(b"r", r);
+        }
+        for (label, scalar) in [
+            (&b"tau_x"[..], &proof.tau_x),
+            (b"mu", &proof.mu),
+            (b"t", &proof.t),
+            (b"a_final", &proof.a_final),
+            (b"b_final", &proof.b_final),
+        ] {
+            transcript.append_bytes(label, &scalar_bytes::<B>(scalar));
+        }
+        transcript.append_ecp(b"opening", &self.opening);
+    }
+}
+
+/// Prover state between committing and receiving the challenge.
+pub(crate) struct RangeWitness<B: PairingBackend> {
+    commitment: RangeCommitment<B>,
+    gamma: B::Scalar,
+    nonce_gamma: B::Scalar,
+}
+
+/// Generators of a range proof: H for blinding, the vectors G_i and H_i
+/// and U for the inner product.
+struct Generators<B: PairingBackend> {
+    h: B::G1,
+    g_vec: Vec<B::G1>,
+    h_vec: Vec<B::G1>,
+    u: B::G1,
+}
+
+impl<B: PairingBackend> Generators<B> {
+    fn new(suite: Ciphersuite, len: usize) -> Self {
+        let generator = |label: &[u8], i: usize| {
+            let mut seed = RANGE_GENERATOR_SEED.to_vec();
+            seed.extend_from_slice(label);
+            seed.push(i as u8);
+            suite.hash_to_g1::<B>(&seed)
+        };
+        Self {
+            h: suite.hash_to_g1::<B>(RANGE_GENERATOR_SEED),
+            g_vec: (0..len).map(|i| generator(b" g", i)).collect(),
+            h_vec: (0..len).map(|i| generator(b" h", i)).collect(),
+            u: generator(b" u", 0),
+        }
+    }
+}
+
+/// Fiat-Shamir challenges of a range proof, each hashed from the previous
+/// one and whatever the prover sent since.
+struct Challenges<B: PairingBackend> {
+    suite: Ciphersuite,
+    state: [u8; BIG_SIZE],
+    backend: PhantomData<B>,
+}
+
+impl<B: PairingBackend> Challenges<B> {
+    fn new(suite: Ciphersuite, range: &AttributeRange, v: &B::G1) -> Self {
+        let mut transcript = Transcript::<B>::labeled(suite, PROTOCOL_RANGE_PROOF);
+        transcript.append_bytes(b"range", &range.to_bytes());
+        transcript.append_ecp(b"v", v);
+        Self {
+            suite,
+            state: scalar_bytes::<B>(&transcript.challenge()),
+            backend: PhantomData,
+        }
+    }
+
+    fn next(&mut self, points: &[&B::G1], scalars: &[&B::Scalar]) -> B::Scalar {
+        let mut transcript = Transcript::<B>::labeled(self.suite, PROTOCOL_RANGE_PROOF);
+        transcript.append_bytes(b"state", &self.state);
+        for point in points {
+            transcript.append_ecp(b"point", point);
+        }
+        for scalar in scalars {
+            transcript.append_bytes(b"scalar", &scalar_bytes::<B>(scalar));
+        }
+        let challenge = transcript.challenge();
+        self.state = scalar_bytes::<B>(&challenge);
+        challenge
+    }
+}
+
+fn scalar_bytes<B: PairingBackend>(scalar: &B::Scalar) -> [u8; BIG_SIZE] {
+    let mut bytes = [0u8; BIG_SIZE];
+    B::scalar_to_bytes(scalar, &mut bytes);
+    bytes
+}
+
+fn g1_sub<B: PairingBackend>(a: &B::G1, b: &B::G1) -> B::G1 {
+    B::g1_add(a, &B::g1_neg(b))
+}
+
+fn scalar_sub<B: PairingBackend>(a: &B::Scalar, b: &B::Scalar) -> B::Scalar {
+    B::scalar_add(a, &B::scalar_neg(b))
+}
+
+fn zero<B: PairingBackend>() -> B::Scalar {
+    scalar_from_u64::<B>(0)
+}
+
+/// [1, x, x^2, ...] of length `len`.
+fn powers<B: PairingBackend>(x: &B::Scalar, len: usize) -> Vec<B::Scalar> {
+    let mut powers = Vec::with_capacity(len);
+    let mut power = scalar_from_u64::<B>(1);
+    for _ in 0..len {
+        powers.push(power);
+        power = B::scalar_mul(&power, x);
+    }
+    powers
+}
+
+fn inner_product<B: PairingBackend>(a: &[B::Scalar], b: &[B::Scalar]) -> B::Scalar {
+    a.iter().zip(b).fold(zero::<B>(), |sum, (a, b)| {
+        B::scalar_add(&sum, &B::scalar_mul(a, b))
+    })
+}
+
+fn multi_mul<B: PairingBackend>(points: &[B::G1], scalars: &[B::Scalar]) -> B::G1 {
+    points
+        .iter()
+        .zip(scalars)
+        .fold(B::g1_infinity(), |sum, (point, scalar)| {
+            B::g1_add(&sum, &B::g1_mul(point, scalar))
+        })
+}
+
+/// a * x + b * y for each pair of entries.
+fn fold_scalars<B: PairingBackend>(
+    a: &[B::Scalar],
+    x: &B::Scalar,
+    b: &[B::Scalar],
+    y: &B::Scalar,
+) -> Vec<B::Scalar> {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| B::scalar_add(&B::scalar_mul(a, x), &B::scalar_mul(b, y)))
+        .collect()
+}
+
+fn fold_points<B: PairingBackend>(
+    a: &[B::G1],
+    x: &B::Scalar,
+    b: &[B::G1],
+    y: &B::Scalar,
+) -> Vec<B::G1> {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| B::g1_add(&B::g1_mul(a, x), &B::g1_mul(b, y)))
+        .collect()
+}
+
+/// Commits to the range of `value`, whose nonce in the proof over the
+/// hidden attributes is `nonce`, and proves the range. Fails if the range
+/// is malformed or `value` lies outside of it.
+pub(crate) fn commit_range<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    range: AttributeRange,
+    value: &B::Scalar,
+    nonce: &B::Scalar,
+) -> Result<RangeWitness<B>, CredentialError> {
+    if !range.is_valid() {
+        return Err(CredentialError::BadAttributes);
+    }
+    let offset = range
+        .offset::<B>(value)
+        .ok_or(CredentialError::AttributeOutOfRange)?;
+
+    let n = range.bits as usize;
+    let g = B::g1_generator();
+    let generators = Generators::<B>::new(suite, n.next_power_of_two());
+    let h = &generators.h;
+
+    let gamma = B::scalar_random(rng);
+    let v = B::g1_add(
+        &B::g1_mul(&g, &scalar_from_u64::<B>(offset)),
+        &B::g1_mul(h, &gamma),
+    );
+
+    // a_L holds the bits of the offset and a_R = a_L - 1
+    let one = scalar_from_u64::<B>(1);
+    let a_l: Vec<_> = (0..n)
+        .map(|j| scalar_from_u64::<B>((offset >> j) & 1))
+        .collect();
+    let a_r: Vec<_> = a_l.iter().map(|bit| scalar_sub::<B>(bit, &one)).collect();
+    let alpha = B::scalar_random(rng);
+    let a = B::g1_add(
+        &B::g1_mul(h, &alpha),
+        &B::g1_add(
+            &multi_mul::<B>(&generators.g_vec, &a_l),
+            &multi_mul::<B>(&generators.h_vec, &a_r),
+        ),
+    );
+    let s_l: Vec<_> = (0..n).map(|_| B::scalar_random(rng)).collect();
+    let s_r: Vec<_> = (0..n).map(|_| B::scalar_random(rng)).collect();
+    let rho = B::scalar_random(rng);
+    let s = B::g1_add(
+        &B::g1_mul(h, &rho),
+        &B::g1_add(
+            &multi_mul::<B>(&generators.g_vec, &s_l),
+            &multi_mul::<B>(&generators.h_vec, &s_r),
+        ),
+    );
+
+    let mut challenges = Challenges::<B>::new(suite, &range, &v);
+    let y = challenges.next(&[&a, &s], &[]);
+    let z = challenges.next(&[], &[]);
+    let z2 = B::scalar_mul(&z, &z);
+    let y_n = powers::<B>(&y, n);
+    let two_n = powers::<B>(&scalar_from_u64::<B>(2), n);
+
+    // l(X) = l0 + l1 * X and r(X) = r0 + r1 * X, with t(X) = <l(X), r(X)>
+    let l0: Vec<_> = a_l.iter().map(|bit| scalar_sub::<B>(bit, &z)).collect();
+    let r0: Vec<_> = (0..n)
+        .map(|i| {
+            let shifted = B::scalar_add(&a_r[i], &z);
+            B::scalar_add(
+                &B::scalar_mul(&y_n[i], &shifted),
+                &B::scalar_mul(&z2, &two_n[i]),
+            )
+        })
+        .collect();
+    let r1: Vec<_> = (0..n).map(|i| B::scalar_mul(&y_n[i], &s_r[i])).collect();
+    let t1 = B::scalar_add(
+        &inner_product::<B>(&l0, &r1),
+        &inner_product::<B>(&s_l, &r0),
+    );
+    let t2 = inner_product::<B>(&s_l, &r1);
+    let tau1 = B::scalar_random(rng);
+    let tau2 = B::scalar_random(rng);
+    let commit = |value: &B::Scalar, blinding: &B::Scalar| {
+        B::g1_add(&B::g1_mul(&g, value), &B::g1_mul(h, blinding))
+    };
+    let big_t1 = commit(&t1, &tau1);
+    let big_t2 = commit(&t2, &tau2);
+
+    let x = challenges.next(&[&big_t1, &big_t2], &[]);
+    let mut l = fold_scalars::<B>(&l0, &one, &s_l, &x);
+    let mut r = fold_scalars::<B>(&r0, &one, &r1, &x);
+    let t = inner_product::<B>(&l, &r);
+    let x2 = B::scalar_mul(&x, &x);
+    let tau_x = B::scalar_add(
+        &B::scalar_add(&B::scalar_mul(&tau2, &x2), &B::scalar_mul(&tau1, &x)),
+        &B::scalar_mul(&z2, &gamma),
+    );
+    let mu = B::scalar_add(&alpha, &B::scalar_mul(&rho, &x));
+
+    // Inner product argument for <l, r> = t over G_i and H'_i = y^-i * H_i,
+    // with the vectors padded by zeros to a power of two
+    let w = challenges.next(&[], &[&tau_x, &mu, &t]);
+    let u = B::g1_mul(&generators.u, &w);
+    let mut g_vec = generators.g_vec;
+    let mut h_vec = scaled_h_vec::<B>(generators.h_vec, &y, n);
+    l.resize(g_vec.len(), zero::<B>());
+    r.resize(g_vec.len(), zero::<B>());
+    let mut rounds = Vec::with_capacity(range.rounds());
+    while l.len() > 1 {
+        let half = l.len() / 2;
+        let (l_lo, l_hi) = l.split_at(half);
+        let (r_lo, r_hi) = r.split_at(half);
+        let (g_lo, g_hi) = g_vec.split_at(half);
+        let (h_lo, h_hi) = h_vec.split_at(half);
+        let big_l = B::g1_add(
+            &B::g1_add(&multi_mul::<B>(g_hi, l_lo), &multi_mul::<B>(h_lo, r_hi)),
+            &B::g1_mul(&u, &inner_product::<B>(l_lo, r_hi)),
+        );
+        let big_r = B::g1_add(
+            &B::g1_add(&multi_mul::<B>(g_lo, l_hi), &multi_mul::<B>(h_hi, r_lo)),
+            &B::g1_mul(&u, &inner_product::<B>(l_hi, r_lo)),
+        );
+
+        let e = challenges.next(&[&big_l, &big_r], &[]);
+        let e_inv = B::scalar_inv(&e);
+        let folded = (
+            fold_scalars::<B>(l_lo, &e, l_hi, &e_inv),
+            fold_scalars::<B>(r_lo, &e_inv, r_hi, &e),
+            fold_points::<B>(g_lo, &e_inv, g_hi, &e),
+            fold_points::<B>(h_lo, &e, h_hi, &e_inv),
+        );
+        (l, r, g_vec, h_vec) = folded;
+        rounds.push((big_l, big_r));
+    }
+
+    let nonce_gamma = B::scalar_random(rng);
+    let opening = B::g1_add(&B::g1_mul(&g, nonce), &B::g1_mul(h, &nonce_gamma));
+
+    Ok(RangeWitness {
+        commitment: RangeCommitment {
+            range,
+            v,
+            bulletproof: Bulletproof {
+                a,
+                s,
+                t1: big_t1,
+                t2: big_t2,
+                tau_x,
+                mu,
+                t,
+                rounds,
+                a_final: l[0],
+                b_final: r[0],
+            },
+            opening,
+        },
+        gamma,
+        nonce_gamma,
+    })
+}
+
+/// H'_i = y^-i * H_i for the first `n` generators, leaving the padding.
+fn scaled_h_vec<B: PairingBackend>(mut h_vec: Vec<B::G1>, y: &B::Scalar, n: usize) -> Vec<B::G1> {
+    let y_inv = powers::<B>(&B::scalar_inv(y), n);
+    for (h, y_inv) in h_vec.iter_mut().zip(&y_inv) {
+        *h = B::g1_mul(h, y_inv);
+    }
+    h_vec
+}
+
+impl<B: PairingBackend> RangeWitness<B> {
+    pub(crate) fn commitment(&self) -> &RangeCommitment<B> {
+        &self.commitment
+    }
+
+    pub(crate) fn respond(self, c: &B::Scalar) -> RangeProof<B> {
+        RangeProof {
+            range: self.commitment.range,
+            v: self.commitment.v,
+            bulletproof: self.commitment.bulletproof,
+            s_gamma: B::scalar_add(&self.nonce_gamma, &B::scalar_mul(c, &self.gamma)),
+        }
+    }
+}
+
+impl<B: PairingBackend> RangeProof<B> {
+    /// Checks the range proof and recomputes the commitments from the
+    /// responses, given the challenge c and the response `s` of the
+    /// attribute in the hidden attribute proof.
+    pub(crate) fn commitment(
+        &self,
+        suite: Ciphersuite,
+        c: &B::Scalar,
+        s: &B::Scalar,
+    ) -> Result<RangeCommitment<B>, CredentialError> {
+        let g = B::g1_generator();
+        let generators =
+            Generators::<B>::new(suite, (self.range.bits as usize).next_power_of_two());
+        if !self.bulletproof_is_valid(suite, &g, generators) {
+            return Err(CredentialError::SignatureValidation);
+        }
+
+        // s * G + s_gamma * H - c * (min * G + V)
+        let h = suite.hash_to_g1::<B>(RANGE_GENERATOR_SEED);
+        let total = B::g1_add(
+            &B::g1_mul(&g, &scalar_from_u64::<B>(self.range.min)),
+            &self.v,
+        );
+        let opening = g1_sub::<B>(
+            &B::g1_add(&B::g1_mul(&g, s), &B::g1_mul(&h, &self.s_gamma)),
+            &B::g1_mul(&total, c),
+        );
+
+        Ok(RangeCommitment {
+            range: self.range,
+            v: self.v.clone(),
+            bulletproof: self.bulletproof.clone(),
+            opening,
+        })
+    }
+
+    fn bulletproof_is_valid(
+        &self,
+        suite: Ciphersuite,
+        g: &B::G1,
+        generators: Generators<B>,
+    ) -> bool {
+        let proof = &self.bulletproof;
+        let n = self.range.bits as usize;
+        let h = &generators.h;
+
+        let mut challenges = Challenges::<B>::new(suite, &self.range, &self.v);
+        let y = challenges.next(&[&proof.a, &proof.s], &[]);
+        let z = challenges.next(&[], &[]);
+        let x = challenges.next(&[&proof.t1, &proof.t2], &[]);
+        let w = challenges.next(&[], &[&proof.tau_x, &proof.mu, &proof.t]);
+        let e: Vec<_> = proof
+            .rounds
+            .iter()
+            .map(|(l, r)| challenges.next(&[l, r], &[]))
+            .collect();
+
+        // t * G + tau_x * H = z^2 * V + delta(y, z) * G + x * T1 + x^2 * T2
+        let z2 = B::scalar_mul(&z, &z);
+        let z3 = B::scalar_mul(&z2, &z);
+        let x2 = B::scalar_mul(&x, &x);
+        let y_n = powers::<B>(&y, n);
+        let two_n = powers::<B>(&scalar_from_u64::<B>(2), n);
+        let sum = |values: &[B::Scalar]| {
+            values
+                .iter()
+                .fold(zero::<B>(), |sum, value| B::scalar_add(&sum, value))
+        };
+        let delta = scalar_sub::<B>(
+            &B::scalar_mul(&scalar_sub::<B>(&z, &z2), &sum(&y_n)),
+            &B::scalar_mul(&z3, &sum(&two_n)),
+        );
+        let lhs = B::g1_add(&B::g1_mul(g, &proof.t), &B::g1_mul(h, &proof.tau_x));
+        let rhs = B::g1_add(
+            &B::g1_add(&B::g1_mul(&self.v, &z2), &B::g1_mul(g, &delta)),
+            &B::g1_add(&B::g1_mul(&proof.t1, &x), &B::g1_mul(&proof.t2, &x2)),
+        );
+        if !g1_equals::<B>(&lhs, &rhs) {
+            return false;
+        }
+
+        // P = A + x * S - z * sum(G_i) + sum((z * y^i + z^2 * 2^i) * H'_i)
+        //     - mu * H + t * U, folded with every round's L and R
+        let u = B::g1_mul(&generators.u, &w);
+        let h_vec = scaled_h_vec::<B>(generators.h_vec, &y, n);
+        let h_coefficients: Vec<_> = (0..n)
+            .map(|i| B::scalar_add(&B::scalar_mul(&z, &y_n[i]), &B::scalar_mul(&z2, &two_n[i])))
+            .collect();
+        let mut p = B::g1_add(&proof.a, &B::g1_mul(&proof.s, &x));
+        let g_sum = generators.g_vec[..n]
+            .iter()
+            .fold(B::g1_infinity(), |sum, point| B::g1_add(&sum, point));
+        p = B::g1_add(&p, &B::g1_mul(&g_sum, &B::scalar_neg(&z)));
+        p = B::g1_add(&p, &multi_mul::<B>(&h_vec, &h_coefficients));
+        p = g1_sub::<B>(&p, &B::g1_mul(h, &proof.mu));
+        p = B::g1_add(&p, &B::g1_mul(&u, &proof.t));
+        for ((l, r), e) in proof.rounds.iter().zip(&e) {
+            let e2 = B::scalar_mul(e, e);
+            p = B::g1_add(&p, &B::g1_mul(l, &e2));
+            p = B::g1_add(&p, &B::g1_mul(r, &B::scalar_inv(&e2)));
+        }
+
+        // The generators fold to sum(s_i * G_i) and sum(s_i^-1 * H'_i), where
+        // s_i takes e_j or e_j^-1 by bit j of i, counted from the top
+        let e_inv: Vec<_> = e.iter().map(B::scalar_inv).collect();
+        let len = h_vec.len();
+        let (s_vec, s_inv): (Vec<_>, Vec<_>) = (0..len)
+            .map(|i| {
+                (0..e.len()).fold(
+                    (scalar_from_u64::<B>(1), scalar_from_u64::<B>(1)),
+                    |(s, s_inv), j| {
+                        let high = (i >> (e.len() - 1 - j)) & 1 == 1;
+                        let (factor, inverse) = match high {
+                            true => (&e[j], &e_inv[j]),
+                            false => (&e_inv[j], &e[j]),
+                        };
+                        (B::scalar_mul(&s, factor), B::scalar_mul(&s_inv, inverse))
+                    },
+                )
+            })
+            .unzip();
+        let ab = B::scalar_mul(&proof.a_final, &proof.b_final);
+        let folded = B::g1_add(
+            &B::g1_add(
+                &B::g1_mul(&multi_mul::<B>(&generators.g_vec, &s_vec), &proof.a_final),
+                &B::g1_mul(&multi_mul::<B>(&h_vec, &s_inv), &proof.b_final),
+            ),
+            &B::g1_mul(&u, &ab),
+        );
+        g1_equals::<B>(&p, &folded)
+    }
+
+    pub(crate) fn encoded_size(&self) -> usize {
+        B::RANGE_PROOF_BASE_SIZE + self.bulletproof.rounds.len() * B::RANGE_ROUND_SIZE
+    }
+
+    pub(crate) fn push_to(&self, out: &mut Vec<u8>) {
+        let proof = &self.bulletproof;
+        out.extend_from_slice(&self.range.to_bytes());
+        for point in [&self.v, &proof.a, &proof.s, &proof.t1, &proof.t2] {
+            push_ecp::<B>(out, point);
+        }
+        for (l, r) in &proof.rounds {
+            push_ecp::<B>(out, l);
+            push_ecp::<B>(out, r);
+        }
+        for scalar in [
+            &proof.tau_x,
+            &proof.mu,
+            &proof.t,
+            &proof.a_final,
+            &proof.b_final,
+            &self.s_gamma,
+        ] {
+            push_scalar::<B>(out, scalar);
+        }
+    }
+
+    /// Parses one proof from the front of `bytes`, returning the rest.
+    pub(crate) fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), CredentialError> {
+        if bytes.len() < 10 {
+            return Err(CredentialError::BadAttributes);
+        }
+        let range = AttributeRange {
+            index: bytes[0] as usize,
+            min: u64::from_be_bytes(bytes[1..9].try_into().unwrap()),
+            bits: bytes[9],
+        };
+        if !range.is_valid() {
+            return Err(CredentialError::BadAttributes);
+        }
+        let size = B::RANGE_PROOF_BASE_SIZE + range.rounds() * B::RANGE_ROUND_SIZE;
+        if bytes.len() < size {
+            return Err(CredentialError::BadAttributes);
+        }
+
+        let mut points = bytes[10..size - BIG_SIZE * 6]
+            .chunks(B::G1_SIZE)
+            .map(ecp_from_bytes::<B>);
+        let mut point = || points.next().unwrap();
+        let (v, a, s, t1, t2) = (point()?, point()?, point()?, point()?, point()?);
+        let rounds = (0..range.rounds())
+            .map(|_| Ok((point()?, point()?)))
+            .collect::<Result<_, CredentialError>>()?;
+        let mut scalars = bytes[size - BIG_SIZE * 6..size]
+            .chunks(BIG_SIZE)
+            .map(big_from_bytes::<B>);
+        let mut scalar = || scalars.next().unwrap();
+
+        let bulletproof = Bulletproof {
+            a,
+            s,
+            t1,
+            t2,
+            tau_x: scalar()?,
+            mu: scalar()?,
+            t: scalar()?,
+            rounds,
+            a_final: scalar()?,
+            b_final: scalar()?,
+        };
+        let proof = Self {
+            range,
+            v,
+            bulletproof,
+            s_gamma: scalar()?,
+        };
+        Ok((proof, &bytes[size..]))
+    }
+}
@@ -0,0 +1,136 @@
+use alloc::vec::Vec;
+use brave_miracl::rand::RAND;
+
+use super::range::{commit_range, RangeWitness};
+use super::verify::attribute_challenge;
+use super::{AttributeCredentials, AttributeRange, AttributeSignature, SignatureAttribute};
+use crate::backend::PairingBackend;
+use crate::ciphersuite::Ciphersuite;
+use crate::data::CredentialBIG;
+use crate::sign::sign_randomized;
+use crate::{CredentialError, Result};
+
+/// Signs like `sign`, revealing the attributes at the indices in `disclose`
+/// and proving knowledge of the others.
+pub fn sign_with_attributes<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    gsk: &CredentialBIG<B>,
+    credentials: &AttributeCredentials<B>,
+    msg: &[u8],
+    bsn: &[u8],
+    disclose: &[usize],
+) -> Result<AttributeSignature<B>> {
+    sign_with_attribute_ranges(rng, suite, gsk, credentials, msg, bsn, disclose, &[])
+}
+
+/// Like `sign_with_attributes`, also proving that the hidden attributes
+/// named by `ranges` lie in those ranges. Fails with
+/// `CredentialError::AttributeOutOfRange` if one of them does not.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_with_attribute_ranges<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    gsk: &CredentialBIG<B>,
+    credentials: &AttributeCredentials<B>,
+    msg: &[u8],
+    bsn: &[u8],
+    disclose: &[usize],
+    ranges: &[AttributeRange],
+) -> Result<AttributeSignature<B>> {
+    let count = credentials.attributes.len();
+    if disclose.iter().any(|&i| i >= count)
+        || ranges
+            .iter()
+            .any(|range| range.index >= count || disclose.contains(&range.index))
+        || ranges.len() > u8::MAX as usize
+    {
+        return Err(CredentialError::BadAttributes);
+    }
+
+    // Attribute points are randomized by the same factor as A, B, C and D
+    let r = B::scalar_random(rng);
+    let base = sign_randomized::<B>(
+        rng,
+        suite,
+        gsk,
+        &credentials.base,
+        &r,
+        &suite.hash(msg),
+        bsn,
+    );
+
+    let attributes: Vec<SignatureAttribute<B>> = credentials
+        .attributes
+        .iter()
+        .enumerate()
+        .map(|(i, attribute)| SignatureAttribute {
+            a: B::g1_mul(&attribute.a, &r),
+            b: B::g1_mul(&attribute.b, &r),
+            disclosed: disclose.contains(&i).then_some(attribute.value),
+        })
+        .collect();
+
+    // Schnorr proof of knowledge of the hidden m_i in sum(m_i * B_i)
+    let hidden_values: Vec<_> = credentials
+        .attributes
+        .iter()
+        .zip(&attributes)
+        .enumerate()
+        .filter(|(_, (_, randomized))| randomized.disclosed.is_none())
+        .map(|(i, (attribute, randomized))| (i, attribute.value, &randomized.b))
+        .collect();
+    let nonces: Vec<_> = hidden_values
+        .iter()
+        .map(|_| B::scalar_random(rng))
+        .collect();
+
+    let mut hidden = B::g1_infinity();
+    let mut commitment = B::g1_infinity();
+    for ((_, value, b), nonce) in hidden_values.iter().zip(&nonces) {
+        hidden = B::g1_add(&hidden, &B::g1_mul(b, value));
+        commitment = B::g1_add(&commitment, &B::g1_mul(b, nonce));
+    }
+
+    // Range proofs reuse the nonce of their attribute
+    let witnesses = ranges
+        .iter()
+        .map(|range| {
+            let position = hidden_values
+                .iter()
+                .position(|(i, _, _)| *i == range.index)
+                .unwrap();
+            let (_, value, _) = &hidden_values[position];
+            commit_range::<B>(rng, suite, *range, value, &nonces[position])
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let c = attribute_challenge::<B>(
+        suite,
+        msg,
+        bsn,
+        &base,
+        &attributes,
+        &hidden,
+        &commitment,
+        witnesses.iter().map(RangeWitness::commitment),
+    );
+    let s = hidden_values
+        .iter()
+        .zip(&nonces)
+        .map(|((_, value, _), nonce)| B::scalar_add(&B::scalar_mul(&c, value), nonce))
+        .collect();
+    let ranges = witnesses
+        .into_iter()
+        .map(|witness| witness.respond(&c))
+        .collect();
+
+    Ok(AttributeSignature {
+        base,
+        attributes,
+        hidden,
+        c,
+        s,
+        ranges,
+    })
+}
@@ -0,0 +1,450 @@
+use brave_miracl::rand::RAND;
+
+use super::*;
+use crate::backend::{PairingBackend, BIG_SIZE};
+use crate::ciphersuite::{Ciphersuite, Curve};
+use crate::data::CredentialBIG;
+use crate::issuer::{Fixture, Issuer};
+use crate::join::start_join;
+use crate::Bn254 as Backend;
+use crate::{
+    Attribute, AttributeCredentials, AttributeGroupPublicKey, AttributeSignature, StartJoinResult,
+    ECP_SIZE, SIGNATURE_SIZE,
+};
+
+const MSG: &[u8] = b"message";
+const BSN: &[u8] = b"basename";
+
+fn values() -> Vec<Attribute> {
+    [120u64, 7, 0xdead_beef]
+        .into_iter()
+        .map(Attribute::from)
+        .collect()
+}
+
+fn fixture(suite: Ciphersuite) -> Fixture<AttributeCredentials> {
+    Fixture::new_with_attributes(suite, &values())
+}
+
+impl Fixture<AttributeCredentials> {
+    fn sign(&mut self, disclose: &[usize]) -> AttributeSignature {
+        sign_with_attributes(
+            &mut self.rng,
+            self.suite,
+            &self.gsk,
+            &self.credentials,
+            MSG,
+            BSN,
+            disclose,
+        )
+        .unwrap()
+    }
+
+    fn verify(&self, signature: &AttributeSignature) -> crate::Result<()> {
+        verify_with_attributes(
+            self.suite,
+            &self.issuer.attribute_public_key(),
+            signature,
+            MSG,
+            BSN,
+        )
+    }
+}
+
+fn assert_signature_rejected(result: crate::Result<()>) {
+    assert!(matches!(result, Err(CredentialError::SignatureValidation)));
+}
+
+#[test]
+fn test_selective_disclosure() {
+    for suite in Curve::Bn254.suites() {
+        let mut fixture = fixture(suite);
+        assert_eq!(fixture.credentials.attributes(), values());
+
+        for disclose in [&[][..], &[1], &[0, 2], &[0, 1, 2]] {
+            let signature = fixture.sign(disclose);
+            assert!(fixture.verify(&signature).is_ok());
+
+            let disclosed: Vec<_> = disclose.iter().map(|&i| (i, values()[i])).collect();
+            assert_eq!(signature.disclosed(), disclosed);
+        }
+    }
+}
+
+#[test]
+fn test_no_attributes() {
+    let mut rng = RAND::new();
+    rng.seed(1, &[0]);
+    let suite = Ciphersuite::default();
+
+    let issuer = Issuer::new_with_attributes(&mut rng, suite, 0);
+    let StartJoinResult { gsk, join_msg } = start_join(&mut rng, suite, b"challenge");
+    let response = issuer
+        .join_with_attributes(&mut rng, b"challenge", &join_msg, &[])
+        .unwrap();
+    let key = issuer.attribute_public_key();
+    let credentials = finish_join_with_attributes(suite, &key, &gsk, response).unwrap();
+
+    let signature =
+        sign_with_attributes(&mut rng, suite, &gsk, &credentials, MSG, BSN, &[]).unwrap();
+    assert!(verify_with_attributes(suite, &key, &signature, MSG, BSN).is_ok());
+    assert!(crate::verify(suite, key.base(), &signature.base, MSG, BSN).is_ok());
+}
+
+#[test]
+fn test_tampered_disclosed_value() {
+    let mut fixture = fixture(Ciphersuite::default());
+
+    let mut signature = fixture.sign(&[0]);
+    signature.attributes[0].disclosed = Some(Attribute::from(121).0);
+    assert_signature_rejected(fixture.verify(&signature));
+
+    // Revealing an attribute the signer chose to hide
+    let mut signature = fixture.sign(&[]);
+    signature.attributes[1].disclosed = Some(values()[1].0);
+    assert!(fixture.verify(&signature).is_err());
+
+    // Attribute points from another signature
+    let mut signature = fixture.sign(&[0]);
+    signature.attributes[0].a = fixture.sign(&[0]).attributes[0].a.clone();
+    assert_signature_rejected(fixture.verify(&signature));
+}
+
+#[test]
+fn test_tampered_hidden_proof() {
+    let mut fixture = fixture(Ciphersuite::default());
+
+    let mut signature = fixture.sign(&[1]);
+    signature.s.swap(0, 1);
+    assert_signature_rejected(fixture.verify(&signature));
+
+    let mut signature = fixture.sign(&[1]);
+    signature.hidden = fixture.sign(&[1]).hidden;
+    assert_signature_rejected(fixture.verify(&signature));
+
+    // The proof is bound to the message
+    let signature = fixture.sign(&[1]);
+    let key = fixture.issuer.attribute_public_key();
+    assert_signature_rejected(verify_with_attributes(
+        fixture.suite,
+        &key,
+        &signature,
+        b"other message",
+        BSN,
+    ));
+}
+
+#[test]
+fn test_mismatched_ciphersuite() {
+    let mut fixture = fixture(Ciphersuite::Bn254Sha256V1);
+    let signature = fixture.sign(&[0]);
+    assert!(matches!(
+        verify_with_attributes(
+            Ciphersuite::Bls12381Sha256V1,
+            &fixture.issuer.attribute_public_key(),
+            &signature,
+            MSG,
+            BSN,
+        ),
+        Err(CredentialError::UnknownCiphersuite)
+    ));
+}
+
+#[test]
+fn test_zero_gsk_signature() {
+    let mut fixture = fixture(Ciphersuite::default());
+    let key = fixture.issuer.attribute_public_key();
+    let r = Backend::scalar_random(&mut fixture.rng);
+    let response = fixture.issuer.issue_with_attributes(
+        &mut fixture.rng,
+        &Backend::g1_infinity(),
+        &r,
+        &values(),
+    );
+    let gsk = CredentialBIG(Backend::scalar_from_bytes(&[0u8; BIG_SIZE]));
+    let credentials = finish_join_with_attributes(fixture.suite, &key, &gsk, response).unwrap();
+
+    let signature = sign_with_attributes(
+        &mut fixture.rng,
+        fixture.suite,
+        &gsk,
+        &credentials,
+        MSG,
+        BSN,
+        &[0],
+    )
+    .unwrap();
+    assert_signature_rejected(fixture.verify(&signature));
+}
+
+#[test]
+fn test_tampered_join_response() {
+    let mut fixture = fixture(Ciphersuite::default());
+    let key = fixture.issuer.attribute_public_key();
+    let StartJoinResult { gsk, join_msg } =
+        start_join(&mut fixture.rng, fixture.suite, b"challenge");
+    let response = |rng: &mut RAND| {
+        fixture
+            .issuer
+            .join_with_attributes(rng, b"challenge", &join_msg, &values())
+            .unwrap()
+    };
+
+    let mut tampered = response(&mut fixture.rng);
+    tampered.attributes[2].value = Attribute::from(0).0;
+    assert!(matches!(
+        finish_join_with_attributes(fixture.suite, &key, &gsk, tampered),
+        Err(CredentialError::JoinResponseValidation)
+    ));
+
+    let mut tampered = response(&mut fixture.rng);
+    tampered.attributes.swap(0, 1);
+    assert!(matches!(
+        finish_join_with_attributes(fixture.suite, &key, &gsk, tampered),
+        Err(CredentialError::JoinResponseValidation)
+    ));
+
+    let mut tampered = response(&mut fixture.rng);
+    tampered.attributes.pop();
+    assert!(matches!(
+        finish_join_with_attributes(fixture.suite, &key, &gsk, tampered),
+        Err(CredentialError::BadAttributes)
+    ));
+
+    let mut forged_key = key.clone();
+    forged_key.attributes[0].s = Attribute::from(1).0;
+    assert!(matches!(
+        finish_join_with_attributes(fixture.suite, &forged_key, &gsk, response(&mut fixture.rng)),
+        Err(CredentialError::BadGroupPublicKey)
+    ));
+}
+
+#[test]
+fn test_mismatched_key_and_indices() {
+    let mut fixture = fixture(Ciphersuite::default());
+
+    let signature = fixture.sign(&[]);
+    let mut key = fixture.issuer.attribute_public_key();
+    key.attributes.pop();
+    assert!(matches!(
+        verify_with_attributes(fixture.suite, &key, &signature, MSG, BSN),
+        Err(CredentialError::BadAttributes)
+    ));
+
+    assert!(matches!(
+        sign_with_attributes(
+            &mut fixture.rng,
+            fixture.suite,
+            &fixture.gsk,
+            &fixture.credentials,
+            MSG,
+            BSN,
+            &[3],
+        ),
+        Err(CredentialError::BadAttributes)
+    ));
+}
+
+#[test]
+fn test_unlinkable_across_basenames() {
+    let mut fixture = fixture(Ciphersuite::default());
+
+    let signature = fixture.sign(&[0]);
+    assert_eq!(signature.nym(), fixture.sign(&[1]).nym());
+    let other = sign_with_attributes(
+        &mut fixture.rng,
+        fixture.suite,
+        &fixture.gsk,
+        &fixture.credentials,
+        MSG,
+        b"other basename",
+        &[0],
+    )
+    .unwrap();
+    assert_ne!(signature.nym(), other.nym());
+
+    // Attribute points are rerandomized along with the credentials
+    let point = |attribute: &SignatureAttribute<Backend>| {
+        let mut bytes = [0u8; ECP_SIZE];
+        Backend::g1_to_bytes(&attribute.a, &mut bytes);
+        bytes
+    };
+    assert_ne!(point(&signature.attributes[0]), point(&other.attributes[0]));
+}
+
+#[test]
+fn test_encodings() {
+    let mut fixture = fixture(Ciphersuite::default());
+
+    let key = fixture.issuer.attribute_public_key().to_bytes();
+    assert_eq!(
+        AttributeGroupPublicKey::try_from(&key[..])
+            .unwrap()
+            .to_bytes(),
+        key
+    );
+    let credentials = fixture.credentials.to_bytes();
+    assert_eq!(
+        AttributeCredentials::try_from(&credentials[..])
+            .unwrap()
+            .to_bytes(),
+        credentials
+    );
+
+    let signature = fixture.sign(&[2]);
+    let bytes = signature.to_bytes();
+    let decoded = AttributeSignature::try_from(&bytes[..]).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert!(fixture.verify(&decoded).is_ok());
+
+    for malformed in [&bytes[..bytes.len() - 1], &bytes[..SIGNATURE_SIZE]] {
+        assert!(matches!(
+            AttributeSignature::try_from(malformed),
+            Err(CredentialError::BadAttributes)
+        ));
+    }
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert!(matches!(
+        AttributeSignature::try_from(&extended[..]),
+        Err(CredentialError::BadAttributes)
+    ));
+}
+
+impl Fixture<AttributeCredentials> {
+    fn sign_with_ranges(
+        &mut self,
+        disclose: &[usize],
+        ranges: &[AttributeRange],
+    ) -> crate::Result<AttributeSignature> {
+        sign_with_attribute_ranges(
+            &mut self.rng,
+            self.suite,
+            &self.gsk,
+            &self.credentials,
+            MSG,
+            BSN,
+            disclose,
+            ranges,
+        )
+    }
+}
+
+#[test]
+fn test_range_proofs() {
+    for suite in Curve::Bn254.suites() {
+        let mut fixture = fixture(suite);
+
+        // 120 >= 30, 7 in [0, 8), and a full 64-bit range
+        let ranges = [
+            AttributeRange::new(0, 30, 16),
+            AttributeRange::new(1, 0, 3),
+            AttributeRange::new(2, 0, MAX_RANGE_BITS),
+        ];
+        let signature = fixture.sign_with_ranges(&[], &ranges).unwrap();
+        assert!(fixture.verify(&signature).is_ok());
+        assert_eq!(signature.ranges(), ranges);
+
+        // Bounds are inclusive of min and exclusive of min + 2^bits
+        let ranges = [AttributeRange::new(0, 120, 1), AttributeRange::new(1, 0, 3)];
+        let signature = fixture.sign_with_ranges(&[2], &ranges).unwrap();
+        assert!(fixture.verify(&signature).is_ok());
+    }
+}
+
+#[test]
+fn test_range_not_satisfied() {
+    let mut fixture = fixture(Ciphersuite::default());
+
+    for range in [
+        AttributeRange::new(0, 121, 16),
+        AttributeRange::new(0, 0, 6),
+        AttributeRange::new(1, 0, 2),
+    ] {
+        assert!(matches!(
+            fixture.sign_with_ranges(&[], &[range]),
+            Err(CredentialError::AttributeOutOfRange)
+        ));
+    }
+
+    // Out of bounds, disclosed or malformed statements
+    for (disclose, range) in [
+        (&[][..], AttributeRange::new(3, 0, 8)),
+        (&[0], AttributeRange::new(0, 0, 8)),
+        (&[], AttributeRange::new(0, 0, 0)),
+        (&[], AttributeRange::new(0, 0, MAX_RANGE_BITS + 1)),
+    ] {
+        assert!(matches!(
+            fixture.sign_with_ranges(disclose, &[range]),
+            Err(CredentialError::BadAttributes)
+        ));
+    }
+}
+
+#[test]
+fn test_tampered_range_proof() {
+    let mut fixture = fixture(Ciphersuite::default());
+    let range = AttributeRange::new(0, 100, 8);
+
+    // A stronger statement than the one proven
+    let mut signature = fixture.sign_with_ranges(&[], &[range]).unwrap();
+    signature.ranges[0].range.min = 110;
+    assert_signature_rejected(fixture.verify(&signature));
+
+    // The proof moved to another hidden attribute
+    let mut signature = fixture.sign_with_ranges(&[], &[range]).unwrap();
+    signature.ranges[0].range.index = 1;
+    assert_signature_rejected(fixture.verify(&signature));
+
+    // A proof from another signature
+    let mut signature = fixture.sign_with_ranges(&[], &[range]).unwrap();
+    let other = fixture.sign_with_ranges(&[], &[range]).unwrap();
+    signature.ranges = other.ranges;
+    assert_signature_rejected(fixture.verify(&signature));
+
+    // The challenge covers the proofs, so they cannot be stripped either
+    let mut signature = fixture.sign_with_ranges(&[], &[range]).unwrap();
+    signature.ranges.clear();
+    assert_signature_rejected(fixture.verify(&signature));
+
+    // Pointing the proof at a disclosed attribute
+    let mut signature = fixture
+        .sign_with_ranges(&[1], &[AttributeRange::new(0, 100, 8)])
+        .unwrap();
+    signature.ranges[0].range.index = 1;
+    assert!(matches!(
+        fixture.verify(&signature),
+        Err(CredentialError::BadAttributes)
+    ));
+}
+
+#[test]
+fn test_range_proof_encoding() {
+    let mut fixture = fixture(Ciphersuite::default());
+    let ranges = [AttributeRange::new(0, 30, 8), AttributeRange::new(2, 0, 32)];
+    let signature = fixture.sign_with_ranges(&[1], &ranges).unwrap();
+
+    let bytes = signature.to_bytes();
+    let decoded = AttributeSignature::try_from(&bytes[..]).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(decoded.ranges(), ranges);
+    assert!(fixture.verify(&decoded).is_ok());
+
+    assert!(matches!(
+        AttributeSignature::try_from(&bytes[..bytes.len() - 1]),
+        Err(CredentialError::BadAttributes)
+    ));
+
+    // Proofs grow with the logarithm of the width, by 130 bytes per round
+    // on BN254 from a base of 527
+    let (base, round) = (10 + ECP_SIZE * 5 + BIG_SIZE * 6, ECP_SIZE * 2);
+    assert_eq!((base, round), (527, 130));
+    let signature = fixture
+        .sign_with_ranges(&[], &[AttributeRange::new(0, 0, 64)])
+        .unwrap();
+    assert_eq!(signature.ranges[0].encoded_size(), base + round * 6);
+    let signature = fixture
+        .sign_with_ranges(&[], &[AttributeRange::new(1, 0, 5)])
+        .unwrap();
+    assert_eq!(signature.ranges[0].encoded_size(), base + round * 3);
+}
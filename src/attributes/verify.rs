@@ -0,0 +1,158 @@
+use super::range::RangeCommitment;
+use super::{AttributeGroupPublicKey, AttributeSignature, SignatureAttribute};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::backend::{PairingBackend, BIG_SIZE};
+use crate::ciphersuite::Ciphersuite;
+use crate::data::{EncodingSizes, Signature};
+use crate::sign::message_hash;
+use crate::transcript::{Transcript, PROTOCOL_SIGN_ATTRIBUTES};
+use crate::verify::{nym_proof_is_valid, randomized_credentials_are_valid};
+use crate::{CredentialError, Result};
+
+/// Checks e(A, Z_i) = e(A_i, G2) and e(A_i, Y) = e(B_i, G2) for each
+/// attribute's (A_i, B_i).
+pub(crate) fn attribute_points_are_valid<'a, B: PairingBackend>(
+    pub_key: &AttributeGroupPublicKey<B>,
+    a: &B::G1,
+    mut points: impl Iterator<Item = (&'a B::G1, &'a B::G1)>,
+) -> bool {
+    let g2 = B::g2_generator();
+    pub_key.attributes.iter().all(|key| match points.next() {
+        Some((a_i, b_i)) => {
+            B::pairing_product_is_one(&[(&key.z, a), (&g2, &B::g1_neg(a_i))])
+                && B::pairing_product_is_one(&[(&pub_key.base.y, a_i), (&g2, &B::g1_neg(b_i))])
+        }
+        None => false,
+    })
+}
+
+/// Challenge for the proof of knowledge of the hidden attributes and any
+/// range proofs over them, bound to the message, the rest of the signature
+/// and which attributes are revealed.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn attribute_challenge<'a, B: PairingBackend>(
+    suite: Ciphersuite,
+    msg: &[u8],
+    bsn: &[u8],
+    base: &Signature<B>,
+    attributes: &[SignatureAttribute<B>],
+    hidden: &B::G1,
+    commitment: &B::G1,
+    ranges: impl Iterator<Item = &'a RangeCommitment<B>>,
+) -> B::Scalar {
+    let mut transcript = Transcript::<B>::labeled(suite, PROTOCOL_SIGN_ATTRIBUTES);
+    transcript.append_bytes(b"message", &message_hash(suite, msg, bsn));
+    let mut signature = vec![0u8; B::SIGNATURE_SIZE];
+    base.write_bytes(&mut signature);
+    transcript.append_bytes(b"signature", &signature);
+    for attribute in attributes {
+        transcript.append_ecp(b"a", &attribute.a);
+        transcript.append_ecp(b"b", &attribute.b);
+        let mut disclosed = [0u8; BIG_SIZE + 1];
+        if let Some(value) = &attribute.disclosed {
+            disclosed[0] = 1;
+            B::scalar_to_bytes(value, &mut disclosed[1..]);
+        }
+        transcript.append_bytes(b"disclosed", &disclosed);
+    }
+    transcript.append_ecp(b"hidden", hidden);
+    transcript.append_ecp(b"commitment", commitment);
+    for range in ranges {
+        range.append_to(&mut transcript);
+    }
+    transcript.challenge()
+}
+
+/// Verifies a signature made with `sign_with_attributes` for the basename
+/// `bsn`, given as raw bytes or as a structured `Basename`. On success, the
+/// revealed attributes returned by `AttributeSignature::disclosed` are
+/// certified by the issuer.
+pub fn verify_with_attributes<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &AttributeGroupPublicKey<B>,
+    signature: &AttributeSignature<B>,
+    msg: &[u8],
+    bsn: impl AsRef<[u8]>,
+) -> Result<()> {
+    suite.check::<B>()?;
+    let bsn = bsn.as_ref();
+    if signature.attributes.len() != pub_key.attributes.len() {
+        return Err(CredentialError::BadAttributes);
+    }
+    let base = &signature.base;
+
+    // C = (A + D + sum(m_i * B_i)) ** x, with the hidden terms summed up by
+    // the signer
+    let d =
+        signature
+            .attributes
+            .iter()
+            .fold(
+                B::g1_add(&base.d, &signature.hidden),
+                |d, attribute| match &attribute.disclosed {
+                    Some(value) => B::g1_add(&d, &B::g1_mul(&attribute.b, value)),
+                    None => d,
+                },
+            );
+    if !randomized_credentials_are_valid::<B>(&pub_key.base, &base.a, &base.b, &base.c, &d)
+        || !attribute_points_are_valid::<B>(
+            pub_key,
+            &base.a,
+            signature
+                .attributes
+                .iter()
+                .map(|attribute| (&attribute.a, &attribute.b)),
+        )
+        || !nym_proof_is_valid::<B>(suite, base, &suite.hash(msg), bsn)
+    {
+        return Err(CredentialError::SignatureValidation);
+    }
+
+    // sum(s_i * B_i) - c * hidden must reproduce the signer's commitment
+    let hidden_bases: Vec<_> = signature
+        .attributes
+        .iter()
+        .enumerate()
+        .filter(|(_, attribute)| attribute.disclosed.is_none())
+        .map(|(i, attribute)| (i, &attribute.b))
+        .collect();
+    if hidden_bases.len() != signature.s.len() {
+        return Err(CredentialError::BadAttributes);
+    }
+    let commitment = hidden_bases.iter().zip(&signature.s).fold(
+        B::g1_mul(&signature.hidden, &B::scalar_neg(&signature.c)),
+        |commitment, ((_, b), s)| B::g1_add(&commitment, &B::g1_mul(b, s)),
+    );
+
+    // Each range proof is checked against the response of its attribute,
+    // which must be hidden
+    let ranges = signature
+        .ranges
+        .iter()
+        .map(|proof| {
+            let position = hidden_bases
+                .iter()
+                .position(|(i, _)| *i == proof.range.index)
+                .ok_or(CredentialError::BadAttributes)?;
+            proof.commitment(suite, &signature.c, &signature.s[position])
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let c = attribute_challenge::<B>(
+        suite,
+        msg,
+        bsn,
+        base,
+        &signature.attributes,
+        &signature.hidden,
+        &commitment,
+        ranges.iter(),
+    );
+    if !B::scalar_equals(&signature.c, &c) {
+        return Err(CredentialError::SignatureValidation);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use ark_bls12_381::{g1, Bls12_381, Fr, G1Projective, G2Projective};
+use ark_ec::{pairing::Pairing, Group};
+use ark_ff::{BigInt, Field, PrimeField, Zero};
+use brave_miracl::rand::RAND;
+
+use crate::backend::PairingBackend;
+use crate::ciphersuite::Curve;
+
+const FIELD_SIZE: usize = 48;
+
+/// BLS12-381 arithmetic from `ark-bls12-381`, enabled by the `bls12-381`
+/// feature. Encodings follow the BN254 backends with 48-byte coordinates.
+/// Unlike BN254, both groups have a cofactor, so decoding checks that points
+/// are in the prime order subgroup and hashing to G1 clears the cofactor.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bls12381;
+
+impl PairingBackend for Bls12381 {
+    type Scalar = BigInt<4>;
+    type G1 = G1Projective;
+    type G2 = G2Projective;
+
+    const CURVE: Curve = Curve::Bls12381;
+    const SCALAR_SIZE: usize = super::SCALAR_SIZE;
+    const G1_SIZE: usize = FIELD_SIZE * 2 + 1;
+    const G2_SIZE: usize = FIELD_SIZE * 4;
+
+    fn scalar_random(rng: &mut RAND) -> BigInt<4> {
+        super::random_scalar::<Fr>(rng)
+    }
+
+    fn scalar_from_bytes(bytes: &[u8]) -> BigInt<4> {
+        super::scalar_from_bytes(bytes)
+    }
+
+    fn scalar_from_hash(hash: &[u8]) -> BigInt<4> {
+        super::reduce::<Fr>(&super::scalar_from_bytes(hash)).into_bigint()
+    }
+
+    fn scalar_to_bytes(scalar: &BigInt<4>, bytes: &mut [u8]) {
+        super::scalar_to_bytes(scalar, bytes);
+    }
+
+    fn scalar_add(a: &BigInt<4>, b: &BigInt<4>) -> BigInt<4> {
+        (super::reduce::<Fr>(a) + super::reduce::<Fr>(b)).into_bigint()
+    }
+
+    fn scalar_mul(a: &BigInt<4>, b: &BigInt<4>) -> BigInt<4> {
+        (super::reduce::<Fr>(a) * super::reduce::<Fr>(b)).into_bigint()
+    }
+
+    fn scalar_neg(a: &BigInt<4>) -> BigInt<4> {
+        (-super::reduce::<Fr>(a)).into_bigint()
+    }
+
+    fn scalar_inv(a: &BigInt<4>) -> BigInt<4> {
+        super::reduce::<Fr>(a)
+            .inverse()
+            .unwrap_or_default()
+            .into_bigint()
+    }
+
+    fn scalar_equals(a: &BigInt<4>, b: &BigInt<4>) -> bool {
+        a == b
+    }
+
+    fn g1_generator() -> G1Projective {
+        G1Projective::generator()
+    }
+
+    fn g1_infinity() -> G1Projective {
+        G1Projective::zero()
+    }
+
+    fn g1_mul(point: &G1Projective, scalar: &BigInt<4>) -> G1Projective {
+        *point * super::reduce::<Fr>(scalar)
+    }
+
+    fn g1_add(a: &G1Projective, b: &G1Projective) -> G1Projective {
+        *a + b
+    }
+
+    fn g1_neg(point: &G1Projective) -> G1Projective {
+        -*point
+    }
+
+    fn g1_is_infinity(point: &G1Projective) -> bool {
+        point.is_zero()
+    }
+
+    fn g1_to_bytes(point: &G1Projective, bytes: &mut [u8]) {
+        super::g1_to_bytes(point, bytes);
+    }
+
+    fn g1_from_bytes(bytes: &[u8]) -> G1Projective {
+        super::g1_from_bytes(bytes)
+    }
+
+    fn g1_from_hash(hash: &[u8]) -> G1Projective {
+        super::g1_from_hash::<g1::Config>(hash)
+    }
+
+    fn g2_generator() -> G2Projective {
+        G2Projective::generator()
+    }
+
+    fn g2_mul(point: &G2Projective, scalar: &BigInt<4>) -> G2Projective {
+        *point * super::reduce::<Fr>(scalar)
+    }
+
+    fn g2_add(a: &G2Projective, b: &G2Projective) -> G2Projective {
+        *a + b
+    }
+
+    fn g2_to_bytes(point: &G2Projective, bytes: &mut [u8]) {
+        super::g2_to_bytes(point, bytes);
+    }
+
+    fn g2_from_bytes(bytes: &[u8]) -> G2Projective {
+        super::g2_from_bytes(bytes).map_or(G2Projective::zero(), super::on_curve_in_subgroup)
+    }
+
+    fn pairing_product_is_one(pairs: &[(&G2Projective, &G1Projective)]) -> bool {
+        let g1 = pairs.iter().map(|(_, p)| **p);
+        let g2 = pairs.iter().map(|(q, _)| **q);
+        Bls12_381::multi_pairing(g1, g2).is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::{g2, Fq, Fq2, G1Affine, G2Affine};
+    use ark_ec::{short_weierstrass::SWCurveConfig, AffineRepr, CurveGroup};
+    use ark_ff::One;
+
+    use super::*;
+
+    #[test]
+    fn test_points_outside_the_subgroup_are_rejected() {
+        // The first points with x = 1, 2, ... are on the curves but, given
+        // the cofactors, almost certainly not in the prime order subgroups
+        let mut x = Fq::one();
+        let p = loop {
+            if let Some(p) = G1Affine::get_point_from_x_unchecked(x, false) {
+                break p;
+            }
+            x += Fq::one();
+        };
+        assert!(p.is_on_curve() && !p.is_in_correct_subgroup_assuming_on_curve());
+        let mut bytes = [0u8; Bls12381::G1_SIZE];
+        Bls12381::g1_to_bytes(&p.into(), &mut bytes);
+        assert!(Bls12381::g1_is_infinity(&Bls12381::g1_from_bytes(&bytes)));
+
+        let cleared = p.clear_cofactor().into_group();
+        Bls12381::g1_to_bytes(&cleared, &mut bytes);
+        assert_eq!(Bls12381::g1_from_bytes(&bytes), cleared);
+
+        let mut x = Fq2::one();
+        let q = loop {
+            if let Some(q) = G2Affine::get_point_from_x_unchecked(x, false) {
+                break q;
+            }
+            x += Fq2::one();
+        };
+        assert!(!g2::Config::is_in_correct_subgroup_assuming_on_curve(&q));
+        let mut bytes = [0u8; Bls12381::G2_SIZE];
+        Bls12381::g2_to_bytes(&q.into(), &mut bytes);
+        assert!(Bls12381::g2_from_bytes(&bytes).is_zero());
+
+        let cleared = q.clear_cofactor().into_group();
+        Bls12381::g2_to_bytes(&cleared, &mut bytes);
+        assert_eq!(Bls12381::g2_from_bytes(&bytes), cleared);
+    }
+
+    #[test]
+    fn test_hash_to_g1_is_in_the_subgroup() {
+        for i in 0..8u8 {
+            let p = Bls12381::g1_from_hash(&[i; 32]).into_affine();
+            assert!(p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve());
+            assert!(!p.is_zero());
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use ark_ec::{pairing::Pairing, Group};
+use ark_ff::{BigInt, Field, PrimeField, Zero};
+use brave_miracl::rand::RAND;
+
+use super::bn254_curve::{Bn254, Fr, G1Config, G1Projective, G2Projective};
+use crate::backend::PairingBackend;
+use crate::ciphersuite::Curve;
+
+const FIELD_SIZE: usize = 32;
+
+/// BN254 arithmetic in pure Rust, built on arkworks, enabled by the
+/// `arkworks` feature. It gives the same results and encodings as `Miracl`,
+/// and only uses `brave-miracl` for `RAND`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Arkworks;
+
+impl PairingBackend for Arkworks {
+    type Scalar = BigInt<4>;
+    type G1 = G1Projective;
+    type G2 = G2Projective;
+
+    const CURVE: Curve = Curve::Bn254;
+    const SCALAR_SIZE: usize = super::SCALAR_SIZE;
+    const G1_SIZE: usize = FIELD_SIZE * 2 + 1;
+    const G2_SIZE: usize = FIELD_SIZE * 4;
+
+    fn scalar_random(rng: &mut RAND) -> BigInt<4> {
+        super::random_scalar::<Fr>(rng)
+    }
+
+    fn scalar_from_bytes(bytes: &[u8]) -> BigInt<4> {
+        super::scalar_from_bytes(bytes)
+    }
+
+    fn scalar_from_hash(hash: &[u8]) -> BigInt<4> {
+        super::reduce::<Fr>(&super::scalar_from_bytes(hash)).into_bigint()
+    }
+
+    fn scalar_to_bytes(scalar: &BigInt<4>, bytes: &mut [u8]) {
+        super::scalar_to_bytes(scalar, bytes);
+    }
+
+    fn scalar_add(a: &BigInt<4>, b: &BigInt<4>) -> BigInt<4> {
+        (super::reduce::<Fr>(a) + super::reduce::<Fr>(b)).into_bigint()
+    }
+
+    fn scalar_mul(a: &BigInt<4>, b: &BigInt<4>) -> BigInt<4> {
+        (super::reduce::<Fr>(a) * super::reduce::<Fr>(b)).into_bigint()
+    }
+
+    fn scalar_neg(a: &BigInt<4>) -> BigInt<4> {
+        (-super::reduce::<Fr>(a)).into_bigint()
+    }
+
+    fn scalar_inv(a: &BigInt<4>) -> BigInt<4> {
+        super::reduce::<Fr>(a)
+            .inverse()
+            .unwrap_or_default()
+            .into_bigint()
+    }
+
+    fn scalar_equals(a: &BigInt<4>, b: &BigInt<4>) -> bool {
+        a == b
+    }
+
+    fn g1_generator() -> G1Projective {
+        G1Projective::generator()
+    }
+
+    fn g1_infinity() -> G1Projective {
+        G1Projective::zero()
+    }
+
+    fn g1_mul(point: &G1Projective, scalar: &BigInt<4>) -> G1Projective {
+        *point * super::reduce::<Fr>(scalar)
+    }
+
+    fn g1_add(a: &G1Projective, b: &G1Projective) -> G1Projective {
+        *a + b
+    }
+
+    fn g1_neg(point: &G1Projective) -> G1Projective {
+        -*point
+    }
+
+    fn g1_is_infinity(point: &G1Projective) -> bool {
+        point.is_zero()
+    }
+
+    fn g1_to_bytes(point: &G1Projective, bytes: &mut [u8]) {
+        super::g1_to_bytes(point, bytes);
+    }
+
+    fn g1_from_bytes(bytes: &[u8]) -> G1Projective {
+        super::g1_from_bytes(bytes)
+    }
+
+    fn g1_from_hash(hash: &[u8]) -> G1Projective {
+        // The cofactor is 1, so this is exactly ECP::mapit
+        super::g1_from_hash::<G1Config>(hash)
+    }
+
+    fn g2_generator() -> G2Projective {
+        G2Projective::generator()
+    }
+
+    fn g2_mul(point: &G2Projective, scalar: &BigInt<4>) -> G2Projective {
+        *point * super::reduce::<Fr>(scalar)
+    }
+
+    fn g2_add(a: &G2Projective, b: &G2Projective) -> G2Projective {
+        *a + b
+    }
+
+    fn g2_to_bytes(point: &G2Projective, bytes: &mut [u8]) {
+        super::g2_to_bytes(point, bytes);
+    }
+
+    fn g2_from_bytes(bytes: &[u8]) -> G2Projective {
+        // MIRACL only checks that the point is on the twist
+        super::g2_from_bytes(bytes).map_or(G2Projective::zero(), Into::into)
+    }
+
+    fn pairing_product_is_one(pairs: &[(&G2Projective, &G1Projective)]) -> bool {
+        let g1 = pairs.iter().map(|(_, p)| **p);
+        let g2 = pairs.iter().map(|(q, _)| **q);
+        Bn254::multi_pairing(g1, g2).is_zero()
+    }
+}
@@ -0,0 +1,277 @@
+//! MIRACL's BN254 curve (Nogami et al.), defined over arkworks' generic
+//! fields and BN pairing. It is not the BN254 of `ark-bn254`, which uses
+//! another prime. The Frobenius coefficients are powers of the sextic
+//! non-residue 1 + i, and the tower is Fp2 = Fp[i] / (i^2 + 1),
+//! Fp6 = Fp2[v] / (v^3 - (1 + i)) and Fp12 = Fp6[w] / (w^2 - v).
+
+// `MontConfig` emits code for ark-ff's own `asm` feature inside a const
+// function
+#![allow(unexpected_cfgs, non_local_definitions)]
+
+use ark_ec::{
+    bn::{self, Bn, BnConfig, TwistType},
+    models::{short_weierstrass::SWCurveConfig, CurveConfig},
+    short_weierstrass::Affine,
+};
+use ark_ff::{
+    fields::{Fp12Config, Fp2, Fp256, Fp2Config, Fp6, Fp6Config, MontBackend, MontConfig},
+    Field, MontFp, Zero,
+};
+
+#[derive(MontConfig)]
+#[modulus = "16798108731015832284940804142231733909889187121439069848933715426072753864723"]
+#[generator = "3"]
+pub struct FqConfig;
+pub type Fq = Fp256<MontBackend<FqConfig, 4>>;
+
+#[derive(MontConfig)]
+#[modulus = "16798108731015832284940804142231733909759579603404752749028378864165570215949"]
+#[generator = "2"]
+pub struct FrConfig;
+pub type Fr = Fp256<MontBackend<FrConfig, 4>>;
+
+pub type Fq2 = Fp2<Fq2Config>;
+pub type Fq6 = Fp6<Fq6Config>;
+
+pub struct Fq2Config;
+
+impl Fp2Config for Fq2Config {
+    type Fp = Fq;
+
+    const NONRESIDUE: Fq = MontFp!("-1");
+
+    const FROBENIUS_COEFF_FP2_C1: &'static [Fq] = &[Fq::ONE, MontFp!("-1")];
+
+    #[inline(always)]
+    fn mul_fp_by_nonresidue_in_place(fe: &mut Fq) -> &mut Fq {
+        fe.neg_in_place()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Fq6Config;
+
+impl Fp6Config for Fq6Config {
+    type Fp2Config = Fq2Config;
+
+    const NONRESIDUE: Fq2 = Fq2::new(Fq::ONE, Fq::ONE);
+
+    const FROBENIUS_COEFF_FP6_C1: &'static [Fq2] = &[
+        Fq2::new(Fq::ONE, Fq::ZERO),
+        Fq2::new(
+            Fq::ZERO,
+            MontFp!(
+                "16798108731015832283133667796947756444075910019074449559301910896669540483083"
+            ),
+        ),
+        Fq2::new(
+            MontFp!("1807136345283977465813277102364620289631804529403213381639"),
+            Fq::ZERO,
+        ),
+        Fq2::new(Fq::ZERO, Fq::ONE),
+        Fq2::new(
+            MontFp!(
+                "16798108731015832283133667796947756444075910019074449559301910896669540483083"
+            ),
+            Fq::ZERO,
+        ),
+        Fq2::new(
+            Fq::ZERO,
+            MontFp!("1807136345283977465813277102364620289631804529403213381639"),
+        ),
+    ];
+
+    const FROBENIUS_COEFF_FP6_C2: &'static [Fq2] = &[
+        Fq2::new(Fq::ONE, Fq::ZERO),
+        Fq2::new(
+            MontFp!(
+                "16798108731015832283133667796947756444075910019074449559301910896669540483084"
+            ),
+            Fq::ZERO,
+        ),
+        Fq2::new(
+            MontFp!(
+                "16798108731015832283133667796947756444075910019074449559301910896669540483083"
+            ),
+            Fq::ZERO,
+        ),
+        Fq2::new(MontFp!("-1"), Fq::ZERO),
+        Fq2::new(
+            MontFp!("1807136345283977465813277102364620289631804529403213381639"),
+            Fq::ZERO,
+        ),
+        Fq2::new(
+            MontFp!("1807136345283977465813277102364620289631804529403213381640"),
+            Fq::ZERO,
+        ),
+    ];
+}
+
+#[derive(Clone, Copy)]
+pub struct Fq12Config;
+
+impl Fp12Config for Fq12Config {
+    type Fp6Config = Fq6Config;
+
+    const NONRESIDUE: Fq6 = Fq6::new(Fq2::ZERO, Fq2::ONE, Fq2::ZERO);
+
+    const FROBENIUS_COEFF_FP12_C1: &'static [Fq2] = &[
+        Fq2::new(Fq::ONE, Fq::ZERO),
+        Fq2::new(
+            MontFp!(
+                "12310438583873020660552735091161044116898065562217439662059245424880585960937"
+            ),
+            MontFp!("4487670147142811624388069051070689792991121559221630186874470001192167903786"),
+        ),
+        Fq2::new(
+            MontFp!("1807136345283977465813277102364620289631804529403213381640"),
+            Fq::ZERO,
+        ),
+        Fq2::new(
+            MontFp!("571759232279933406358082416437452803737039382138144404732186496954757578318"),
+            MontFp!(
+                "16226349498735898878582721725794281106152147739300925444201528929117996286405"
+            ),
+        ),
+        Fq2::new(
+            MontFp!("1807136345283977465813277102364620289631804529403213381639"),
+            Fq::ZERO,
+        ),
+        Fq2::new(
+            MontFp!("5059429379422745030746151467508142596728160941359774591606656498146925482104"),
+            MontFp!(
+                "11738679351593087254194652674723591313161026180079295257327058927925828382619"
+            ),
+        ),
+        Fq2::new(MontFp!("-1"), Fq::ZERO),
+        Fq2::new(
+            MontFp!("4487670147142811624388069051070689792991121559221630186874470001192167903786"),
+            MontFp!(
+                "12310438583873020660552735091161044116898065562217439662059245424880585960937"
+            ),
+        ),
+        Fq2::new(
+            MontFp!(
+                "16798108731015832283133667796947756444075910019074449559301910896669540483083"
+            ),
+            Fq::ZERO,
+        ),
+        Fq2::new(
+            MontFp!(
+                "16226349498735898878582721725794281106152147739300925444201528929117996286405"
+            ),
+            MontFp!("571759232279933406358082416437452803737039382138144404732186496954757578318"),
+        ),
+        Fq2::new(
+            MontFp!(
+                "16798108731015832283133667796947756444075910019074449559301910896669540483084"
+            ),
+            Fq::ZERO,
+        ),
+        Fq2::new(
+            MontFp!(
+                "11738679351593087254194652674723591313161026180079295257327058927925828382619"
+            ),
+            MontFp!("5059429379422745030746151467508142596728160941359774591606656498146925482104"),
+        ),
+    ];
+}
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct G1Config;
+
+impl CurveConfig for G1Config {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+
+    const COFACTOR: &'static [u64] = &[1];
+    const COFACTOR_INV: Fr = Fr::ONE;
+}
+
+impl SWCurveConfig for G1Config {
+    const COEFF_A: Fq = Fq::ZERO;
+    const COEFF_B: Fq = MontFp!("2");
+
+    // (-1, 1)
+    const GENERATOR: Affine<Self> = Affine::new_unchecked(MontFp!("-1"), Fq::ONE);
+
+    #[inline(always)]
+    fn mul_by_a(_: Fq) -> Fq {
+        Fq::zero()
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct G2Config;
+
+impl CurveConfig for G2Config {
+    type BaseField = Fq2;
+    type ScalarField = Fr;
+
+    // 2p - r
+    const COFACTOR: &'static [u64] = &[
+        0xad00000000000019,
+        0xc2a2800000000016,
+        0xba344d8000000008,
+        0x2523648240000001,
+    ];
+    const COFACTOR_INV: Fr =
+        MontFp!("8399054365507916140663265725831889489001708940320597534943659766172318957567");
+}
+
+impl SWCurveConfig for G2Config {
+    const COEFF_A: Fq2 = Fq2::ZERO;
+    // 2 / (1 + i), as the twist is a D-type one
+    const COEFF_B: Fq2 = Fq2::new(Fq::ONE, MontFp!("-1"));
+
+    const GENERATOR: Affine<Self> = Affine::new_unchecked(
+        Fq2::new(
+            MontFp!("2759930593230997547690248631365636073479225314645471320757910281674905877291"),
+            MontFp!("2301614907882718573745244110620256732212332571700737603512907075120331574515"),
+        ),
+        Fq2::new(
+            MontFp!("948076515351688797057606839494504109262247838840660288969725032302618946458"),
+            MontFp!("6663077446927392079224045631425291036692402823802663947112913140121004068507"),
+        ),
+    );
+
+    #[inline(always)]
+    fn mul_by_a(_: Fq2) -> Fq2 {
+        Fq2::zero()
+    }
+}
+
+pub struct Config;
+
+impl BnConfig for Config {
+    // |x| = 2^62 + 2^55 + 1, for x negative
+    const X: &'static [u64] = &[0x4080000000000001];
+    const X_IS_NEGATIVE: bool = true;
+    // Non-adjacent form of |6x + 2|, least significant digit first
+    const ATE_LOOP_COUNT: &'static [i8] = &[
+        0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1, 0, 1, 0,
+        0, 0, 0, -1, 0, 1,
+    ];
+
+    const TWIST_MUL_BY_Q_X: Fq2 = Fq2::new(
+        Fq::ZERO,
+        MontFp!("16798108731015832283133667796947756444075910019074449559301910896669540483083"),
+    );
+    const TWIST_MUL_BY_Q_Y: Fq2 = Fq2::new(
+        MontFp!("16226349498735898878582721725794281106152147739300925444201528929117996286405"),
+        MontFp!("16226349498735898878582721725794281106152147739300925444201528929117996286405"),
+    );
+    const TWIST_TYPE: TwistType = TwistType::D;
+
+    type Fp = Fq;
+    type Fp2Config = Fq2Config;
+    type Fp6Config = Fq6Config;
+    type Fp12Config = Fq12Config;
+    type G1Config = G1Config;
+    type G2Config = G2Config;
+}
+
+pub type Bn254 = Bn<Config>;
+pub type G1Projective = bn::G1Projective<Config>;
+pub type G2Projective = bn::G2Projective<Config>;
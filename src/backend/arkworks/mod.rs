@@ -0,0 +1,170 @@
+//! Backends on arkworks' generic fields and curves. Scalars are kept as
+//! integers, as MIRACL keeps them, so that encodings of the curve order or
+//! above stay distinct from their reductions.
+
+#[cfg(feature = "bls12-381")]
+mod bls12_381;
+#[cfg(feature = "arkworks")]
+mod bn254;
+#[cfg(feature = "arkworks")]
+mod bn254_curve;
+
+use ark_ec::short_weierstrass::{Affine, Projective, SWCurveConfig};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInt, BigInteger, Field, Fp2, Fp2Config, One, PrimeField, Zero};
+use brave_miracl::rand::RAND;
+
+#[cfg(feature = "bls12-381")]
+pub use self::bls12_381::Bls12381;
+#[cfg(feature = "arkworks")]
+pub use self::bn254::Arkworks;
+
+const SCALAR_SIZE: usize = 32;
+
+fn field_size<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize).div_ceil(64) * 8
+}
+
+fn scalar_from_bytes(bytes: &[u8]) -> BigInt<4> {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes[..SCALAR_SIZE].rchunks(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    BigInt(limbs)
+}
+
+fn scalar_to_bytes(scalar: &BigInt<4>, bytes: &mut [u8]) {
+    bytes[..SCALAR_SIZE].copy_from_slice(&scalar.to_bytes_be());
+}
+
+fn reduce<F: PrimeField<BigInt = BigInt<4>>>(scalar: &BigInt<4>) -> F {
+    let mut reduced = *scalar;
+    while reduced >= F::MODULUS {
+        reduced.sub_with_borrow(&F::MODULUS);
+    }
+    F::from_bigint(reduced).unwrap()
+}
+
+/// Like MIRACL's BIG::randomnum: twice as many bits as the order, taken from
+/// each byte least significant bit first and shifted in from the bottom,
+/// then reduced.
+fn random_scalar<F: PrimeField<BigInt = BigInt<4>>>(rng: &mut RAND) -> BigInt<4> {
+    let bits = 2 * F::MODULUS_BIT_SIZE as usize;
+    let mut wide = [0u8; SCALAR_SIZE * 2];
+    let mut byte = 0;
+    for i in 0..bits {
+        match i % 8 {
+            0 => byte = rng.getbyte(),
+            _ => byte >>= 1,
+        }
+        let position = bits - 1 - i;
+        wide[wide.len() - 1 - position / 8] |= (byte & 1) << (position % 8);
+    }
+    F::from_be_bytes_mod_order(&wide).into_bigint()
+}
+
+/// Reads a big-endian field element, or `None` if it is not below the
+/// modulus.
+fn field_from_bytes<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    let bytes = &bytes[..field_size::<F>()];
+    let value = F::from_be_bytes_mod_order(bytes);
+    (value.into_bigint().to_bytes_be() == bytes).then_some(value)
+}
+
+fn field_to_bytes<F: PrimeField>(value: &F, bytes: &mut [u8]) {
+    bytes[..field_size::<F>()].copy_from_slice(&value.into_bigint().to_bytes_be());
+}
+
+/// Writes 0x04 followed by x and y, with infinity as (0, 1) like MIRACL.
+fn g1_to_bytes<P: SWCurveConfig>(point: &Projective<P>, bytes: &mut [u8])
+where
+    P::BaseField: PrimeField,
+{
+    let size = field_size::<P::BaseField>();
+    let (x, y) = point
+        .into_affine()
+        .xy()
+        .map_or((Zero::zero(), One::one()), |(x, y)| (*x, *y));
+    bytes[0] = 0x04;
+    field_to_bytes(&x, &mut bytes[1..]);
+    field_to_bytes(&y, &mut bytes[1 + size..]);
+}
+
+/// Decodes what `g1_to_bytes` writes. Anything else, including points
+/// outside of the prime order subgroup, decodes to infinity.
+fn g1_from_bytes<P: SWCurveConfig>(bytes: &[u8]) -> Projective<P>
+where
+    P::BaseField: PrimeField,
+{
+    let size = field_size::<P::BaseField>();
+    if bytes[0] != 0x04 {
+        return Projective::zero();
+    }
+    match (
+        field_from_bytes(&bytes[1..]),
+        field_from_bytes(&bytes[1 + size..]),
+    ) {
+        (Some(x), Some(y)) => on_curve_in_subgroup(Affine::new_unchecked(x, y)),
+        _ => Projective::zero(),
+    }
+}
+
+/// Maps a hash output to the first x from it onwards that is on the curve,
+/// taking the even y like MIRACL's ECP::mapit, then clears the cofactor.
+/// Points of small order, which clear to infinity, are skipped as well.
+fn g1_from_hash<P: SWCurveConfig>(hash: &[u8]) -> Projective<P>
+where
+    P::BaseField: PrimeField,
+{
+    let mut x = P::BaseField::from_be_bytes_mod_order(hash);
+    loop {
+        let rhs = x * x * x + P::COEFF_B;
+        if let Some(y) = rhs.sqrt() {
+            let y = match y.into_bigint().is_odd() {
+                true => -y,
+                false => y,
+            };
+            let point = Affine::<P>::new_unchecked(x, y).clear_cofactor();
+            if !point.is_zero() {
+                return point.into();
+            }
+        }
+        x += P::BaseField::one();
+    }
+}
+
+/// Writes x.a, x.b, y.a, y.b, with infinity as (0, 1).
+fn g2_to_bytes<C: Fp2Config, P: SWCurveConfig<BaseField = Fp2<C>>>(
+    point: &Projective<P>,
+    bytes: &mut [u8],
+) {
+    let size = field_size::<C::Fp>();
+    let (x, y) = point
+        .into_affine()
+        .xy()
+        .map_or((Fp2::zero(), Fp2::one()), |(x, y)| (*x, *y));
+    for (i, value) in [x.c0, x.c1, y.c0, y.c1].iter().enumerate() {
+        field_to_bytes(value, &mut bytes[size * i..]);
+    }
+}
+
+/// Decodes what `g2_to_bytes` writes, reducing each coordinate mod p like
+/// MIRACL, or `None` if the point is not on the curve.
+fn g2_from_bytes<C: Fp2Config, P: SWCurveConfig<BaseField = Fp2<C>>>(
+    bytes: &[u8],
+) -> Option<Affine<P>> {
+    let size = field_size::<C::Fp>();
+    let coordinate = |i: usize| C::Fp::from_be_bytes_mod_order(&bytes[size * i..size * (i + 1)]);
+    let point = Affine::new_unchecked(
+        Fp2::new(coordinate(0), coordinate(1)),
+        Fp2::new(coordinate(2), coordinate(3)),
+    );
+    point.is_on_curve().then_some(point)
+}
+
+fn on_curve_in_subgroup<P: SWCurveConfig>(point: Affine<P>) -> Projective<P> {
+    match point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() {
+        true => point.into(),
+        false => Projective::zero(),
+    }
+}
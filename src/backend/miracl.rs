@@ -0,0 +1,223 @@
+use brave_miracl::{
+    bn254::{
+        big::{self, BIG},
+        ecp::ECP,
+        ecp2::ECP2,
+        fp12::FP12,
+        fp2::FP2,
+        pair::{another, fexp, g1mul, g2mul, initmp, miller},
+        rom::{CURVE_GX, CURVE_GY, CURVE_ORDER, CURVE_PXA, CURVE_PXB, CURVE_PYA, CURVE_PYB},
+    },
+    rand::RAND,
+};
+
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+use super::PairingBackend;
+use crate::ciphersuite::Curve;
+
+// The curve order is a plain array of limbs, so every scalar operation can
+// share one constant
+const ORDER: BIG = BIG { w: CURVE_ORDER };
+
+// Building the generators converts their coordinates to Montgomery form and
+// checks them against the curve equation, so with std they are built once.
+// Without std they are rebuilt on each call, as caching them would need a
+// spinlock.
+
+fn build_g1() -> ECP {
+    let gx = BIG::new_ints(&CURVE_GX);
+    let gy = BIG::new_ints(&CURVE_GY);
+    ECP::new_bigs(&gx, &gy)
+}
+
+fn build_g2() -> ECP2 {
+    let pxa = BIG::new_ints(&CURVE_PXA);
+    let pxb = BIG::new_ints(&CURVE_PXB);
+    let pya = BIG::new_ints(&CURVE_PYA);
+    let pyb = BIG::new_ints(&CURVE_PYB);
+    let wx = FP2::new_bigs(&pxa, &pxb);
+    let wy = FP2::new_bigs(&pya, &pyb);
+    ECP2::new_fp2s(&wx, &wy)
+}
+
+#[cfg(feature = "std")]
+fn g1_ecp() -> ECP {
+    static G1: OnceLock<ECP> = OnceLock::new();
+    G1.get_or_init(build_g1).clone()
+}
+
+#[cfg(not(feature = "std"))]
+fn g1_ecp() -> ECP {
+    build_g1()
+}
+
+#[cfg(feature = "std")]
+fn g2_ecp() -> ECP2 {
+    static G2: OnceLock<ECP2> = OnceLock::new();
+    G2.get_or_init(build_g2).clone()
+}
+
+#[cfg(not(feature = "std"))]
+fn g2_ecp() -> ECP2 {
+    build_g2()
+}
+
+/// BN254 arithmetic provided by `brave-miracl`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Miracl;
+
+impl PairingBackend for Miracl {
+    type Scalar = BIG;
+    type G1 = ECP;
+    type G2 = ECP2;
+
+    const CURVE: Curve = Curve::Bn254;
+    const SCALAR_SIZE: usize = big::MODBYTES;
+    const G1_SIZE: usize = big::MODBYTES * 2 + 1;
+    const G2_SIZE: usize = big::MODBYTES * 4;
+
+    fn scalar_random(rng: &mut RAND) -> BIG {
+        BIG::randomnum(&ORDER, rng)
+    }
+
+    fn scalar_from_bytes(bytes: &[u8]) -> BIG {
+        BIG::frombytes(bytes)
+    }
+
+    fn scalar_from_hash(hash: &[u8]) -> BIG {
+        let mut c = BIG::frombytes(hash);
+        c.rmod(&ORDER);
+        c
+    }
+
+    fn scalar_to_bytes(scalar: &BIG, bytes: &mut [u8]) {
+        scalar.tobytes(bytes);
+    }
+
+    fn scalar_add(a: &BIG, b: &BIG) -> BIG {
+        BIG::modadd(a, b, &ORDER)
+    }
+
+    fn scalar_mul(a: &BIG, b: &BIG) -> BIG {
+        BIG::modmul(a, b, &ORDER)
+    }
+
+    fn scalar_neg(a: &BIG) -> BIG {
+        BIG::modneg(a, &ORDER)
+    }
+
+    fn scalar_inv(a: &BIG) -> BIG {
+        let mut inverse = *a;
+        inverse.invmodp(&ORDER);
+        inverse
+    }
+
+    fn scalar_equals(a: &BIG, b: &BIG) -> bool {
+        BIG::comp(a, b) == 0
+    }
+
+    fn g1_generator() -> ECP {
+        g1_ecp()
+    }
+
+    fn g1_infinity() -> ECP {
+        ECP::new()
+    }
+
+    fn g1_mul(point: &ECP, scalar: &BIG) -> ECP {
+        g1mul(point, scalar)
+    }
+
+    fn g1_add(a: &ECP, b: &ECP) -> ECP {
+        let mut sum = a.clone();
+        sum.add(b);
+        sum
+    }
+
+    fn g1_neg(point: &ECP) -> ECP {
+        let mut neg = point.clone();
+        neg.neg();
+        neg
+    }
+
+    fn g1_is_infinity(point: &ECP) -> bool {
+        point.is_infinity()
+    }
+
+    fn g1_to_bytes(point: &ECP, bytes: &mut [u8]) {
+        // Infinity keeps whatever projective y the arithmetic left it with,
+        // so encode a single representative of it
+        if point.is_infinity() {
+            return ECP::new().tobytes(bytes, false);
+        }
+        point.tobytes(bytes, false);
+    }
+
+    fn g1_from_bytes(bytes: &[u8]) -> ECP {
+        // MIRACL also accepts compressed points, which would give a second
+        // valid encoding of every signature
+        if bytes[0] != 0x04 {
+            return ECP::new();
+        }
+        ECP::frombytes(bytes)
+    }
+
+    fn g1_from_hash(hash: &[u8]) -> ECP {
+        ECP::mapit(hash)
+    }
+
+    fn g2_generator() -> ECP2 {
+        g2_ecp()
+    }
+
+    fn g2_mul(point: &ECP2, scalar: &BIG) -> ECP2 {
+        g2mul(point, scalar)
+    }
+
+    fn g2_add(a: &ECP2, b: &ECP2) -> ECP2 {
+        let mut sum = a.clone();
+        sum.add(b);
+        sum
+    }
+
+    fn g2_to_bytes(point: &ECP2, bytes: &mut [u8]) {
+        const BIG_SIZE: usize = big::MODBYTES;
+
+        let mut x = point.getx();
+        let mut y = point.gety();
+
+        x.geta().tobytes(&mut bytes[..BIG_SIZE]);
+        x.getb().tobytes(&mut bytes[BIG_SIZE..BIG_SIZE * 2]);
+        y.geta().tobytes(&mut bytes[BIG_SIZE * 2..BIG_SIZE * 3]);
+        y.getb().tobytes(&mut bytes[BIG_SIZE * 3..BIG_SIZE * 4]);
+    }
+
+    fn g2_from_bytes(bytes: &[u8]) -> ECP2 {
+        const BIG_SIZE: usize = big::MODBYTES;
+
+        let x = FP2::new_bigs(
+            &BIG::frombytes(&bytes[..BIG_SIZE]),
+            &BIG::frombytes(&bytes[BIG_SIZE..BIG_SIZE * 2]),
+        );
+        let y = FP2::new_bigs(
+            &BIG::frombytes(&bytes[BIG_SIZE * 2..BIG_SIZE * 3]),
+            &BIG::frombytes(&bytes[BIG_SIZE * 3..BIG_SIZE * 4]),
+        );
+        ECP2::new_fp2s(&x, &y)
+    }
+
+    fn pairing_product_is_one(pairs: &[(&ECP2, &ECP)]) -> bool {
+        let mut rr = initmp();
+        for (p, q) in pairs {
+            another(&mut rr, p, q);
+        }
+        let r = miller(&mut rr);
+
+        let mut fp12_one = FP12::new();
+        fp12_one.one();
+
+        fexp(&r).equals(&fp12_one)
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(any(feature = "arkworks", feature = "bls12-381"))]
+mod arkworks;
+// With `arkworks`, `Miracl` is only used to compare the backends in tests
+#[cfg_attr(feature = "arkworks", allow(dead_code))]
+mod miracl;
+#[cfg(all(test, feature = "arkworks"))]
+mod tests;
+
+use brave_miracl::rand::RAND;
+
+use super::ciphersuite::Curve;
+
+#[cfg(feature = "arkworks")]
+pub use self::arkworks::Arkworks;
+#[cfg(feature = "bls12-381")]
+pub use self::arkworks::Bls12381;
+#[cfg_attr(feature = "arkworks", allow(unused_imports))]
+pub use self::miracl::Miracl;
+
+/// Size of an encoded scalar, which is the same for every backend.
+pub const BIG_SIZE: usize = 32;
+
+// Bounds on the encodings of every backend, for buffers in the code that is
+// generic over them
+pub(crate) const MAX_ECP_SIZE: usize = 97;
+pub(crate) const MAX_ECP2_SIZE: usize = 192;
+
+/// Group and pairing arithmetic used by the protocol code.
+///
+/// Everything outside of this module works on the associated types through
+/// these functions, so the protocol code is generic over the backend, and
+/// the backend picks the curve. An alternative implementation of a curve
+/// can be swapped in as `Bn254` is by the `arkworks` feature. Any backend
+/// must derive random scalars from `RAND` exactly like MIRACL's
+/// `BIG::randomnum`, and one of an existing curve must use the same
+/// encodings, for the fixed-seed test vectors of the curve to keep passing.
+pub trait PairingBackend: Clone + Copy + Default + 'static {
+    type Scalar: Copy;
+    type G1: Clone;
+    type G2: Clone;
+
+    const CURVE: Curve;
+    const SCALAR_SIZE: usize;
+    const G1_SIZE: usize;
+    const G2_SIZE: usize;
+
+    fn scalar_random(rng: &mut RAND) -> Self::Scalar;
+    /// Reads a big-endian scalar without reducing it mod the curve order.
+    fn scalar_from_bytes(bytes: &[u8]) -> Self::Scalar;
+    /// Reads a big-endian hash output and reduces it mod the curve order.
+    fn scalar_from_hash(hash: &[u8]) -> Self::Scalar;
+    fn scalar_to_bytes(scalar: &Self::Scalar, bytes: &mut [u8]);
+    fn scalar_add(a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+    fn scalar_mul(a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+    fn scalar_neg(a: &Self::Scalar) -> Self::Scalar;
+    /// Inverse mod the curve order, or zero for zero. Not constant time.
+    fn scalar_inv(a: &Self::Scalar) -> Self::Scalar;
+    fn scalar_equals(a: &Self::Scalar, b: &Self::Scalar) -> bool;
+
+    fn g1_generator() -> Self::G1;
+    fn g1_infinity() -> Self::G1;
+    fn g1_mul(point: &Self::G1, scalar: &Self::Scalar) -> Self::G1;
+    fn g1_add(a: &Self::G1, b: &Self::G1) -> Self::G1;
+    fn g1_neg(point: &Self::G1) -> Self::G1;
+    fn g1_is_infinity(point: &Self::G1) -> bool;
+    /// Writes the uncompressed encoding of `point`, which is unique for
+    /// every point including infinity.
+    fn g1_to_bytes(point: &Self::G1, bytes: &mut [u8]);
+    /// Decodes an uncompressed point. Any other encoding, or a point that is
+    /// not on the curve, decodes to infinity.
+    fn g1_from_bytes(bytes: &[u8]) -> Self::G1;
+    /// Maps a hash output to a point in G1.
+    fn g1_from_hash(hash: &[u8]) -> Self::G1;
+
+    fn g2_generator() -> Self::G2;
+    fn g2_mul(point: &Self::G2, scalar: &Self::Scalar) -> Self::G2;
+    fn g2_add(a: &Self::G2, b: &Self::G2) -> Self::G2;
+    /// Writes the encoding of the original C library: x.a, x.b, y.a, y.b.
+    fn g2_to_bytes(point: &Self::G2, bytes: &mut [u8]);
+    fn g2_from_bytes(bytes: &[u8]) -> Self::G2;
+
+    /// Returns whether the product of the pairings e(p, q) is the identity.
+    fn pairing_product_is_one(pairs: &[(&Self::G2, &Self::G1)]) -> bool;
+}
+
+/// Backend of the types at the root of the crate.
+#[cfg(feature = "arkworks")]
+pub type Bn254 = Arkworks;
+/// Backend of the types at the root of the crate.
+#[cfg(not(feature = "arkworks"))]
+pub type Bn254 = Miracl;
+
+// Encodings are sized by the curve, and buffers by the bounds above
+const fn check_sizes<B: PairingBackend>() {
+    assert!(B::SCALAR_SIZE == BIG_SIZE && B::SCALAR_SIZE == B::CURVE.big_size());
+    assert!(B::G1_SIZE == B::CURVE.ecp_size() && B::G1_SIZE <= MAX_ECP_SIZE);
+    assert!(B::G2_SIZE == B::CURVE.ecp2_size() && B::G2_SIZE <= MAX_ECP2_SIZE);
+}
+
+const _: () = check_sizes::<Miracl>();
+#[cfg(feature = "arkworks")]
+const _: () = check_sizes::<Arkworks>();
+#[cfg(feature = "bls12-381")]
+const _: () = check_sizes::<Bls12381>();
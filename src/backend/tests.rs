@@ -0,0 +1,153 @@
+use super::*;
+use crate::issuer::rng;
+
+fn scalar<B: PairingBackend>(scalar: &B::Scalar) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    B::scalar_to_bytes(scalar, &mut bytes);
+    bytes
+}
+
+fn g1<B: PairingBackend>(point: &B::G1) -> [u8; 65] {
+    let mut bytes = [0u8; 65];
+    B::g1_to_bytes(point, &mut bytes);
+    bytes
+}
+
+fn g2<B: PairingBackend>(point: &B::G2) -> [u8; 128] {
+    let mut bytes = [0u8; 128];
+    B::g2_to_bytes(point, &mut bytes);
+    bytes
+}
+
+#[test]
+fn test_sizes_match() {
+    assert_eq!(Miracl::SCALAR_SIZE, Arkworks::SCALAR_SIZE);
+    assert_eq!(Miracl::G1_SIZE, Arkworks::G1_SIZE);
+    assert_eq!(Miracl::G2_SIZE, Arkworks::G2_SIZE);
+}
+
+#[test]
+fn test_random_scalars_match() {
+    let (mut miracl_rng, mut arkworks_rng) = (rng(), rng());
+    for _ in 0..16 {
+        let a = Miracl::scalar_random(&mut miracl_rng);
+        let b = Arkworks::scalar_random(&mut arkworks_rng);
+        assert_eq!(scalar::<Miracl>(&a), scalar::<Arkworks>(&b));
+    }
+}
+
+#[test]
+fn test_scalar_arithmetic_matches() {
+    let mut rng = rng();
+    for _ in 0..8 {
+        let a = scalar::<Miracl>(&Miracl::scalar_random(&mut rng));
+        let b = scalar::<Miracl>(&Miracl::scalar_random(&mut rng));
+        let (ma, mb) = (Miracl::scalar_from_bytes(&a), Miracl::scalar_from_bytes(&b));
+        let (aa, ab) = (
+            Arkworks::scalar_from_bytes(&a),
+            Arkworks::scalar_from_bytes(&b),
+        );
+        assert_eq!(
+            scalar::<Miracl>(&Miracl::scalar_add(&ma, &mb)),
+            scalar::<Arkworks>(&Arkworks::scalar_add(&aa, &ab))
+        );
+        assert_eq!(
+            scalar::<Miracl>(&Miracl::scalar_mul(&ma, &mb)),
+            scalar::<Arkworks>(&Arkworks::scalar_mul(&aa, &ab))
+        );
+        assert_eq!(
+            scalar::<Miracl>(&Miracl::scalar_neg(&ma)),
+            scalar::<Arkworks>(&Arkworks::scalar_neg(&aa))
+        );
+        assert_eq!(
+            scalar::<Miracl>(&Miracl::scalar_inv(&ma)),
+            scalar::<Arkworks>(&Arkworks::scalar_inv(&aa))
+        );
+    }
+
+    // Hash outputs are reduced, plain encodings are not
+    let max = [0xffu8; 32];
+    assert_eq!(
+        scalar::<Miracl>(&Miracl::scalar_from_hash(&max)),
+        scalar::<Arkworks>(&Arkworks::scalar_from_hash(&max))
+    );
+    assert_eq!(scalar::<Arkworks>(&Arkworks::scalar_from_bytes(&max)), max);
+}
+
+#[test]
+fn test_points_match() {
+    assert_eq!(
+        g1::<Miracl>(&Miracl::g1_generator()),
+        g1::<Arkworks>(&Arkworks::g1_generator())
+    );
+    assert_eq!(
+        g1::<Miracl>(&Miracl::g1_infinity()),
+        g1::<Arkworks>(&Arkworks::g1_infinity())
+    );
+    assert_eq!(
+        g2::<Miracl>(&Miracl::g2_generator()),
+        g2::<Arkworks>(&Arkworks::g2_generator())
+    );
+
+    let mut rng = rng();
+    for i in 0..8u8 {
+        let s = scalar::<Miracl>(&Miracl::scalar_random(&mut rng));
+        let (ms, as_) = (
+            Miracl::scalar_from_bytes(&s),
+            Arkworks::scalar_from_bytes(&s),
+        );
+
+        let mp = Miracl::g1_from_hash(&[i; 32]);
+        let ap = Arkworks::g1_from_hash(&[i; 32]);
+        assert_eq!(g1::<Miracl>(&mp), g1::<Arkworks>(&ap));
+        let mp = Miracl::g1_add(&Miracl::g1_mul(&mp, &ms), &Miracl::g1_generator());
+        let ap = Arkworks::g1_add(&Arkworks::g1_mul(&ap, &as_), &Arkworks::g1_generator());
+        assert_eq!(g1::<Miracl>(&mp), g1::<Arkworks>(&ap));
+        assert_eq!(
+            g1::<Arkworks>(&Arkworks::g1_from_bytes(&g1::<Miracl>(&mp))),
+            g1::<Miracl>(&mp)
+        );
+
+        let mq = Miracl::g2_mul(&Miracl::g2_generator(), &ms);
+        let aq = Arkworks::g2_mul(&Arkworks::g2_generator(), &as_);
+        let mq = Miracl::g2_add(&mq, &Miracl::g2_generator());
+        let aq = Arkworks::g2_add(&aq, &Arkworks::g2_generator());
+        assert_eq!(g2::<Miracl>(&mq), g2::<Arkworks>(&aq));
+        assert_eq!(
+            g2::<Arkworks>(&Arkworks::g2_from_bytes(&g2::<Miracl>(&mq))),
+            g2::<Miracl>(&mq)
+        );
+    }
+
+    // Off-curve encodings decode to infinity in both
+    let mut bad = g1::<Miracl>(&Miracl::g1_generator());
+    bad[64] ^= 1;
+    assert!(Miracl::g1_is_infinity(&Miracl::g1_from_bytes(&bad)));
+    assert!(Arkworks::g1_is_infinity(&Arkworks::g1_from_bytes(&bad)));
+}
+
+#[test]
+fn test_pairings_match() {
+    let mut rng = rng();
+    let s = scalar::<Miracl>(&Miracl::scalar_random(&mut rng));
+    let t = scalar::<Miracl>(&Miracl::scalar_random(&mut rng));
+
+    // e(s * G1, t * G2) * e(-(s * t) * G1, G2) = 1, and not with s * t + 1
+    fn check<B: PairingBackend>(s: &[u8], t: &[u8], offset: bool) -> bool {
+        let (s, t) = (B::scalar_from_bytes(s), B::scalar_from_bytes(t));
+        let mut st = B::scalar_mul(&s, &t);
+        if offset {
+            let mut one = [0u8; 32];
+            one[31] = 1;
+            st = B::scalar_add(&st, &B::scalar_from_bytes(&one));
+        }
+        let p = B::g1_mul(&B::g1_generator(), &s);
+        let q = B::g2_mul(&B::g2_generator(), &t);
+        let r = B::g1_neg(&B::g1_mul(&B::g1_generator(), &st));
+        B::pairing_product_is_one(&[(&q, &p), (&B::g2_generator(), &r)])
+    }
+    for offset in [false, true] {
+        assert_eq!(check::<Miracl>(&s, &t, offset), !offset);
+        assert_eq!(check::<Arkworks>(&s, &t, offset), !offset);
+    }
+}
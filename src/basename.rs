@@ -0,0 +1,204 @@
+//! Structured basenames with a canonical encoding.
+//!
+//! Signatures with the same basename share a `nym`, so the basename decides
+//! what a collector can link. A `Basename` makes that window explicit: a
+//! scope (such as a service), an action within it and an epoch, encoded as
+//!
+//! ```text
+//! tag || len(scope) as u32 || scope || len(action) as u32 || action || epoch kind || period as u64
+//! ```
+//!
+//! with big-endian integers, so that clients and collectors agree on the
+//! bytes and distinct basenames never collide. The leading tag keeps them
+//! apart from raw basenames, unless the raw bytes start with the tag too.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{CredentialError, Result};
+
+/// Prefix of every structured basename encoding.
+pub const BASENAME_TAG: &[u8] = b"anonymous-credentials/basename\0";
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Linkability window of a basename. Periods are counted in UTC from the
+/// Unix epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Epoch {
+    /// Signatures stay linkable forever.
+    Unbounded,
+    /// Days since 1970-01-01.
+    Daily(u64),
+    /// Weeks starting on Monday, since the week of 1970-01-01.
+    Weekly(u64),
+    /// Months since January of year 0, i.e. `year * 12 + month - 1`.
+    Monthly(u64),
+}
+
+impl Epoch {
+    /// Day of `timestamp`, in seconds since the Unix epoch.
+    pub fn daily(timestamp: u64) -> Self {
+        Self::Daily(timestamp / SECONDS_PER_DAY)
+    }
+
+    /// Monday-based week of `timestamp`, in seconds since the Unix epoch.
+    pub fn weekly(timestamp: u64) -> Self {
+        // 1970-01-01 was a Thursday, three days after the week started
+        Self::Weekly((timestamp / SECONDS_PER_DAY + 3) / 7)
+    }
+
+    /// Calendar month of `timestamp`, in seconds since the Unix epoch.
+    pub fn monthly(timestamp: u64) -> Self {
+        let (year, month) = civil_from_days(timestamp / SECONDS_PER_DAY);
+        Self::Monthly(year * 12 + month - 1)
+    }
+
+    fn kind(&self) -> u8 {
+        match self {
+            Self::Unbounded => 0,
+            Self::Daily(_) => 1,
+            Self::Weekly(_) => 2,
+            Self::Monthly(_) => 3,
+        }
+    }
+
+    fn period(&self) -> u64 {
+        match self {
+            Self::Unbounded => 0,
+            Self::Daily(period) | Self::Weekly(period) | Self::Monthly(period) => *period,
+        }
+    }
+}
+
+/// Year and month (1-12) of a day count since 1970-01-01, after Howard
+/// Hinnant's `civil_from_days`.
+fn civil_from_days(days: u64) -> (u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month)
+}
+
+/// Basename made of a scope, an action and an epoch. Pass it wherever a
+/// basename is expected, e.g. `CredentialManager::sign` and `verify`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Basename {
+    scope: String,
+    action: String,
+    epoch: Epoch,
+    encoded: Vec<u8>,
+}
+
+impl Basename {
+    /// Fails with `BadBasename` if the scope or the action does not fit
+    /// its 32-bit length prefix.
+    pub fn new(scope: &str, action: &str, epoch: Epoch) -> Result<Self> {
+        let mut encoded = Vec::with_capacity(BASENAME_TAG.len() + scope.len() + action.len() + 17);
+        encoded.extend_from_slice(BASENAME_TAG);
+        for field in [scope, action] {
+            let len = u32::try_from(field.len()).map_err(|_| CredentialError::BadBasename)?;
+            encoded.extend_from_slice(&len.to_be_bytes());
+            encoded.extend_from_slice(field.as_bytes());
+        }
+        encoded.push(epoch.kind());
+        encoded.extend_from_slice(&epoch.period().to_be_bytes());
+
+        Ok(Self {
+            scope: scope.into(),
+            action: action.into(),
+            epoch,
+            encoded,
+        })
+    }
+
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    /// Canonical encoding, which is what gets hashed to the basename point.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.encoded
+    }
+}
+
+impl AsRef<[u8]> for Basename {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-02-29T12:00:00Z, a Thursday
+    const LEAP_DAY: u64 = 1_709_208_000;
+    // 2023-12-31T23:59:59Z, a Sunday
+    const NEW_YEARS_EVE: u64 = 1_704_067_199;
+
+    #[test]
+    fn test_epochs() {
+        assert_eq!(Epoch::daily(0), Epoch::Daily(0));
+        assert_eq!(Epoch::daily(LEAP_DAY), Epoch::Daily(19_782));
+        assert_eq!(Epoch::daily(NEW_YEARS_EVE + 1), Epoch::Daily(19_723));
+
+        assert_eq!(Epoch::weekly(0), Epoch::Weekly(0));
+        assert_eq!(Epoch::weekly(LEAP_DAY), Epoch::Weekly(2_826));
+        assert_eq!(Epoch::weekly(NEW_YEARS_EVE), Epoch::Weekly(2_817));
+        assert_eq!(Epoch::weekly(NEW_YEARS_EVE + 1), Epoch::Weekly(2_818));
+
+        assert_eq!(Epoch::monthly(0), Epoch::Monthly(1970 * 12));
+        assert_eq!(Epoch::monthly(LEAP_DAY), Epoch::Monthly(2024 * 12 + 1));
+        assert_eq!(
+            Epoch::monthly(NEW_YEARS_EVE),
+            Epoch::Monthly(2023 * 12 + 11)
+        );
+        assert_eq!(Epoch::monthly(NEW_YEARS_EVE + 1), Epoch::Monthly(2024 * 12));
+    }
+
+    #[test]
+    fn test_encoding() {
+        let basename = Basename::new("wdp", "query", Epoch::Daily(19_782)).unwrap();
+        let (tag, fields) = basename.as_bytes().split_at(BASENAME_TAG.len());
+        assert_eq!(tag, BASENAME_TAG);
+        assert_eq!(
+            hex::encode(fields),
+            // "wdp", "query", day 19782
+            "00000003776470000000057175657279010000000000004d46"
+        );
+        assert_eq!(basename.scope(), "wdp");
+        assert_eq!(basename.action(), "query");
+        assert_eq!(basename.epoch(), Epoch::Daily(19_782));
+    }
+
+    #[test]
+    fn test_distinct_basenames_do_not_collide() {
+        let basenames = [
+            Basename::new("ab", "c", Epoch::Unbounded).unwrap(),
+            Basename::new("a", "bc", Epoch::Unbounded).unwrap(),
+            Basename::new("a", "bc", Epoch::Daily(0)).unwrap(),
+            Basename::new("a", "bc", Epoch::Weekly(0)).unwrap(),
+            Basename::new("a", "bc", Epoch::Daily(1)).unwrap(),
+            Basename::new("", "abc", Epoch::Unbounded).unwrap(),
+        ];
+        for (i, basename) in basenames.iter().enumerate() {
+            for other in &basenames[i + 1..] {
+                assert_ne!(basename.as_bytes(), other.as_bytes());
+            }
+        }
+    }
+}
@@ -0,0 +1,9 @@
+//! The types of the crate on BLS12-381, for `Ciphersuite::Bls12381Sha256V1`.
+//!
+//! They work with the same generic functions as the types at the root of
+//! the crate, which are on BN254, so both curves can be used side by side.
+//! Encodings are longer, as sized by the constants of this module.
+
+pub use crate::backend::Bls12381;
+
+crate::backend_types!(Bls12381);
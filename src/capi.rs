@@ -0,0 +1,629 @@
+//! C ABI over the member API, enabled by the `capi` feature.
+//!
+//! Inputs and outputs use the same byte formats as the original C library.
+//! Output buffers must be at least as large as the matching `AC_*_SIZE`
+//! constant. The `GS_*` functions mirror the C library's entry points, so
+//! its callers can switch over without changing their call sites.
+//!
+//! `include/anonymous_credentials.h` is generated from this file alone with
+//! `cbindgen --config cbindgen.toml --output include/anonymous_credentials.h
+//! src/capi.rs`, which is why the sizes below are spelled out for bn254
+//! rather than taken from `data`.
+
+use core::ffi::c_int;
+use core::{mem, ptr, slice};
+
+use super::{
+    verify, Ciphersuite, CredentialBIG, CredentialError, CredentialManager, GroupPublicKey,
+    JoinResponse, Signature, UserCredentials, BIG_SIZE, ECP2_COMPAT_SIZE, ECP_SIZE,
+    GROUP_PUBLIC_KEY_SIZE, JOIN_REQUEST_SIZE, JOIN_RESPONSE_SIZE, SIGNATURE_SIZE,
+    USER_CREDENTIALS_SIZE,
+};
+
+pub const AC_BIG_SIZE: usize = 32;
+pub const AC_ECP_SIZE: usize = 65;
+pub const AC_ECP2_SIZE: usize = 128;
+pub const AC_ECP_PROOF_SIZE: usize = AC_BIG_SIZE * 2;
+pub const AC_JOIN_REQUEST_SIZE: usize = AC_ECP_SIZE + AC_ECP_PROOF_SIZE;
+pub const AC_USER_CREDENTIALS_SIZE: usize = AC_ECP_SIZE * 4;
+pub const AC_JOIN_RESPONSE_SIZE: usize = AC_USER_CREDENTIALS_SIZE + AC_ECP_PROOF_SIZE;
+pub const AC_GROUP_PUBLIC_KEY_SIZE: usize = AC_ECP2_SIZE * 2 + AC_BIG_SIZE * 4;
+pub const AC_SIGNATURE_SIZE: usize = AC_ECP_SIZE * 5 + AC_ECP_PROOF_SIZE;
+/// gsk followed by the credentials, as written by `GS_finishJoin`.
+pub const GS_USER_CREDENTIALS_SIZE: usize = AC_BIG_SIZE + AC_USER_CREDENTIALS_SIZE;
+
+const _: () = {
+    assert!(AC_BIG_SIZE == BIG_SIZE);
+    assert!(AC_ECP_SIZE == ECP_SIZE);
+    assert!(AC_ECP2_SIZE == ECP2_COMPAT_SIZE);
+    assert!(AC_JOIN_REQUEST_SIZE == JOIN_REQUEST_SIZE);
+    assert!(AC_JOIN_RESPONSE_SIZE == JOIN_RESPONSE_SIZE);
+    assert!(AC_USER_CREDENTIALS_SIZE == USER_CREDENTIALS_SIZE);
+    assert!(AC_GROUP_PUBLIC_KEY_SIZE == GROUP_PUBLIC_KEY_SIZE);
+    assert!(AC_SIGNATURE_SIZE == SIGNATURE_SIZE);
+};
+
+/// Opaque handle to a `CredentialManager`.
+pub struct AcCredentialManager(CredentialManager);
+
+/// Result code returned by every fallible function. Apart from `Ok`,
+/// `NullPointer` and `BufferTooSmall`, each code corresponds to a
+/// `CredentialError` variant.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AcResult {
+    Ok = 0,
+    BadEcp = 1,
+    BadEcp2 = 2,
+    BadBig = 3,
+    BadEcpProof = 4,
+    BadUserCredentials = 5,
+    BadJoinResponse = 6,
+    GroupPublicKeyLength = 7,
+    JoinResponseValidation = 8,
+    CredentialsNotSet = 9,
+    BadGroupPublicKey = 10,
+    UnknownCiphersuite = 11,
+    BadSignature = 12,
+    SignatureValidation = 13,
+    BadJoinRequest = 14,
+    BadAttributes = 15,
+    AttributeOutOfRange = 16,
+    CounterOutOfRange = 17,
+    BadKTimesSignature = 18,
+    BadBasename = 19,
+    BadUnlinkableSignature = 20,
+    CredentialsValidation = 21,
+    BadMigrationRequest = 22,
+    MigrationValidation = 23,
+    BadCredentialMetadata = 24,
+    CredentialsExpired = 25,
+    BadThresholdMessage = 26,
+    ThresholdIssuance = 27,
+    KeyGeneration = 28,
+    BadKeyLogMessage = 29,
+    KeyLogVerification = 30,
+    NullPointer = 100,
+    /// An output buffer's length, passed in through a pointer, is too small.
+    BufferTooSmall = 101,
+}
+
+impl From<CredentialError> for AcResult {
+    fn from(err: CredentialError) -> Self {
+        match err {
+            CredentialError::BadECP => AcResult::BadEcp,
+            CredentialError::BadECP2 => AcResult::BadEcp2,
+            CredentialError::BadBIG => AcResult::BadBig,
+            CredentialError::BadECPProof => AcResult::BadEcpProof,
+            CredentialError::BadUserCredentials => AcResult::BadUserCredentials,
+            CredentialError::BadJoinResponse => AcResult::BadJoinResponse,
+            CredentialError::GroupPublicKeyLength => AcResult::GroupPublicKeyLength,
+            CredentialError::JoinResponseValidation => AcResult::JoinResponseValidation,
+            CredentialError::CredentialsNotSet => AcResult::CredentialsNotSet,
+            CredentialError::BadGroupPublicKey => AcResult::BadGroupPublicKey,
+            CredentialError::UnknownCiphersuite => AcResult::UnknownCiphersuite,
+            CredentialError::BadSignature => AcResult::BadSignature,
+            CredentialError::SignatureValidation => AcResult::SignatureValidation,
+            CredentialError::BadJoinRequest => AcResult::BadJoinRequest,
+            CredentialError::BadAttributes => AcResult::BadAttributes,
+            CredentialError::AttributeOutOfRange => AcResult::AttributeOutOfRange,
+            CredentialError::CounterOutOfRange => AcResult::CounterOutOfRange,
+            CredentialError::BadKTimesSignature => AcResult::BadKTimesSignature,
+            CredentialError::BadBasename => AcResult::BadBasename,
+            CredentialError::BadUnlinkableSignature => AcResult::BadUnlinkableSignature,
+            CredentialError::CredentialsValidation => AcResult::CredentialsValidation,
+            CredentialError::BadMigrationRequest => AcResult::BadMigrationRequest,
+            CredentialError::MigrationValidation => AcResult::MigrationValidation,
+            CredentialError::BadCredentialMetadata => AcResult::BadCredentialMetadata,
+            CredentialError::CredentialsExpired => AcResult::CredentialsExpired,
+            CredentialError::BadThresholdMessage => AcResult::BadThresholdMessage,
+            CredentialError::ThresholdIssuance => AcResult::ThresholdIssuance,
+            CredentialError::KeyGeneration => AcResult::KeyGeneration,
+            CredentialError::BadKeyLogMessage => AcResult::BadKeyLogMessage,
+            CredentialError::KeyLogVerification => AcResult::KeyLogVerification,
+        }
+    }
+}
+
+/// Returns an empty slice for a zero length, so that callers may pass NULL
+/// for empty messages and basenames.
+unsafe fn input<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(slice::from_raw_parts(ptr, len)),
+    }
+}
+
+unsafe fn output<'a>(ptr: *mut u8, len: usize) -> Option<&'a mut [u8]> {
+    match ptr.is_null() {
+        true => None,
+        false => Some(slice::from_raw_parts_mut(ptr, len)),
+    }
+}
+
+macro_rules! try_ac {
+    ($expr:expr) => {
+        match $expr {
+            Some(value) => value,
+            None => return AcResult::NullPointer,
+        }
+    };
+}
+
+/// Writes `bytes` to an output buffer whose capacity `*len` is replaced by
+/// the number of bytes written, as the C library's functions do.
+unsafe fn write_output(bytes: &[u8], out: *mut u8, len: *mut c_int) -> AcResult {
+    let capacity = match len.as_ref() {
+        Some(&capacity) => capacity,
+        None => return AcResult::NullPointer,
+    };
+    if usize::try_from(capacity).map_or(true, |capacity| capacity < bytes.len()) {
+        return AcResult::BufferTooSmall;
+    }
+    let out = try_ac!(output(out, bytes.len()));
+    out.copy_from_slice(bytes);
+    *len = bytes.len() as c_int;
+    AcResult::Ok
+}
+
+unsafe fn input_int<'a>(ptr: *const u8, len: c_int) -> Option<&'a [u8]> {
+    input(ptr, usize::try_from(len).ok()?)
+}
+
+macro_rules! try_cred {
+    ($expr:expr) => {
+        match $expr {
+            Ok(value) => value,
+            Err(err) => return AcResult::from(err),
+        }
+    };
+}
+
+/// Creates a manager seeded from the operating system's entropy source.
+/// Must be released with `ac_credential_manager_free`.
+#[no_mangle]
+pub extern "C" fn ac_credential_manager_new() -> *mut AcCredentialManager {
+    Box::into_raw(Box::new(AcCredentialManager(CredentialManager::new())))
+}
+
+/// Creates a manager seeded with caller-supplied entropy, or returns NULL
+/// if `entropy` is NULL. Must be released with `ac_credential_manager_free`.
+///
+/// # Safety
+///
+/// `entropy` must point to `entropy_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ac_credential_manager_new_with_seed(
+    entropy: *const u8,
+    entropy_len: usize,
+) -> *mut AcCredentialManager {
+    match input(entropy, entropy_len) {
+        Some(entropy) => Box::into_raw(Box::new(AcCredentialManager(
+            CredentialManager::new_with_seed(entropy),
+        ))),
+        None => ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `manager` must be NULL or a pointer returned by one of the
+/// `ac_credential_manager_new*` functions that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ac_credential_manager_free(manager: *mut AcCredentialManager) {
+    if !manager.is_null() {
+        drop(Box::from_raw(manager));
+    }
+}
+
+/// Selects the ciphersuite by its `Ciphersuite::id`, which must be one on
+/// BN254.
+///
+/// # Safety
+///
+/// `manager` must be a live pointer returned by `ac_credential_manager_new*`.
+#[no_mangle]
+pub unsafe extern "C" fn ac_set_ciphersuite(manager: *mut AcCredentialManager, id: u8) -> AcResult {
+    let manager = &mut try_ac!(manager.as_mut()).0;
+    try_cred!(manager.set_ciphersuite(try_cred!(Ciphersuite::try_from(id))));
+    AcResult::Ok
+}
+
+/// Writes a new gsk to `gsk_out` (`AC_BIG_SIZE` bytes) and the join request
+/// to `join_msg_out` (`AC_JOIN_REQUEST_SIZE` bytes).
+///
+/// # Safety
+///
+/// `manager` must be a live pointer returned by `ac_credential_manager_new*`,
+/// `challenge` must point to `challenge_len` readable bytes and the output
+/// buffers must be writable for their documented sizes.
+#[no_mangle]
+pub unsafe extern "C" fn ac_start_join(
+    manager: *mut AcCredentialManager,
+    challenge: *const u8,
+    challenge_len: usize,
+    gsk_out: *mut u8,
+    join_msg_out: *mut u8,
+) -> AcResult {
+    let manager = &mut try_ac!(manager.as_mut()).0;
+    let challenge = try_ac!(input(challenge, challenge_len));
+    let gsk_out = try_ac!(output(gsk_out, AC_BIG_SIZE));
+    let join_msg_out = try_ac!(output(join_msg_out, AC_JOIN_REQUEST_SIZE));
+
+    let result = manager.start_join(challenge);
+    gsk_out.copy_from_slice(&result.gsk.to_bytes());
+    join_msg_out.copy_from_slice(&result.join_msg.to_bytes());
+    AcResult::Ok
+}
+
+/// Validates the issuer's join response and writes the credentials to
+/// `credentials_out` (`AC_USER_CREDENTIALS_SIZE` bytes).
+///
+/// # Safety
+///
+/// `manager` must be a live pointer returned by `ac_credential_manager_new*`,
+/// every input must point to its given number of readable bytes and
+/// `credentials_out` must be writable for `AC_USER_CREDENTIALS_SIZE` bytes.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ac_finish_join(
+    manager: *mut AcCredentialManager,
+    public_key: *const u8,
+    public_key_len: usize,
+    gsk: *const u8,
+    gsk_len: usize,
+    join_response: *const u8,
+    join_response_len: usize,
+    credentials_out: *mut u8,
+) -> AcResult {
+    let manager = &mut try_ac!(manager.as_mut()).0;
+    let public_key = try_ac!(input(public_key, public_key_len));
+    let gsk = try_ac!(input(gsk, gsk_len));
+    let join_response = try_ac!(input(join_response, join_response_len));
+    let credentials_out = try_ac!(output(credentials_out, AC_USER_CREDENTIALS_SIZE));
+
+    let public_key: GroupPublicKey = try_cred!(public_key.try_into());
+    let gsk: CredentialBIG = try_cred!(gsk.try_into());
+    let join_response: JoinResponse = try_cred!(join_response.try_into());
+
+    let credentials = try_cred!(manager.finish_join(&public_key, &gsk, join_response));
+    credentials_out.copy_from_slice(&credentials.to_bytes());
+    AcResult::Ok
+}
+
+/// # Safety
+///
+/// `manager` must be a live pointer returned by `ac_credential_manager_new*`
+/// and every input must point to its given number of readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ac_set_gsk_and_credentials(
+    manager: *mut AcCredentialManager,
+    gsk: *const u8,
+    gsk_len: usize,
+    credentials: *const u8,
+    credentials_len: usize,
+) -> AcResult {
+    let manager = &mut try_ac!(manager.as_mut()).0;
+    let gsk = try_ac!(input(gsk, gsk_len));
+    let credentials = try_ac!(input(credentials, credentials_len));
+
+    let gsk: CredentialBIG = try_cred!(gsk.try_into());
+    let credentials: UserCredentials = try_cred!(credentials.try_into());
+
+    manager.set_gsk_and_credentials(gsk, credentials);
+    AcResult::Ok
+}
+
+/// Signs `msg` under `basename` and writes the signature to `signature_out`
+/// (`AC_SIGNATURE_SIZE` bytes).
+///
+/// # Safety
+///
+/// `manager` must be a live pointer returned by `ac_credential_manager_new*`,
+/// every input must point to its given number of readable bytes and
+/// `signature_out` must be writable for `AC_SIGNATURE_SIZE` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ac_sign(
+    manager: *mut AcCredentialManager,
+    msg: *const u8,
+    msg_len: usize,
+    basename: *const u8,
+    basename_len: usize,
+    signature_out: *mut u8,
+) -> AcResult {
+    let manager = &mut try_ac!(manager.as_mut()).0;
+    let msg = try_ac!(input(msg, msg_len));
+    let basename = try_ac!(input(basename, basename_len));
+    let signature_out = try_ac!(output(signature_out, AC_SIGNATURE_SIZE));
+
+    let signature = try_cred!(manager.sign(msg, basename));
+    signature_out.copy_from_slice(&signature.to_bytes());
+    AcResult::Ok
+}
+
+/// Verifies a signature on `msg` under `basename` against the group public
+/// key, using the ciphersuite with the given `Ciphersuite::id`.
+///
+/// # Safety
+///
+/// Every input must point to its given number of readable bytes.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ac_verify(
+    ciphersuite: u8,
+    public_key: *const u8,
+    public_key_len: usize,
+    msg: *const u8,
+    msg_len: usize,
+    basename: *const u8,
+    basename_len: usize,
+    signature: *const u8,
+    signature_len: usize,
+) -> AcResult {
+    let public_key = try_ac!(input(public_key, public_key_len));
+    let msg = try_ac!(input(msg, msg_len));
+    let basename = try_ac!(input(basename, basename_len));
+    let signature = try_ac!(input(signature, signature_len));
+
+    let suite = try_cred!(Ciphersuite::try_from(ciphersuite));
+    let public_key: GroupPublicKey = try_cred!(public_key.try_into());
+    let signature: Signature = try_cred!(signature.try_into());
+
+    try_cred!(verify(suite, &public_key, &signature, msg, basename));
+    AcResult::Ok
+}
+
+/// State of the `GS_*` functions: a manager, the group public key and the
+/// gsk of a join in progress. Lengths are `int`s, output lengths pass the
+/// buffer's capacity in and the bytes written out, and functions return an
+/// `int` that is 0 on success and otherwise the `AcResult` code.
+pub struct GsState {
+    manager: CredentialManager,
+    public_key: Option<GroupPublicKey>,
+    pending_gsk: Option<[u8; BIG_SIZE]>,
+}
+
+/// Runs the body of a `GS_*` function and returns its result as the `int`
+/// status of the C library.
+fn gs_status(body: impl FnOnce() -> AcResult) -> c_int {
+    body() as c_int
+}
+
+/// Bytes to allocate for a `GsState`.
+#[no_mangle]
+pub extern "C" fn GS_getStateSize() -> c_int {
+    mem::size_of::<GsState>() as c_int
+}
+
+/// Initializes `state`, seeding it from the operating system's entropy
+/// source. Must be released with `GS_destroyState`.
+///
+/// # Safety
+///
+/// `state` must point to `GS_getStateSize()` writable bytes, aligned as
+/// `malloc` aligns them.
+#[no_mangle]
+pub unsafe extern "C" fn GS_initState(state: *mut GsState) -> c_int {
+    gs_status(|| {
+        if state.is_null() {
+            return AcResult::NullPointer;
+        }
+        state.write(GsState {
+            manager: CredentialManager::new(),
+            public_key: None,
+            pending_gsk: None,
+        });
+        AcResult::Ok
+    })
+}
+
+/// # Safety
+///
+/// `state` must be NULL or initialized by `GS_initState` and not destroyed
+/// yet. Its memory may be freed afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn GS_destroyState(state: *mut GsState) {
+    if !state.is_null() {
+        ptr::drop_in_place(state);
+    }
+}
+
+/// Reseeds the state's random number generator, keeping any loaded key and
+/// credentials. Does nothing if `state` or `seed` is NULL.
+///
+/// # Safety
+///
+/// `state` must be initialized by `GS_initState` and `seed` must point to
+/// `seed_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn GS_seed(state: *mut GsState, seed: *const u8, seed_len: c_int) {
+    if let (Some(state), Some(seed)) = (state.as_mut(), input_int(seed, seed_len)) {
+        state.manager.rng.seed(seed.len(), seed);
+    }
+}
+
+/// # Safety
+///
+/// `state` must be initialized by `GS_initState` and `public_key` must
+/// point to `public_key_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn GS_loadGroupPubKey(
+    state: *mut GsState,
+    public_key: *const u8,
+    public_key_len: c_int,
+) -> c_int {
+    gs_status(|| {
+        let state = try_ac!(state.as_mut());
+        let public_key = try_ac!(input_int(public_key, public_key_len));
+        state.public_key = Some(try_cred!(public_key.try_into()));
+        AcResult::Ok
+    })
+}
+
+/// Starts a join, keeping the new gsk in `state` for `GS_finishJoin`, and
+/// writes the join request to `join_msg`.
+///
+/// # Safety
+///
+/// `state` must be initialized by `GS_initState`, `challenge` must point
+/// to `challenge_len` readable bytes and `join_msg` must be writable for
+/// `*join_msg_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn GS_startJoin(
+    state: *mut GsState,
+    challenge: *const u8,
+    challenge_len: c_int,
+    join_msg: *mut u8,
+    join_msg_len: *mut c_int,
+) -> c_int {
+    gs_status(|| {
+        let state = try_ac!(state.as_mut());
+        let challenge = try_ac!(input_int(challenge, challenge_len));
+
+        let result = state.manager.start_join(challenge);
+        let status = write_output(&result.join_msg.to_bytes(), join_msg, join_msg_len);
+        if status == AcResult::Ok {
+            state.pending_gsk = Some(result.gsk.to_bytes());
+        }
+        status
+    })
+}
+
+/// Validates the join response against the loaded group public key, loads
+/// the credentials and writes them to `credentials` in the format read by
+/// `GS_loadUserCredentials` (`GS_USER_CREDENTIALS_SIZE` bytes).
+///
+/// # Safety
+///
+/// `state` must be initialized by `GS_initState`, `join_response` must
+/// point to `join_response_len` readable bytes and `credentials` must be
+/// writable for `*credentials_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn GS_finishJoin(
+    state: *mut GsState,
+    join_response: *const u8,
+    join_response_len: c_int,
+    credentials: *mut u8,
+    credentials_len: *mut c_int,
+) -> c_int {
+    gs_status(|| {
+        let state = try_ac!(state.as_mut());
+        let join_response = try_ac!(input_int(join_response, join_response_len));
+
+        let public_key = match &state.public_key {
+            Some(public_key) => public_key,
+            None => return AcResult::BadGroupPublicKey,
+        };
+        let gsk = match &state.pending_gsk {
+            Some(gsk) => try_cred!(CredentialBIG::try_from(&gsk[..])),
+            None => return AcResult::CredentialsNotSet,
+        };
+        let join_response: JoinResponse = try_cred!(join_response.try_into());
+        let user_credentials =
+            try_cred!(state.manager.finish_join(public_key, &gsk, join_response));
+
+        let mut bytes = [0u8; GS_USER_CREDENTIALS_SIZE];
+        bytes[..AC_BIG_SIZE].copy_from_slice(&gsk.to_bytes());
+        bytes[AC_BIG_SIZE..].copy_from_slice(&user_credentials.to_bytes());
+        let status = write_output(&bytes, credentials, credentials_len);
+        if status == AcResult::Ok {
+            state.pending_gsk = None;
+            state.manager.set_gsk_and_credentials(gsk, user_credentials);
+        }
+        status
+    })
+}
+
+/// Loads the gsk and credentials written by `GS_finishJoin`.
+///
+/// # Safety
+///
+/// `state` must be initialized by `GS_initState` and `credentials` must
+/// point to `credentials_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn GS_loadUserCredentials(
+    state: *mut GsState,
+    credentials: *const u8,
+    credentials_len: c_int,
+) -> c_int {
+    gs_status(|| {
+        let state = try_ac!(state.as_mut());
+        let credentials = try_ac!(input_int(credentials, credentials_len));
+        if credentials.len() != GS_USER_CREDENTIALS_SIZE {
+            return AcResult::BadUserCredentials;
+        }
+
+        let (gsk, credentials) = credentials.split_at(AC_BIG_SIZE);
+        let gsk: CredentialBIG = try_cred!(gsk.try_into());
+        let credentials: UserCredentials = try_cred!(credentials.try_into());
+        state.manager.set_gsk_and_credentials(gsk, credentials);
+        AcResult::Ok
+    })
+}
+
+/// Signs `msg` under `basename` and writes the signature to `signature`
+/// (`AC_SIGNATURE_SIZE` bytes).
+///
+/// # Safety
+///
+/// `state` must be initialized by `GS_initState`, every input must point
+/// to its given number of readable bytes and `signature` must be writable
+/// for `*signature_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn GS_sign(
+    state: *mut GsState,
+    msg: *const u8,
+    msg_len: c_int,
+    basename: *const u8,
+    basename_len: c_int,
+    signature: *mut u8,
+    signature_len: *mut c_int,
+) -> c_int {
+    gs_status(|| {
+        let state = try_ac!(state.as_mut());
+        let msg = try_ac!(input_int(msg, msg_len));
+        let basename = try_ac!(input_int(basename, basename_len));
+
+        let result = try_cred!(state.manager.sign(msg, basename));
+        write_output(&result.to_bytes(), signature, signature_len)
+    })
+}
+
+/// Verifies a signature against the loaded group public key.
+///
+/// # Safety
+///
+/// `state` must be initialized by `GS_initState` and every input must
+/// point to its given number of readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn GS_verify(
+    state: *mut GsState,
+    msg: *const u8,
+    msg_len: c_int,
+    basename: *const u8,
+    basename_len: c_int,
+    signature: *const u8,
+    signature_len: c_int,
+) -> c_int {
+    gs_status(|| {
+        let state = try_ac!(state.as_ref());
+        let msg = try_ac!(input_int(msg, msg_len));
+        let basename = try_ac!(input_int(basename, basename_len));
+        let signature = try_ac!(input_int(signature, signature_len));
+
+        let public_key = match &state.public_key {
+            Some(public_key) => public_key,
+            None => return AcResult::BadGroupPublicKey,
+        };
+        let signature: Signature = try_cred!(signature.try_into());
+        try_cred!(verify(
+            state.manager.ciphersuite(),
+            public_key,
+            &signature,
+            msg,
+            basename
+        ));
+        AcResult::Ok
+    })
+}
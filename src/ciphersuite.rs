@@ -0,0 +1,164 @@
+use super::backend::{PairingBackend, BIG_SIZE};
+use super::util::hash256;
+use super::CredentialError;
+
+/// Pairing-friendly curve on which a ciphersuite operates. Each backend
+/// implements one curve, and a credential manager only uses the suites of
+/// its backend's curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    Bn254,
+    Bls12381,
+}
+
+impl Curve {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Curve::Bn254 => "bn254",
+            Curve::Bls12381 => "bls12381",
+        }
+    }
+
+    /// Size of an encoded scalar, and of the hashes mapped to scalars.
+    pub const fn big_size(&self) -> usize {
+        match self {
+            Curve::Bn254 | Curve::Bls12381 => 32,
+        }
+    }
+
+    /// Size of an encoded field element.
+    pub const fn field_size(&self) -> usize {
+        match self {
+            Curve::Bn254 => 32,
+            Curve::Bls12381 => 48,
+        }
+    }
+
+    /// Size of an uncompressed G1 point.
+    pub const fn ecp_size(&self) -> usize {
+        self.field_size() * 2 + 1
+    }
+
+    /// Size of a G2 point in the C library's compat encoding.
+    pub const fn ecp2_size(&self) -> usize {
+        self.field_size() * 4
+    }
+
+    /// Suite a new credential manager on the curve starts with.
+    pub const fn default_suite(&self) -> Ciphersuite {
+        match self {
+            Curve::Bn254 => Ciphersuite::Bn254Sha256Legacy,
+            Curve::Bls12381 => Ciphersuite::Bls12381Sha256V1,
+        }
+    }
+
+    /// The newest suite of the curve.
+    pub const fn latest_suite(&self) -> Ciphersuite {
+        match self {
+            Curve::Bn254 => Ciphersuite::Bn254Sha256V1,
+            Curve::Bls12381 => Ciphersuite::Bls12381Sha256V1,
+        }
+    }
+
+    pub fn suites(&self) -> impl Iterator<Item = Ciphersuite> + '_ {
+        Ciphersuite::ALL
+            .into_iter()
+            .filter(move |suite| suite.curve() == *self)
+    }
+}
+
+/// Hash function used for messages, basenames and proof challenges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashFunction {
+    Sha256,
+}
+
+/// Curve, hash, hash-to-curve and challenge encoding used by every
+/// operation of a credential manager.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Ciphersuite {
+    /// BN254 with SHA-256, mapping basenames with `ECP::mapit` and hashing
+    /// bare concatenations of points for challenges, matching the byte
+    /// layout of the original C library.
+    #[default]
+    Bn254Sha256Legacy,
+    /// Same as `Bn254Sha256Legacy`, except that challenges hash a labeled
+    /// transcript prefixed with a versioned domain separator and the name
+    /// of the proof's protocol step.
+    Bn254Sha256V1,
+    /// BLS12-381 with SHA-256 and the labeled transcripts of
+    /// `Bn254Sha256V1`. Basenames are mapped like `ECP::mapit` followed by
+    /// clearing the cofactor. Needs the `bls12-381` feature for a backend.
+    Bls12381Sha256V1,
+}
+
+impl Ciphersuite {
+    pub const ALL: [Ciphersuite; 3] = [
+        Ciphersuite::Bn254Sha256Legacy,
+        Ciphersuite::Bn254Sha256V1,
+        Ciphersuite::Bls12381Sha256V1,
+    ];
+
+    /// Stable identifier of the suite, suitable for wire formats. Ids are
+    /// unique across curves.
+    pub fn id(&self) -> u8 {
+        match self {
+            Ciphersuite::Bn254Sha256Legacy => 0,
+            Ciphersuite::Bn254Sha256V1 => 1,
+            Ciphersuite::Bls12381Sha256V1 => 2,
+        }
+    }
+
+    pub const fn curve(&self) -> Curve {
+        match self {
+            Ciphersuite::Bn254Sha256Legacy | Ciphersuite::Bn254Sha256V1 => Curve::Bn254,
+            Ciphersuite::Bls12381Sha256V1 => Curve::Bls12381,
+        }
+    }
+
+    pub fn hash_function(&self) -> HashFunction {
+        HashFunction::Sha256
+    }
+
+    /// Domain separator absorbed at the start of every transcript, or
+    /// `None` if the suite uses the unlabeled legacy layout.
+    pub(crate) fn domain_separator(&self) -> Option<&'static [u8]> {
+        match self {
+            Ciphersuite::Bn254Sha256Legacy => None,
+            Ciphersuite::Bn254Sha256V1 => Some(b"anonymous-credentials/v1/bn254-sha256"),
+            Ciphersuite::Bls12381Sha256V1 => Some(b"anonymous-credentials/v1/bls12381-sha256"),
+        }
+    }
+
+    /// Fails with `UnknownCiphersuite` unless the suite is on the curve of
+    /// backend `B`.
+    pub(crate) fn check<B: PairingBackend>(&self) -> Result<(), CredentialError> {
+        match self.curve() == B::CURVE {
+            true => Ok(()),
+            false => Err(CredentialError::UnknownCiphersuite),
+        }
+    }
+
+    pub(crate) fn hash(&self, data: &[u8]) -> [u8; BIG_SIZE] {
+        match self.hash_function() {
+            HashFunction::Sha256 => hash256(data),
+        }
+    }
+
+    /// Maps arbitrary bytes, such as a basename, to a point in G1 with the
+    /// map of the curve's backend.
+    pub(crate) fn hash_to_g1<B: PairingBackend>(&self, data: &[u8]) -> B::G1 {
+        B::g1_from_hash(&self.hash(data))
+    }
+}
+
+impl TryFrom<u8> for Ciphersuite {
+    type Error = CredentialError;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        Ciphersuite::ALL
+            .into_iter()
+            .find(|suite| suite.id() == id)
+            .ok_or(CredentialError::UnknownCiphersuite)
+    }
+}
@@ -1,217 +1,395 @@
-use brave_miracl::bn254::{
-    big::{self, BIG},
-    ecp::ECP,
-    ecp2::ECP2,
-    fp2::FP2,
-};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use brave_miracl::hash256::HASH256;
 
+use super::backend::{PairingBackend, BIG_SIZE, MAX_ECP2_SIZE};
+use super::ciphersuite::Ciphersuite;
+use super::migrate::epoch_group_public_key_is_valid;
 use super::CredentialError;
 
-pub const BIG_SIZE: usize = big::MODBYTES;
-pub const ECP_SIZE: usize = BIG_SIZE * 2 + 1;
-pub const ECP2_COMPAT_SIZE: usize = BIG_SIZE * 4;
-
 pub const ECP_PROOF_SIZE: usize = BIG_SIZE * 2;
-pub const JOIN_REQUEST_SIZE: usize = ECP_SIZE + ECP_PROOF_SIZE;
-pub const USER_CREDENTIALS_SIZE: usize = ECP_SIZE * 4;
-pub const JOIN_RESPONSE_SIZE: usize = USER_CREDENTIALS_SIZE + ECP_PROOF_SIZE;
-pub const GROUP_PUBLIC_KEY_SIZE: usize = ECP2_COMPAT_SIZE * 2 + BIG_SIZE * 4;
-pub const SIGNATURE_SIZE: usize = ECP_SIZE * 5 + ECP_PROOF_SIZE;
+pub const GROUP_KEY_ID_SIZE: usize = 32;
+pub const CREDENTIAL_METADATA_SIZE: usize = 8 * 2 + GROUP_KEY_ID_SIZE;
+
+/// Sizes of the encodings on the curve of a backend, which the crate root
+/// and the `bls12_381` module export as constants.
+pub(crate) trait EncodingSizes: PairingBackend {
+    const JOIN_REQUEST_SIZE: usize = Self::G1_SIZE + ECP_PROOF_SIZE;
+    const USER_CREDENTIALS_SIZE: usize = Self::G1_SIZE * 4;
+    const JOIN_RESPONSE_SIZE: usize = Self::USER_CREDENTIALS_SIZE + ECP_PROOF_SIZE;
+    const GROUP_PUBLIC_KEY_SIZE: usize = Self::G2_SIZE * 2 + BIG_SIZE * 4;
+    const SIGNATURE_SIZE: usize = Self::G1_SIZE * 5 + ECP_PROOF_SIZE;
+    const UNLINKABLE_SIGNATURE_SIZE: usize = Self::G1_SIZE * 4 + ECP_PROOF_SIZE;
+    const MIGRATION_REQUEST_SIZE: usize =
+        Self::JOIN_REQUEST_SIZE + Self::USER_CREDENTIALS_SIZE + ECP_PROOF_SIZE;
+    const EPOCH_GROUP_PUBLIC_KEY_SIZE: usize = 1 + 8 + Self::GROUP_PUBLIC_KEY_SIZE + BIG_SIZE * 3;
+}
+
+impl<B: PairingBackend> EncodingSizes for B {}
+
+/// Implements the methods returning fixed-size arrays for the concrete
+/// types of each backend, since their sizes depend on the curve. Each
+/// method fills its array with the generic `$write`, and the size is the
+/// constant of that name at the crate root or in `bls12_381`.
+macro_rules! impl_fixed_size {
+    ($($(#[$doc:meta])* $ty:ident::$method:ident($write:ident) -> $size:ident;)*) => {
+        $(
+            impl $ty<crate::Bn254> {
+                $(#[$doc])*
+                pub fn $method(&self) -> [u8; crate::$size] {
+                    let mut bytes = [0u8; crate::$size];
+                    self.$write(&mut bytes);
+                    bytes
+                }
+            }
+
+            #[cfg(feature = "bls12-381")]
+            impl $ty<crate::bls12_381::Bls12381> {
+                $(#[$doc])*
+                pub fn $method(&self) -> [u8; crate::bls12_381::$size] {
+                    let mut bytes = [0u8; crate::bls12_381::$size];
+                    self.$write(&mut bytes);
+                    bytes
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) use impl_fixed_size;
 
-pub struct JoinRequest {
-    pub(crate) q: ECP, // G1 ** gsk
+const GROUP_KEY_ID_DOMAIN: &[u8] = b"anonymous-credentials group key id";
 
-    pub(crate) proof: ECPProof,
+pub struct JoinRequest<B: PairingBackend> {
+    pub(crate) q: B::G1, // G1 ** gsk
+
+    pub(crate) proof: ECPProof<B>,
+}
+
+pub struct JoinResponse<B: PairingBackend> {
+    pub(crate) cred: UserCredentials<B>,
+    pub(crate) proof: ECPProof<B>,
 }
 
-pub struct JoinResponse {
-    pub(crate) cred: UserCredentials,
-    pub(crate) proof: ECPProof,
+pub struct UserCredentials<B: PairingBackend> {
+    pub(crate) a: B::G1,
+    pub(crate) b: B::G1,
+    pub(crate) c: B::G1,
+    pub(crate) d: B::G1,
 }
 
-pub struct UserCredentials {
-    pub(crate) a: ECP,
-    pub(crate) b: ECP,
-    pub(crate) c: ECP,
-    pub(crate) d: ECP,
+pub struct Signature<B: PairingBackend> {
+    pub(crate) a: B::G1,
+    pub(crate) b: B::G1,
+    pub(crate) c: B::G1,
+    pub(crate) d: B::G1,
+    pub(crate) nym: B::G1,
+
+    pub(crate) proof: ECPProof<B>,
 }
 
-pub struct Signature {
-    pub(crate) a: ECP,
-    pub(crate) b: ECP,
-    pub(crate) c: ECP,
-    pub(crate) d: ECP,
-    pub(crate) nym: ECP,
+/// Signature without a nym, which proves membership only and cannot be
+/// linked to any other signature.
+pub struct UnlinkableSignature<B: PairingBackend> {
+    pub(crate) a: B::G1,
+    pub(crate) b: B::G1,
+    pub(crate) c: B::G1,
+    pub(crate) d: B::G1,
 
-    pub(crate) proof: ECPProof,
+    pub(crate) proof: ECPProof<B>,
 }
 
-pub struct GroupPublicKey {
-    pub(crate) x: ECP2, // G2 ** x
-    pub(crate) y: ECP2, // G2 ** y
+#[derive(Clone)]
+pub struct GroupPublicKey<B: PairingBackend> {
+    pub(crate) x: B::G2, // G2 ** x
+    pub(crate) y: B::G2, // G2 ** y
 
     // ZK of discrete-log knowledge for X and Y
-    pub(crate) cx: BIG,
-    pub(crate) sx: BIG,
-    pub(crate) cy: BIG,
-    pub(crate) sy: BIG,
+    pub(crate) cx: B::Scalar,
+    pub(crate) sx: B::Scalar,
+    pub(crate) cy: B::Scalar,
+    pub(crate) sy: B::Scalar,
 }
 
-pub struct ECPProof {
-    pub(crate) c: BIG,
-    pub(crate) s: BIG,
+/// Identifier of a group public key, a hash of X and Y. The proofs of
+/// discrete-log knowledge are left out, so every encoding of the same key
+/// gets the same id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupKeyId(pub [u8; GROUP_KEY_ID_SIZE]);
+
+/// Group public key of one issuer key epoch. Epochs increase with every
+/// rotation of the key.
+///
+/// The issuer proves knowledge of x and y once more, under a `migrate`
+/// transcript that absorbs the epoch, so that a key cannot be presented
+/// under an epoch other than the one it was made for. The encoding is
+/// `suite id || epoch || key || c || sx || sy`, with a big-endian epoch,
+/// and decoding verifies both the key's proofs and the epoch's.
+#[derive(Clone)]
+pub struct EpochGroupPublicKey<B: PairingBackend> {
+    pub(crate) suite: Ciphersuite,
+    pub(crate) epoch: u64,
+    pub(crate) key: GroupPublicKey<B>,
+
+    // ZK of discrete-log knowledge for X and Y, with one challenge
+    pub(crate) c: B::Scalar,
+    pub(crate) sx: B::Scalar,
+    pub(crate) sy: B::Scalar,
 }
 
-pub struct CredentialBIG(pub(crate) BIG);
+/// Request for credentials under a new issuer key, proving credentials
+/// under the old one for the same gsk.
+pub struct MigrationRequest<B: PairingBackend> {
+    pub(crate) join: JoinRequest<B>,
+    // Old credentials, randomized as in a signature
+    pub(crate) cred: UserCredentials<B>,
+    // log_G1(Q) = log_B(D)
+    pub(crate) proof: ECPProof<B>,
+}
+
+/// Issuance metadata the issuer binds to a join response. Times are in
+/// seconds since the Unix epoch.
+///
+/// The encoding is `issued_at || not_after || group`, with big-endian
+/// integers. An issuer binds it by absorbing the encoding into the
+/// transcript of the response's proof right after the protocol name, before
+/// any point: as the value labeled `metadata` under a labeled ciphersuite,
+/// and as raw bytes under the legacy one. The rest of the proof is that of
+/// a plain join response; `test-vectors/join_response.json` has cases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CredentialMetadata {
+    pub issued_at: u64,
+    /// Last second at which the credentials may be used.
+    pub not_after: u64,
+    pub group: GroupKeyId,
+}
+
+pub struct ECPProof<B: PairingBackend> {
+    pub(crate) c: B::Scalar,
+    pub(crate) s: B::Scalar,
+}
+
+pub struct CredentialBIG<B: PairingBackend>(pub(crate) B::Scalar);
 
-pub struct StartJoinResult {
-    pub gsk: CredentialBIG,
-    pub join_msg: JoinRequest,
+pub struct StartJoinResult<B: PairingBackend> {
+    pub gsk: CredentialBIG<B>,
+    pub join_msg: JoinRequest<B>,
 }
 
-pub(crate) fn ecp_from_bytes(bytes: &[u8]) -> Result<ECP, CredentialError> {
-    if bytes.len() != ECP_SIZE {
+pub(crate) fn ecp_from_bytes<B: PairingBackend>(bytes: &[u8]) -> Result<B::G1, CredentialError> {
+    if bytes.len() != B::G1_SIZE {
         return Err(CredentialError::BadECP);
     }
-    Ok(ECP::frombytes(bytes))
+    Ok(B::g1_from_bytes(bytes))
 }
 
-pub(crate) fn ecp2_from_compat_bytes(bytes: &[u8]) -> Result<ECP2, CredentialError> {
-    if bytes.len() != ECP2_COMPAT_SIZE {
+pub(crate) fn ecp2_from_compat_bytes<B: PairingBackend>(
+    bytes: &[u8],
+) -> Result<B::G2, CredentialError> {
+    if bytes.len() != B::G2_SIZE {
         return Err(CredentialError::BadECP2);
     }
-    let x = FP2::new_bigs(
-        &big_from_bytes(&bytes[..BIG_SIZE])?,
-        &big_from_bytes(&bytes[BIG_SIZE..BIG_SIZE * 2])?,
-    );
-    let y = FP2::new_bigs(
-        &big_from_bytes(&bytes[BIG_SIZE * 2..BIG_SIZE * 3])?,
-        &big_from_bytes(&bytes[BIG_SIZE * 3..BIG_SIZE * 4])?,
-    );
-    Ok(ECP2::new_fp2s(&x, &y))
+    Ok(B::g2_from_bytes(bytes))
 }
 
-pub(crate) fn ecp2_to_compat_bytes(point: &ECP2) -> [u8; ECP2_COMPAT_SIZE] {
-    let mut result = [0u8; ECP2_COMPAT_SIZE];
+/// Decodes a scalar, which must be below the curve order. Otherwise a proof
+/// response s would have a second valid encoding s + r, making proofs and
+/// signatures malleable.
+pub(crate) fn big_from_bytes<B: PairingBackend>(
+    bytes: &[u8],
+) -> Result<B::Scalar, CredentialError> {
+    if bytes.len() != BIG_SIZE {
+        return Err(CredentialError::BadBIG);
+    }
+    let scalar = B::scalar_from_bytes(bytes);
+    if !B::scalar_equals(&scalar, &B::scalar_from_hash(bytes)) {
+        return Err(CredentialError::BadBIG);
+    }
+    Ok(scalar)
+}
 
-    let mut x = point.getx();
-    let mut y = point.gety();
+/// Writes the points back to back, each `B::G1_SIZE` bytes.
+pub(crate) fn write_ecps<B: PairingBackend>(points: &[&B::G1], bytes: &mut [u8]) {
+    for (point, chunk) in points.iter().zip(bytes.chunks_mut(B::G1_SIZE)) {
+        B::g1_to_bytes(point, chunk);
+    }
+}
 
-    x.geta().tobytes(&mut result[..BIG_SIZE]);
-    x.getb().tobytes(&mut result[BIG_SIZE..BIG_SIZE * 2]);
-    y.geta().tobytes(&mut result[BIG_SIZE * 2..BIG_SIZE * 3]);
-    y.getb().tobytes(&mut result[BIG_SIZE * 3..]);
+#[cfg(feature = "alloc")]
+pub(crate) fn push_scalar<B: PairingBackend>(out: &mut Vec<u8>, scalar: &B::Scalar) {
+    let mut bytes = [0u8; BIG_SIZE];
+    B::scalar_to_bytes(scalar, &mut bytes);
+    out.extend_from_slice(&bytes);
+}
 
-    result
+#[cfg(feature = "alloc")]
+pub(crate) fn push_ecp<B: PairingBackend>(out: &mut Vec<u8>, point: &B::G1) {
+    push_encoded(out, B::G1_SIZE, |bytes| B::g1_to_bytes(point, bytes));
 }
 
-pub(crate) fn big_from_bytes(bytes: &[u8]) -> Result<BIG, CredentialError> {
-    if bytes.len() != BIG_SIZE {
-        return Err(CredentialError::BadBIG);
-    }
-    Ok(BIG::frombytes(bytes))
+/// Appends the fixed-size encoding that `write` fills in.
+#[cfg(feature = "alloc")]
+pub(crate) fn push_encoded(out: &mut Vec<u8>, size: usize, write: impl FnOnce(&mut [u8])) {
+    let start = out.len();
+    out.resize(start + size, 0);
+    write(&mut out[start..]);
 }
 
-impl TryFrom<&[u8]> for JoinResponse {
+impl<B: PairingBackend> TryFrom<&[u8]> for JoinResponse<B> {
     type Error = CredentialError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        if bytes.len() != JOIN_RESPONSE_SIZE {
+        if bytes.len() != B::JOIN_RESPONSE_SIZE {
             return Err(CredentialError::BadJoinResponse);
         }
 
         Ok(JoinResponse {
-            cred: bytes[..USER_CREDENTIALS_SIZE].try_into()?,
-            proof: bytes[USER_CREDENTIALS_SIZE..].try_into()?,
+            cred: bytes[..B::USER_CREDENTIALS_SIZE].try_into()?,
+            proof: bytes[B::USER_CREDENTIALS_SIZE..].try_into()?,
         })
     }
 }
 
-impl TryFrom<&[u8]> for UserCredentials {
+impl<B: PairingBackend> TryFrom<&[u8]> for UserCredentials<B> {
     type Error = CredentialError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        if bytes.len() != USER_CREDENTIALS_SIZE {
+        if bytes.len() != B::USER_CREDENTIALS_SIZE {
             return Err(CredentialError::BadUserCredentials);
         }
+        let point = |i: usize| ecp_from_bytes::<B>(&bytes[B::G1_SIZE * i..B::G1_SIZE * (i + 1)]);
         Ok(UserCredentials {
-            a: ecp_from_bytes(&bytes[..ECP_SIZE])?,
-            b: ecp_from_bytes(&bytes[ECP_SIZE..ECP_SIZE * 2])?,
-            c: ecp_from_bytes(&bytes[ECP_SIZE * 2..ECP_SIZE * 3])?,
-            d: ecp_from_bytes(&bytes[ECP_SIZE * 3..ECP_SIZE * 4])?,
+            a: point(0)?,
+            b: point(1)?,
+            c: point(2)?,
+            d: point(3)?,
         })
     }
 }
 
-impl TryFrom<&[u8]> for GroupPublicKey {
+impl<B: PairingBackend> TryFrom<&[u8]> for GroupPublicKey<B> {
     type Error = CredentialError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        if bytes.len() != GROUP_PUBLIC_KEY_SIZE {
+        if bytes.len() != B::GROUP_PUBLIC_KEY_SIZE {
             return Err(CredentialError::GroupPublicKeyLength);
         }
 
-        let big_start = ECP2_COMPAT_SIZE * 2;
+        let big_start = B::G2_SIZE * 2;
+        let big = |i: usize| {
+            big_from_bytes::<B>(&bytes[big_start + BIG_SIZE * i..big_start + BIG_SIZE * (i + 1)])
+        };
 
         Ok(GroupPublicKey {
-            x: ecp2_from_compat_bytes(&bytes[..ECP2_COMPAT_SIZE])?,
-            y: ecp2_from_compat_bytes(&bytes[ECP2_COMPAT_SIZE..ECP2_COMPAT_SIZE * 2])?,
-            cx: big_from_bytes(&bytes[big_start..big_start + BIG_SIZE])?,
-            sx: big_from_bytes(&bytes[big_start + BIG_SIZE..big_start + BIG_SIZE * 2])?,
-            cy: big_from_bytes(&bytes[big_start + BIG_SIZE * 2..big_start + BIG_SIZE * 3])?,
-            sy: big_from_bytes(&bytes[big_start + BIG_SIZE * 3..big_start + BIG_SIZE * 4])?,
+            x: ecp2_from_compat_bytes::<B>(&bytes[..B::G2_SIZE])?,
+            y: ecp2_from_compat_bytes::<B>(&bytes[B::G2_SIZE..big_start])?,
+            cx: big(0)?,
+            sx: big(1)?,
+            cy: big(2)?,
+            sy: big(3)?,
         })
     }
 }
 
-impl CredentialBIG {
+impl<B: PairingBackend> GroupPublicKey<B> {
+    pub(crate) fn write_bytes(&self, bytes: &mut [u8]) {
+        let big_start = B::G2_SIZE * 2;
+
+        B::g2_to_bytes(&self.x, &mut bytes[..B::G2_SIZE]);
+        B::g2_to_bytes(&self.y, &mut bytes[B::G2_SIZE..big_start]);
+        for (i, big) in [&self.cx, &self.sx, &self.cy, &self.sy]
+            .into_iter()
+            .enumerate()
+        {
+            B::scalar_to_bytes(
+                big,
+                &mut bytes[big_start + BIG_SIZE * i..big_start + BIG_SIZE * (i + 1)],
+            );
+        }
+    }
+
+    pub fn id(&self) -> GroupKeyId {
+        let mut bytes = [0u8; MAX_ECP2_SIZE * 2];
+        B::g2_to_bytes(&self.x, &mut bytes[..B::G2_SIZE]);
+        B::g2_to_bytes(&self.y, &mut bytes[B::G2_SIZE..B::G2_SIZE * 2]);
+
+        let mut hash = HASH256::new();
+        hash.process_array(GROUP_KEY_ID_DOMAIN);
+        hash.process_array(&bytes[..B::G2_SIZE * 2]);
+        GroupKeyId(hash.hash())
+    }
+}
+
+impl GroupKeyId {
+    pub fn to_bytes(&self) -> [u8; GROUP_KEY_ID_SIZE] {
+        self.0
+    }
+}
+
+impl<B: PairingBackend> CredentialBIG<B> {
     pub fn to_bytes(&self) -> [u8; BIG_SIZE] {
         let mut bytes = [0u8; BIG_SIZE];
-        self.0.tobytes(&mut bytes);
+        B::scalar_to_bytes(&self.0, &mut bytes);
         bytes
     }
 }
 
-impl TryFrom<&[u8]> for CredentialBIG {
+impl<B: PairingBackend> TryFrom<&[u8]> for CredentialBIG<B> {
     type Error = CredentialError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        Ok(Self(big_from_bytes(bytes)?))
+        Ok(Self(big_from_bytes::<B>(bytes)?))
     }
 }
 
-impl JoinRequest {
-    pub fn to_bytes(&self) -> [u8; JOIN_REQUEST_SIZE] {
-        let mut result = [0u8; JOIN_REQUEST_SIZE];
-        self.q.tobytes(&mut result, false);
-        result[ECP_SIZE..].copy_from_slice(&self.proof.to_bytes());
-        result
+impl<B: PairingBackend> JoinRequest<B> {
+    pub(crate) fn write_bytes(&self, bytes: &mut [u8]) {
+        B::g1_to_bytes(&self.q, &mut bytes[..B::G1_SIZE]);
+        bytes[B::G1_SIZE..B::JOIN_REQUEST_SIZE].copy_from_slice(&self.proof.to_bytes());
     }
 }
 
-impl UserCredentials {
-    pub fn to_bytes(&self) -> [u8; USER_CREDENTIALS_SIZE] {
-        let mut result = [0u8; USER_CREDENTIALS_SIZE];
-        self.a.tobytes(&mut result[..ECP_SIZE], false);
-        self.b.tobytes(&mut result[ECP_SIZE..ECP_SIZE * 2], false);
-        self.c
-            .tobytes(&mut result[ECP_SIZE * 2..ECP_SIZE * 3], false);
-        self.d
-            .tobytes(&mut result[ECP_SIZE * 3..ECP_SIZE * 4], false);
-        result
+impl<B: PairingBackend> TryFrom<&[u8]> for JoinRequest<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != B::JOIN_REQUEST_SIZE {
+            return Err(CredentialError::BadJoinRequest);
+        }
+
+        Ok(JoinRequest {
+            q: ecp_from_bytes::<B>(&bytes[..B::G1_SIZE])?,
+            proof: bytes[B::G1_SIZE..].try_into()?,
+        })
+    }
+}
+
+impl<B: PairingBackend> JoinResponse<B> {
+    pub(crate) fn write_bytes(&self, bytes: &mut [u8]) {
+        self.cred
+            .write_bytes(&mut bytes[..B::USER_CREDENTIALS_SIZE]);
+        bytes[B::USER_CREDENTIALS_SIZE..B::JOIN_RESPONSE_SIZE]
+            .copy_from_slice(&self.proof.to_bytes());
+    }
+}
+
+impl<B: PairingBackend> UserCredentials<B> {
+    pub(crate) fn write_bytes(&self, bytes: &mut [u8]) {
+        write_ecps::<B>(&[&self.a, &self.b, &self.c, &self.d], bytes);
     }
 }
 
-impl ECPProof {
+impl<B: PairingBackend> ECPProof<B> {
     pub fn to_bytes(&self) -> [u8; ECP_PROOF_SIZE] {
         let mut result = [0u8; ECP_PROOF_SIZE];
-        self.c.tobytes(&mut result[..BIG_SIZE]);
-        self.s.tobytes(&mut result[BIG_SIZE..]);
+        B::scalar_to_bytes(&self.c, &mut result[..BIG_SIZE]);
+        B::scalar_to_bytes(&self.s, &mut result[BIG_SIZE..]);
         result
     }
 }
 
-impl TryFrom<&[u8]> for ECPProof {
+impl<B: PairingBackend> TryFrom<&[u8]> for ECPProof<B> {
     type Error = CredentialError;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
@@ -220,24 +398,212 @@ impl TryFrom<&[u8]> for ECPProof {
         }
 
         Ok(ECPProof {
-            c: big_from_bytes(&bytes[0..BIG_SIZE])?,
-            s: big_from_bytes(&bytes[BIG_SIZE..])?,
+            c: big_from_bytes::<B>(&bytes[0..BIG_SIZE])?,
+            s: big_from_bytes::<B>(&bytes[BIG_SIZE..])?,
         })
     }
 }
 
-impl Signature {
-    pub fn to_bytes(&self) -> [u8; SIGNATURE_SIZE] {
-        let mut result = [0u8; SIGNATURE_SIZE];
-        self.a.tobytes(&mut result[..ECP_SIZE], false);
-        self.b.tobytes(&mut result[ECP_SIZE..ECP_SIZE * 2], false);
-        self.c
-            .tobytes(&mut result[ECP_SIZE * 2..ECP_SIZE * 3], false);
-        self.d
-            .tobytes(&mut result[ECP_SIZE * 3..ECP_SIZE * 4], false);
-        self.nym
-            .tobytes(&mut result[ECP_SIZE * 4..ECP_SIZE * 5], false);
-        result[ECP_SIZE * 5..].copy_from_slice(&self.proof.to_bytes());
+impl<B: PairingBackend> Signature<B> {
+    pub(crate) fn write_bytes(&self, bytes: &mut [u8]) {
+        write_ecps::<B>(&[&self.a, &self.b, &self.c, &self.d, &self.nym], bytes);
+        bytes[B::G1_SIZE * 5..B::SIGNATURE_SIZE].copy_from_slice(&self.proof.to_bytes());
+    }
+
+    pub(crate) fn write_nym(&self, bytes: &mut [u8]) {
+        B::g1_to_bytes(&self.nym, bytes);
+    }
+}
+
+impl<B: PairingBackend> EpochGroupPublicKey<B> {
+    pub fn suite(&self) -> Ciphersuite {
+        self.suite
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn key(&self) -> &GroupPublicKey<B> {
+        &self.key
+    }
+
+    pub(crate) fn write_bytes(&self, bytes: &mut [u8]) {
+        let big_start = 9 + B::GROUP_PUBLIC_KEY_SIZE;
+
+        bytes[0] = self.suite.id();
+        bytes[1..9].copy_from_slice(&self.epoch.to_be_bytes());
+        self.key.write_bytes(&mut bytes[9..big_start]);
+        for (i, big) in [&self.c, &self.sx, &self.sy].into_iter().enumerate() {
+            B::scalar_to_bytes(
+                big,
+                &mut bytes[big_start + BIG_SIZE * i..big_start + BIG_SIZE * (i + 1)],
+            );
+        }
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for EpochGroupPublicKey<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != B::EPOCH_GROUP_PUBLIC_KEY_SIZE {
+            return Err(CredentialError::GroupPublicKeyLength);
+        }
+
+        let suite = Ciphersuite::try_from(bytes[0])?;
+        suite.check::<B>()?;
+
+        let big_start = 9 + B::GROUP_PUBLIC_KEY_SIZE;
+        let big = |i: usize| {
+            big_from_bytes::<B>(&bytes[big_start + BIG_SIZE * i..big_start + BIG_SIZE * (i + 1)])
+        };
+
+        let key = EpochGroupPublicKey {
+            suite,
+            epoch: u64::from_be_bytes(bytes[1..9].try_into().unwrap()),
+            key: bytes[9..big_start].try_into()?,
+            c: big(0)?,
+            sx: big(1)?,
+            sy: big(2)?,
+        };
+        match epoch_group_public_key_is_valid(&key) {
+            true => Ok(key),
+            false => Err(CredentialError::BadGroupPublicKey),
+        }
+    }
+}
+
+impl CredentialMetadata {
+    pub fn new(issued_at: u64, not_after: u64, group: GroupKeyId) -> Self {
+        Self {
+            issued_at,
+            not_after,
+            group,
+        }
+    }
+
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        now > self.not_after
+    }
+
+    pub fn to_bytes(&self) -> [u8; CREDENTIAL_METADATA_SIZE] {
+        let mut result = [0u8; CREDENTIAL_METADATA_SIZE];
+        result[..8].copy_from_slice(&self.issued_at.to_be_bytes());
+        result[8..16].copy_from_slice(&self.not_after.to_be_bytes());
+        result[16..].copy_from_slice(&self.group.to_bytes());
         result
     }
 }
+
+impl TryFrom<&[u8]> for CredentialMetadata {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != CREDENTIAL_METADATA_SIZE {
+            return Err(CredentialError::BadCredentialMetadata);
+        }
+
+        Ok(CredentialMetadata {
+            issued_at: u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            not_after: u64::from_be_bytes(bytes[8..16].try_into().unwrap()),
+            group: GroupKeyId(bytes[16..].try_into().unwrap()),
+        })
+    }
+}
+
+impl<B: PairingBackend> MigrationRequest<B> {
+    /// Join request for the member's existing gsk, to be answered like any
+    /// other once `verify_migration_request` accepts the migration.
+    pub fn join_request(&self) -> &JoinRequest<B> {
+        &self.join
+    }
+
+    pub(crate) fn write_bytes(&self, bytes: &mut [u8]) {
+        let cred_start = B::JOIN_REQUEST_SIZE;
+        let proof_start = cred_start + B::USER_CREDENTIALS_SIZE;
+
+        self.join.write_bytes(&mut bytes[..cred_start]);
+        self.cred.write_bytes(&mut bytes[cred_start..proof_start]);
+        bytes[proof_start..B::MIGRATION_REQUEST_SIZE].copy_from_slice(&self.proof.to_bytes());
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for MigrationRequest<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != B::MIGRATION_REQUEST_SIZE {
+            return Err(CredentialError::BadMigrationRequest);
+        }
+
+        let cred_start = B::JOIN_REQUEST_SIZE;
+        let proof_start = cred_start + B::USER_CREDENTIALS_SIZE;
+
+        Ok(MigrationRequest {
+            join: bytes[..cred_start].try_into()?,
+            cred: bytes[cred_start..proof_start].try_into()?,
+            proof: bytes[proof_start..].try_into()?,
+        })
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for Signature<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != B::SIGNATURE_SIZE {
+            return Err(CredentialError::BadSignature);
+        }
+
+        let point = |i: usize| ecp_from_bytes::<B>(&bytes[B::G1_SIZE * i..B::G1_SIZE * (i + 1)]);
+        Ok(Signature {
+            a: point(0)?,
+            b: point(1)?,
+            c: point(2)?,
+            d: point(3)?,
+            nym: point(4)?,
+            proof: bytes[B::G1_SIZE * 5..].try_into()?,
+        })
+    }
+}
+
+impl<B: PairingBackend> UnlinkableSignature<B> {
+    pub(crate) fn write_bytes(&self, bytes: &mut [u8]) {
+        write_ecps::<B>(&[&self.a, &self.b, &self.c, &self.d], bytes);
+        bytes[B::G1_SIZE * 4..B::UNLINKABLE_SIGNATURE_SIZE].copy_from_slice(&self.proof.to_bytes());
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for UnlinkableSignature<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != B::UNLINKABLE_SIGNATURE_SIZE {
+            return Err(CredentialError::BadUnlinkableSignature);
+        }
+
+        let point = |i: usize| ecp_from_bytes::<B>(&bytes[B::G1_SIZE * i..B::G1_SIZE * (i + 1)]);
+        Ok(UnlinkableSignature {
+            a: point(0)?,
+            b: point(1)?,
+            c: point(2)?,
+            d: point(3)?,
+            proof: bytes[B::G1_SIZE * 4..].try_into()?,
+        })
+    }
+}
+
+impl_fixed_size! {
+    GroupPublicKey::to_bytes(write_bytes) -> GROUP_PUBLIC_KEY_SIZE;
+    JoinRequest::to_bytes(write_bytes) -> JOIN_REQUEST_SIZE;
+    JoinResponse::to_bytes(write_bytes) -> JOIN_RESPONSE_SIZE;
+    UserCredentials::to_bytes(write_bytes) -> USER_CREDENTIALS_SIZE;
+    Signature::to_bytes(write_bytes) -> SIGNATURE_SIZE;
+    /// Pseudonym of the signer for the signature's basename. Equal for all
+    /// signatures by the same member under the same basename.
+    Signature::nym(write_nym) -> ECP_SIZE;
+    UnlinkableSignature::to_bytes(write_bytes) -> UNLINKABLE_SIGNATURE_SIZE;
+    MigrationRequest::to_bytes(write_bytes) -> MIGRATION_REQUEST_SIZE;
+    EpochGroupPublicKey::to_bytes(write_bytes) -> EPOCH_GROUP_PUBLIC_KEY_SIZE;
+}
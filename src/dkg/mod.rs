@@ -0,0 +1,995 @@
+//! Distributed generation of a threshold issuer key, so that no party ever
+//! learns x or y.
+//!
+//! n parties, any t of which issue together, run a joint Feldman DKG:
+//!
+//! 1. Every party deals shares of random x, y and rho on polynomials of
+//!    degree t - 1, and of zero on two masks o and o' of degree 2t - 2. It
+//!    broadcasts Feldman commitments to the coefficients of x and y in G2,
+//!    Pedersen commitments a * G1 + b * H to those of rho, o and o' under
+//!    random blinding polynomials, and sends each party its shares in
+//!    private.
+//! 2. A party whose shares do not match the commitments complains, and the
+//!    dealer answers by broadcasting the shares. Dealers with an unanswered
+//!    complaint are disqualified, and the rest make up the key: X and Y are
+//!    the sums of their constant commitments, and every party's share is the
+//!    sum of its shares from them.
+//!
+//! Issuance needs shares of z = 1 / y and w = x / y rather than y, which
+//! takes two products of shared values. Products of shares lie on a
+//! polynomial of degree 2t - 2, so both need 2t - 1 honest parties:
+//!
+//! 3. Every party broadcasts mu_i = rho_i * y_i + o_i. 2t - 1 of them
+//!    interpolate mu = rho * y, and z_i = rho_i / mu.
+//! 4. Every party reshares x_i * z_i + o'_i on a polynomial of degree t - 1,
+//!    under Pedersen commitments and with complaints as above. The reshares
+//!    of 2t - 1 parties interpolate to shares of w.
+//! 5. The parties prove knowledge of x and y for the group public key with
+//!    threshold Schnorr proofs, in which each response is checked on its own.
+//!
+//! Rounds 3 and 4 prove their product in zero knowledge: with a = y_i or
+//! x_i, committed to as a * G2, and P the commitment to rho_i or z_i, the
+//! commitment M to the product minus that to the mask is a * P + t * H,
+//! with t a blinding factor the party knows. Every point broadcast in G1
+//! carries a blinding term in H, so no combination of the broadcasts gives
+//! rho * G1, and with it G1 / y or x / y * G1, which would let anyone forge
+//! a credential.
+//!
+//! Secrets stay hidden as long as fewer than t parties collude, so
+//! `nodes` must be at least 2t - 1. Messages are passed in memory and the
+//! caller provides private channels and a broadcast that every party sees
+//! alike.
+
+use alloc::vec::Vec;
+use brave_miracl::rand::RAND;
+
+use super::backend::{PairingBackend, MAX_ECP2_SIZE};
+use super::ciphersuite::Ciphersuite;
+use super::data::GroupPublicKey;
+use super::join::{ecp2_challenge, verify_group_public_key};
+use super::threshold::{evaluate, lagrange_coefficient, zero, IssuerKeyShare};
+use super::transcript::{Transcript, PROTOCOL_DKG_PRODUCT};
+use super::util::{g1_equals, scalar_from_u64};
+use super::{CredentialError, Result};
+
+#[cfg(test)]
+mod tests;
+
+const PEDERSEN_GENERATOR_SEED: &[u8] = b"anonymous-credentials dkg pedersen generator";
+
+/// Broadcast first-round message of a dealer.
+#[derive(Clone)]
+pub struct DkgCommitments<B: PairingBackend> {
+    dealer: u16,
+    x: Vec<B::G2>,
+    y: Vec<B::G2>,
+    rho: Vec<B::G1>,
+    mask: Vec<B::G1>,
+    product_mask: Vec<B::G1>,
+}
+
+/// Shares of a dealer for one recipient, sent in private or broadcast to
+/// answer a complaint.
+#[derive(Clone)]
+pub struct DkgShare<B: PairingBackend> {
+    dealer: u16,
+    recipient: u16,
+    values: Vec<B::Scalar>,
+}
+
+/// Broadcast when the shares from a dealer are missing or wrong.
+#[derive(Clone, Copy)]
+pub struct DkgComplaint {
+    accuser: u16,
+    dealer: u16,
+}
+
+/// Proof of knowledge of a and t such that A = a * G2 and M = a * P + t * H.
+#[derive(Clone)]
+struct ProductProof<B: PairingBackend> {
+    c: B::Scalar,
+    s: B::Scalar,
+    t: B::Scalar,
+}
+
+/// Broadcast masked share of rho * y.
+#[derive(Clone)]
+pub struct DkgProductShare<B: PairingBackend> {
+    index: u16,
+    mu: B::Scalar,
+    proof: ProductProof<B>,
+}
+
+/// Broadcast commitments of a party resharing its share of x * z.
+#[derive(Clone)]
+pub struct DkgReshareCommitments<B: PairingBackend> {
+    dealer: u16,
+    coefficients: Vec<B::G1>,
+    proof: ProductProof<B>,
+}
+
+/// Broadcast nonce commitments for the proofs of the group public key.
+#[derive(Clone)]
+pub struct DkgProofCommitment<B: PairingBackend> {
+    index: u16,
+    x: B::G2,
+    y: B::G2,
+}
+
+/// Broadcast responses for the proofs of the group public key.
+#[derive(Clone)]
+pub struct DkgProofResponse<B: PairingBackend> {
+    index: u16,
+    x: B::Scalar,
+    y: B::Scalar,
+}
+
+/// Commitments to one party's shares, summed over the qualified dealers.
+struct PublicShare<B: PairingBackend> {
+    x: B::G2,
+    y: B::G2,
+    rho: B::G1,
+    mask: B::G1,
+    product_mask: B::G1,
+}
+
+/// One party's state. Each round consumes the broadcasts of the previous
+/// one, which must reach every party alike.
+pub struct DkgParty<B: PairingBackend> {
+    suite: Ciphersuite,
+    index: u16,
+    threshold: u16,
+    nodes: u16,
+    // Blinding generator of the Pedersen commitments
+    h: B::G1,
+    // Polynomials dealt in the current phase, to answer complaints
+    polynomials: Vec<Vec<B::Scalar>>,
+    // Shares received in the current phase, checked against their commitments
+    received: Vec<DkgShare<B>>,
+    commitments: Vec<DkgCommitments<B>>,
+    reshares: Vec<DkgReshareCommitments<B>>,
+    // x, y, rho, o and o', then the blinding shares of rho, o and o'
+    shares: Option<[B::Scalar; 8]>,
+    mu: Option<B::Scalar>,
+    z: Option<B::Scalar>,
+    w: Option<B::Scalar>,
+    nonces: Option<(B::Scalar, B::Scalar)>,
+}
+
+fn random_polynomial<B: PairingBackend>(
+    rng: &mut RAND,
+    constant: B::Scalar,
+    len: usize,
+) -> Vec<B::Scalar> {
+    let mut coefficients = Vec::with_capacity(len);
+    coefficients.push(constant);
+    coefficients.extend((1..len).map(|_| B::scalar_random(rng)));
+    coefficients
+}
+
+fn pedersen_commit<B: PairingBackend>(
+    h: &B::G1,
+    coefficients: &[B::Scalar],
+    blinding: &[B::Scalar],
+) -> Vec<B::G1> {
+    coefficients
+        .iter()
+        .zip(blinding)
+        .map(|(coefficient, blinding)| pedersen::<B>(h, coefficient, blinding))
+        .collect()
+}
+
+/// a * G1 + b * H.
+fn pedersen<B: PairingBackend>(h: &B::G1, a: &B::Scalar, b: &B::Scalar) -> B::G1 {
+    B::g1_add(&B::g1_mul(&B::g1_generator(), a), &B::g1_mul(h, b))
+}
+
+fn g2_commit<B: PairingBackend>(coefficients: &[B::Scalar]) -> Vec<B::G2> {
+    let g2 = B::g2_generator();
+    coefficients
+        .iter()
+        .map(|coefficient| B::g2_mul(&g2, coefficient))
+        .collect()
+}
+
+/// Commitment to the share of `index` from commitments to the coefficients.
+fn g1_commitment_at<B: PairingBackend>(commitments: &[B::G1], index: u16) -> B::G1 {
+    let at = scalar_from_u64::<B>(index.into());
+    commitments
+        .iter()
+        .rev()
+        .fold(B::g1_infinity(), |value, commitment| {
+            B::g1_add(&B::g1_mul(&value, &at), commitment)
+        })
+}
+
+fn g2_commitment_at<B: PairingBackend>(commitments: &[B::G2], index: u16) -> B::G2 {
+    let at = scalar_from_u64::<B>(index.into());
+    let (last, rest) = commitments.split_last().expect("no commitments");
+    rest.iter().rev().fold(last.clone(), |value, commitment| {
+        B::g2_add(&B::g2_mul(&value, &at), commitment)
+    })
+}
+
+fn g2_equals<B: PairingBackend>(a: &B::G2, b: &B::G2) -> bool {
+    let mut a_bytes = [0u8; MAX_ECP2_SIZE];
+    let mut b_bytes = [0u8; MAX_ECP2_SIZE];
+    B::g2_to_bytes(a, &mut a_bytes[..B::G2_SIZE]);
+    B::g2_to_bytes(b, &mut b_bytes[..B::G2_SIZE]);
+    a_bytes == b_bytes
+}
+
+fn pedersen_share_is_valid<B: PairingBackend>(
+    h: &B::G1,
+    commitments: &[B::G1],
+    index: u16,
+    share: &B::Scalar,
+    blinding: &B::Scalar,
+) -> bool {
+    g1_equals::<B>(
+        &pedersen::<B>(h, share, blinding),
+        &g1_commitment_at::<B>(commitments, index),
+    )
+}
+
+fn g2_share_is_valid<B: PairingBackend>(
+    commitments: &[B::G2],
+    index: u16,
+    share: &B::Scalar,
+) -> bool {
+    g2_equals::<B>(
+        &B::g2_mul(&B::g2_generator(), share),
+        &g2_commitment_at::<B>(commitments, index),
+    )
+}
+
+/// Party `index`'s claim that M commits to log_G2(A) times the value
+/// committed to in P.
+struct ProductStatement<'a, B: PairingBackend> {
+    suite: Ciphersuite,
+    // b"product" for mu_i, b"reshare" for the constant term of a reshare
+    label: &'static [u8],
+    index: u16,
+    h: &'a B::G1,
+    a: &'a B::G2,
+    p: B::G1,
+    m: B::G1,
+}
+
+impl<B: PairingBackend> ProductStatement<'_, B> {
+    fn challenge(&self, ta: &B::G2, tm: &B::G1) -> B::Scalar {
+        let mut transcript = Transcript::<B>::labeled(self.suite, PROTOCOL_DKG_PRODUCT);
+        transcript.append_bytes(b"statement", self.label);
+        transcript.append_bytes(b"index", &self.index.to_be_bytes());
+        transcript.append_ecp2(b"a", self.a);
+        transcript.append_ecp(b"p", &self.p);
+        transcript.append_ecp(b"m", &self.m);
+        transcript.append_ecp2(b"ta", ta);
+        transcript.append_ecp(b"tm", tm);
+        transcript.challenge()
+    }
+
+    fn prove(&self, rng: &mut RAND, a: &B::Scalar, t: &B::Scalar) -> ProductProof<B> {
+        let ka = B::scalar_random(rng);
+        let kt = B::scalar_random(rng);
+        let ta = B::g2_mul(&B::g2_generator(), &ka);
+        let tm = B::g1_add(&B::g1_mul(&self.p, &ka), &B::g1_mul(self.h, &kt));
+        let c = self.challenge(&ta, &tm);
+        ProductProof {
+            s: B::scalar_add(&ka, &B::scalar_mul(&c, a)),
+            t: B::scalar_add(&kt, &B::scalar_mul(&c, t)),
+            c,
+        }
+    }
+
+    fn verify(&self, proof: &ProductProof<B>) -> bool {
+        let c = B::scalar_neg(&proof.c);
+        // s * G2 - c * A and s * P + t * H - c * M
+        let ta = B::g2_add(
+            &B::g2_mul(&B::g2_generator(), &proof.s),
+            &B::g2_mul(self.a, &c),
+        );
+        let tm = B::g1_add(
+            &B::g1_add(&B::g1_mul(&self.p, &proof.s), &B::g1_mul(self.h, &proof.t)),
+            &B::g1_mul(&self.m, &c),
+        );
+        B::scalar_equals(&self.challenge(&ta, &tm), &proof.c)
+    }
+}
+
+/// Whether `indices` holds distinct party numbers only.
+fn indices_are_valid(nodes: u16, indices: impl Iterator<Item = u16> + Clone) -> bool {
+    indices.clone().enumerate().all(|(i, index)| {
+        (1..=nodes).contains(&index) && indices.clone().take(i).all(|other| other != index)
+    })
+}
+
+impl<B: PairingBackend> DkgCommitments<B> {
+    pub fn dealer(&self) -> u16 {
+        self.dealer
+    }
+
+    fn is_well_formed(&self, threshold: u16, nodes: u16) -> bool {
+        let len = usize::from(threshold);
+        let mask_len = len * 2 - 1;
+        (1..=nodes).contains(&self.dealer)
+            && self.x.len() == len
+            && self.y.len() == len
+            && self.rho.len() == len
+            && self.mask.len() == mask_len
+            && self.product_mask.len() == mask_len
+            && B::g1_is_infinity(&self.mask[0])
+            && B::g1_is_infinity(&self.product_mask[0])
+    }
+
+    fn verifies(&self, h: &B::G1, share: &DkgShare<B>) -> bool {
+        let (recipient, values) = (share.recipient, &share.values);
+        share.dealer == self.dealer
+            && values.len() == 8
+            && g2_share_is_valid::<B>(&self.x, recipient, &values[0])
+            && g2_share_is_valid::<B>(&self.y, recipient, &values[1])
+            && pedersen_share_is_valid::<B>(h, &self.rho, recipient, &values[2], &values[5])
+            && pedersen_share_is_valid::<B>(h, &self.mask, recipient, &values[3], &values[6])
+            && pedersen_share_is_valid::<B>(
+                h,
+                &self.product_mask,
+                recipient,
+                &values[4],
+                &values[7],
+            )
+    }
+}
+
+impl<B: PairingBackend> DkgReshareCommitments<B> {
+    pub fn dealer(&self) -> u16 {
+        self.dealer
+    }
+
+    fn verifies(&self, h: &B::G1, share: &DkgShare<B>) -> bool {
+        share.dealer == self.dealer
+            && share.values.len() == 2
+            && pedersen_share_is_valid::<B>(
+                h,
+                &self.coefficients,
+                share.recipient,
+                &share.values[0],
+                &share.values[1],
+            )
+    }
+}
+
+impl<B: PairingBackend> DkgShare<B> {
+    pub fn dealer(&self) -> u16 {
+        self.dealer
+    }
+
+    pub fn recipient(&self) -> u16 {
+        self.recipient
+    }
+}
+
+impl DkgComplaint {
+    pub fn accuser(&self) -> u16 {
+        self.accuser
+    }
+
+    pub fn dealer(&self) -> u16 {
+        self.dealer
+    }
+}
+
+impl<B: PairingBackend> DkgProductShare<B> {
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+impl<B: PairingBackend> DkgProofCommitment<B> {
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+impl<B: PairingBackend> DkgProofResponse<B> {
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+impl<B: PairingBackend> DkgParty<B> {
+    /// First round: deals this party's shares. The commitments go to every
+    /// party and each share only to its recipient, this party included.
+    pub fn new(
+        rng: &mut RAND,
+        suite: Ciphersuite,
+        index: u16,
+        threshold: u16,
+        nodes: u16,
+    ) -> Result<(Self, DkgCommitments<B>, Vec<DkgShare<B>>)> {
+        if threshold == 0 || u32::from(nodes) < u32::from(threshold) * 2 - 1 {
+            return Err(CredentialError::KeyGeneration);
+        }
+        if !(1..=nodes).contains(&index) {
+            return Err(CredentialError::KeyGeneration);
+        }
+
+        let len = usize::from(threshold);
+        let mut polynomials = Vec::with_capacity(8);
+        for _ in 0..3 {
+            let secret = B::scalar_random(rng);
+            polynomials.push(random_polynomial::<B>(rng, secret, len));
+        }
+        for _ in 0..2 {
+            polynomials.push(random_polynomial::<B>(rng, zero::<B>(), len * 2 - 1));
+        }
+        // Blinding polynomials of rho, o and o', of zero for the masks so
+        // that their constant commitments stay at infinity
+        let blinding = B::scalar_random(rng);
+        polynomials.push(random_polynomial::<B>(rng, blinding, len));
+        for _ in 0..2 {
+            polynomials.push(random_polynomial::<B>(rng, zero::<B>(), len * 2 - 1));
+        }
+
+        let h = suite.hash_to_g1::<B>(PEDERSEN_GENERATOR_SEED);
+        let commitments = DkgCommitments {
+            dealer: index,
+            x: g2_commit::<B>(&polynomials[0]),
+            y: g2_commit::<B>(&polynomials[1]),
+            rho: pedersen_commit::<B>(&h, &polynomials[2], &polynomials[5]),
+            mask: pedersen_commit::<B>(&h, &polynomials[3], &polynomials[6]),
+            product_mask: pedersen_commit::<B>(&h, &polynomials[4], &polynomials[7]),
+        };
+        let party = DkgParty {
+            suite,
+            index,
+            threshold,
+            nodes,
+            h,
+            polynomials,
+            received: Vec::new(),
+            commitments: Vec::new(),
+            reshares: Vec::new(),
+            shares: None,
+            mu: None,
+            z: None,
+            w: None,
+            nonces: None,
+        };
+        let shares = party.deal();
+        Ok((party, commitments, shares))
+    }
+
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    fn share_for(&self, recipient: u16) -> DkgShare<B> {
+        let at = scalar_from_u64::<B>(recipient.into());
+        DkgShare {
+            dealer: self.index,
+            recipient,
+            values: self
+                .polynomials
+                .iter()
+                .map(|polynomial| evaluate::<B>(polynomial, &at))
+                .collect(),
+        }
+    }
+
+    fn deal(&self) -> Vec<DkgShare<B>> {
+        (1..=self.nodes)
+            .map(|recipient| self.share_for(recipient))
+            .collect()
+    }
+
+    /// Keeps the shares for this party that `verifies` accepts, and
+    /// complains about every dealer in `dealers` without one.
+    fn check_shares(
+        &mut self,
+        dealers: &[u16],
+        shares: &[DkgShare<B>],
+        verifies: impl Fn(&DkgShare<B>) -> bool,
+    ) -> Vec<DkgComplaint> {
+        self.received = shares
+            .iter()
+            .filter(|share| share.recipient == self.index && verifies(share))
+            .cloned()
+            .collect();
+        dealers
+            .iter()
+            .filter(|&&dealer| !self.received.iter().any(|share| share.dealer == dealer))
+            .map(|&dealer| DkgComplaint {
+                accuser: self.index,
+                dealer,
+            })
+            .collect()
+    }
+
+    /// Shares of this party from the dealers in `dealers` that answered every
+    /// complaint against them with shares that `verifies` accepts. The
+    /// dealers of the result are the qualified ones.
+    fn qualified_shares(
+        &self,
+        dealers: &[u16],
+        complaints: &[DkgComplaint],
+        answers: &[DkgShare<B>],
+        verifies: impl Fn(&DkgShare<B>) -> bool,
+    ) -> Vec<DkgShare<B>> {
+        dealers
+            .iter()
+            .filter_map(|&dealer| {
+                let answer = |accuser: u16| {
+                    answers.iter().find(|answer| {
+                        answer.dealer == dealer && answer.recipient == accuser && verifies(answer)
+                    })
+                };
+                let answered = complaints
+                    .iter()
+                    .filter(|complaint| {
+                        complaint.dealer == dealer && (1..=self.nodes).contains(&complaint.accuser)
+                    })
+                    .all(|complaint| answer(complaint.accuser).is_some());
+                if !answered {
+                    return None;
+                }
+                answer(self.index)
+                    .or_else(|| self.received.iter().find(|share| share.dealer == dealer))
+                    .cloned()
+            })
+            .collect()
+    }
+
+    /// Answers the complaints against this party in the current phase, to be
+    /// broadcast.
+    pub fn answer_complaints(&self, complaints: &[DkgComplaint]) -> Vec<DkgShare<B>> {
+        complaints
+            .iter()
+            .filter(|complaint| {
+                complaint.dealer == self.index && (1..=self.nodes).contains(&complaint.accuser)
+            })
+            .map(|complaint| self.share_for(complaint.accuser))
+            .collect()
+    }
+
+    /// Second round: checks the dealings, given every broadcast commitment
+    /// and the shares sent to this party. Dealers with malformed commitments
+    /// are dropped outright.
+    pub fn receive_dealings(
+        &mut self,
+        commitments: &[DkgCommitments<B>],
+        shares: &[DkgShare<B>],
+    ) -> Vec<DkgComplaint> {
+        self.commitments = commitments
+            .iter()
+            .filter(|dealing| {
+                dealing.is_well_formed(self.threshold, self.nodes)
+                    && commitments
+                        .iter()
+                        .filter(|other| other.dealer == dealing.dealer)
+                        .count()
+                        == 1
+            })
+            .cloned()
+            .collect();
+        self.commitments.sort_by_key(|dealing| dealing.dealer);
+
+        let dealers: Vec<_> = self
+            .commitments
+            .iter()
+            .map(|dealing| dealing.dealer)
+            .collect();
+        let commitments = core::mem::take(&mut self.commitments);
+        let h = self.h.clone();
+        let complaints = self.check_shares(&dealers, shares, |share| {
+            commitments
+                .iter()
+                .any(|dealing| dealing.verifies(&h, share))
+        });
+        self.commitments = commitments;
+        complaints
+    }
+
+    /// Third round: disqualifies the dealers with unanswered complaints, and
+    /// opens this party's masked share of rho * y with a proof that it
+    /// matches the commitments.
+    pub fn resolve_complaints(
+        &mut self,
+        rng: &mut RAND,
+        complaints: &[DkgComplaint],
+        answers: &[DkgShare<B>],
+    ) -> Result<DkgProductShare<B>> {
+        let dealers: Vec<_> = self
+            .commitments
+            .iter()
+            .map(|dealing| dealing.dealer)
+            .collect();
+        let qualified = self.qualified_shares(&dealers, complaints, answers, |share| {
+            self.commitments
+                .iter()
+                .any(|dealing| dealing.verifies(&self.h, share))
+        });
+        if qualified.is_empty() {
+            return Err(CredentialError::KeyGeneration);
+        }
+        self.commitments
+            .retain(|dealing| qualified.iter().any(|share| share.dealer == dealing.dealer));
+
+        let mut shares = [zero::<B>(); 8];
+        for share in &qualified {
+            for (sum, value) in shares.iter_mut().zip(&share.values) {
+                *sum = B::scalar_add(sum, value);
+            }
+        }
+        let [_, y, rho, mask, _, rho_blinding, mask_blinding, _] = shares;
+        self.shares = Some(shares);
+
+        // mu_i * G1 - O_i = y_i * R_i - (y_i * rho~_i + o~_i) * H
+        let mu = B::scalar_add(&B::scalar_mul(&rho, &y), &mask);
+        let public = self.public_share(self.index);
+        let blinding = B::scalar_add(&B::scalar_mul(&y, &rho_blinding), &mask_blinding);
+        let proof = self.product_statement(self.index, &public, &mu).prove(
+            rng,
+            &y,
+            &B::scalar_neg(&blinding),
+        );
+        Ok(DkgProductShare {
+            index: self.index,
+            mu,
+            proof,
+        })
+    }
+
+    /// Statement of party `index` that mu_i * G1 - O_i commits to y_i times
+    /// rho_i.
+    fn product_statement<'a>(
+        &'a self,
+        index: u16,
+        public: &'a PublicShare<B>,
+        mu: &B::Scalar,
+    ) -> ProductStatement<'a, B> {
+        let masked = B::g1_mul(&B::g1_generator(), mu);
+        ProductStatement {
+            suite: self.suite,
+            label: b"product",
+            index,
+            h: &self.h,
+            a: &public.y,
+            p: public.rho.clone(),
+            m: B::g1_add(&masked, &B::g1_neg(&public.mask)),
+        }
+    }
+
+    /// Statement of party `index` that the constant commitment of its
+    /// reshare minus O'_i commits to x_i times z_i = rho_i / mu.
+    fn reshare_statement<'a>(
+        &'a self,
+        index: u16,
+        public: &'a PublicShare<B>,
+        constant: &B::G1,
+        inverse: &B::Scalar,
+    ) -> ProductStatement<'a, B> {
+        ProductStatement {
+            suite: self.suite,
+            label: b"reshare",
+            index,
+            h: &self.h,
+            a: &public.x,
+            p: B::g1_mul(&public.rho, inverse),
+            m: B::g1_add(constant, &B::g1_neg(&public.product_mask)),
+        }
+    }
+
+    fn public_share(&self, index: u16) -> PublicShare<B> {
+        let mut dealings = self.commitments.iter();
+        let first = dealings.next().expect("no qualified dealers");
+        dealings.fold(
+            PublicShare {
+                x: g2_commitment_at::<B>(&first.x, index),
+                y: g2_commitment_at::<B>(&first.y, index),
+                rho: g1_commitment_at::<B>(&first.rho, index),
+                mask: g1_commitment_at::<B>(&first.mask, index),
+                product_mask: g1_commitment_at::<B>(&first.product_mask, index),
+            },
+            |sum, dealing| PublicShare {
+                x: B::g2_add(&sum.x, &g2_commitment_at::<B>(&dealing.x, index)),
+                y: B::g2_add(&sum.y, &g2_commitment_at::<B>(&dealing.y, index)),
+                rho: B::g1_add(&sum.rho, &g1_commitment_at::<B>(&dealing.rho, index)),
+                mask: B::g1_add(&sum.mask, &g1_commitment_at::<B>(&dealing.mask, index)),
+                product_mask: B::g1_add(
+                    &sum.product_mask,
+                    &g1_commitment_at::<B>(&dealing.product_mask, index),
+                ),
+            },
+        )
+    }
+
+    /// X and Y of the group public key.
+    fn public_key(&self) -> (B::G2, B::G2) {
+        let mut dealings = self.commitments.iter();
+        let first = dealings.next().expect("no qualified dealers");
+        dealings.fold(
+            (first.x[0].clone(), first.y[0].clone()),
+            |(x, y), dealing| (B::g2_add(&x, &dealing.x[0]), B::g2_add(&y, &dealing.y[0])),
+        )
+    }
+
+    /// Indices of the first 2t - 1 parties in `indices`, or an error if
+    /// there are fewer.
+    fn interpolation_set(&self, mut indices: Vec<u16>) -> Result<Vec<u16>> {
+        let len = usize::from(self.threshold) * 2 - 1;
+        indices.sort_unstable();
+        indices.dedup();
+        if indices.len() < len {
+            return Err(CredentialError::KeyGeneration);
+        }
+        indices.truncate(len);
+        Ok(indices)
+    }
+
+    /// Fourth round: interpolates mu from the broadcast product shares that
+    /// match the commitments, and reshares this party's share of x * z.
+    pub fn reshare(
+        &mut self,
+        rng: &mut RAND,
+        products: &[DkgProductShare<B>],
+    ) -> Result<(DkgReshareCommitments<B>, Vec<DkgShare<B>>)> {
+        let [x, _, rho, _, product_mask, rho_blinding, _, product_mask_blinding] =
+            self.shares.ok_or(CredentialError::KeyGeneration)?;
+
+        let valid = products
+            .iter()
+            .filter(|product| {
+                if !(1..=self.nodes).contains(&product.index) {
+                    return false;
+                }
+                let public = self.public_share(product.index);
+                self.product_statement(product.index, &public, &product.mu)
+                    .verify(&product.proof)
+            })
+            .map(|product| product.index)
+            .collect();
+        let indices = self.interpolation_set(valid)?;
+
+        let mu = indices.iter().fold(zero::<B>(), |mu, &index| {
+            let product = products
+                .iter()
+                .find(|product| product.index == index)
+                .expect("interpolation set outside the products");
+            let lambda = lagrange_coefficient::<B>(index, indices.iter().copied());
+            B::scalar_add(&mu, &B::scalar_mul(&lambda, &product.mu))
+        });
+        if B::scalar_equals(&mu, &zero::<B>()) {
+            return Err(CredentialError::KeyGeneration);
+        }
+        // mu is public, so inverting it in variable time leaks nothing
+        let inverse = B::scalar_inv(&mu);
+        let z = B::scalar_mul(&rho, &inverse);
+        let product = B::scalar_add(&B::scalar_mul(&x, &z), &product_mask);
+
+        let len = self.threshold.into();
+        let blinding = B::scalar_random(rng);
+        self.polynomials = Vec::from([
+            random_polynomial::<B>(rng, product, len),
+            random_polynomial::<B>(rng, blinding, len),
+        ]);
+        self.mu = Some(mu);
+        self.z = Some(z);
+
+        // H_j(0) - O'_j = x_j * (R_j / mu) + (h~_j(0) - o'~_j - x_j * rho~_j / mu) * H
+        let coefficients =
+            pedersen_commit::<B>(&self.h, &self.polynomials[0], &self.polynomials[1]);
+        let public = self.public_share(self.index);
+        let blinding = B::scalar_add(
+            &blinding,
+            &B::scalar_neg(&B::scalar_add(
+                &product_mask_blinding,
+                &B::scalar_mul(&x, &B::scalar_mul(&rho_blinding, &inverse)),
+            )),
+        );
+        let proof = self
+            .reshare_statement(self.index, &public, &coefficients[0], &inverse)
+            .prove(rng, &x, &blinding);
+        let commitments = DkgReshareCommitments {
+            dealer: self.index,
+            coefficients,
+            proof,
+        };
+        Ok((commitments, self.deal()))
+    }
+
+    /// Fifth round: checks the reshares like the dealings of the second
+    /// round. Resharers whose constant term is not their share of x * z + o'
+    /// are dropped outright.
+    pub fn receive_reshares(
+        &mut self,
+        commitments: &[DkgReshareCommitments<B>],
+        shares: &[DkgShare<B>],
+    ) -> Result<Vec<DkgComplaint>> {
+        let mu = self.mu.ok_or(CredentialError::KeyGeneration)?;
+        let inverse = B::scalar_inv(&mu);
+
+        let mut reshares: Vec<_> = commitments
+            .iter()
+            .filter(|reshare| {
+                if !(1..=self.nodes).contains(&reshare.dealer)
+                    || reshare.coefficients.len() != usize::from(self.threshold)
+                    || commitments
+                        .iter()
+                        .filter(|other| other.dealer == reshare.dealer)
+                        .count()
+                        != 1
+                {
+                    return false;
+                }
+                let public = self.public_share(reshare.dealer);
+                self.reshare_statement(reshare.dealer, &public, &reshare.coefficients[0], &inverse)
+                    .verify(&reshare.proof)
+            })
+            .cloned()
+            .collect();
+        reshares.sort_by_key(|reshare| reshare.dealer);
+
+        let dealers: Vec<_> = reshares.iter().map(|reshare| reshare.dealer).collect();
+        let h = self.h.clone();
+        let complaints = self.check_shares(&dealers, shares, |share| {
+            reshares.iter().any(|reshare| reshare.verifies(&h, share))
+        });
+        self.reshares = reshares;
+        Ok(complaints)
+    }
+
+    /// Sixth round: interpolates this party's share of w from the reshares
+    /// of 2t - 1 qualified parties, and commits to the nonces of the proofs.
+    pub fn resolve_reshare_complaints(
+        &mut self,
+        rng: &mut RAND,
+        complaints: &[DkgComplaint],
+        answers: &[DkgShare<B>],
+    ) -> Result<DkgProofCommitment<B>> {
+        if self.z.is_none() {
+            return Err(CredentialError::KeyGeneration);
+        }
+        let dealers: Vec<_> = self.reshares.iter().map(|reshare| reshare.dealer).collect();
+        let qualified = self.qualified_shares(&dealers, complaints, answers, |share| {
+            self.reshares
+                .iter()
+                .any(|reshare| reshare.verifies(&self.h, share))
+        });
+        let indices =
+            self.interpolation_set(qualified.iter().map(|share| share.dealer).collect())?;
+
+        let w = qualified
+            .iter()
+            .filter(|share| indices.contains(&share.dealer))
+            .fold(zero::<B>(), |w, share| {
+                let lambda = lagrange_coefficient::<B>(share.dealer, indices.iter().copied());
+                B::scalar_add(&w, &B::scalar_mul(&lambda, &share.values[0]))
+            });
+        self.w = Some(w);
+        self.polynomials.clear();
+
+        let rx = B::scalar_random(rng);
+        let ry = B::scalar_random(rng);
+        self.nonces = Some((rx, ry));
+        let g2 = B::g2_generator();
+        Ok(DkgProofCommitment {
+            index: self.index,
+            x: B::g2_mul(&g2, &rx),
+            y: B::g2_mul(&g2, &ry),
+        })
+    }
+
+    /// Challenges of the proofs for the nonce commitments of at least t
+    /// parties.
+    fn proof_challenges(
+        &self,
+        commitments: &[DkgProofCommitment<B>],
+    ) -> Result<(B::Scalar, B::Scalar)> {
+        if commitments.len() < self.threshold.into()
+            || !indices_are_valid(self.nodes, commitments.iter().map(|c| c.index))
+        {
+            return Err(CredentialError::KeyGeneration);
+        }
+
+        let (rx, ry) = commitments[1..].iter().fold(
+            (commitments[0].x.clone(), commitments[0].y.clone()),
+            |(rx, ry), commitment| (B::g2_add(&rx, &commitment.x), B::g2_add(&ry, &commitment.y)),
+        );
+        let (x, y) = self.public_key();
+        let g2 = B::g2_generator();
+        Ok((
+            ecp2_challenge::<B>(self.suite, &x, &g2, &rx),
+            ecp2_challenge::<B>(self.suite, &y, &g2, &ry),
+        ))
+    }
+
+    /// Seventh round: responds to the challenges of the proofs, given the
+    /// nonce commitments of every party taking part, this one included. The
+    /// nonces are used only once.
+    pub fn prove(&mut self, commitments: &[DkgProofCommitment<B>]) -> Result<DkgProofResponse<B>> {
+        let [x, y, ..] = self.shares.ok_or(CredentialError::KeyGeneration)?;
+        let (rx, ry) = self.nonces.take().ok_or(CredentialError::KeyGeneration)?;
+        if !commitments
+            .iter()
+            .any(|commitment| commitment.index == self.index)
+        {
+            return Err(CredentialError::KeyGeneration);
+        }
+        let (cx, cy) = self.proof_challenges(commitments)?;
+
+        let lambda = lagrange_coefficient::<B>(
+            self.index,
+            commitments.iter().map(|commitment| commitment.index),
+        );
+        Ok(DkgProofResponse {
+            index: self.index,
+            x: B::scalar_add(&B::scalar_mul(&cx, &B::scalar_mul(&lambda, &x)), &rx),
+            y: B::scalar_add(&B::scalar_mul(&cy, &B::scalar_mul(&lambda, &y)), &ry),
+        })
+    }
+
+    /// Checks every response against the commitments to its party's shares
+    /// and assembles the group public key and this party's key share.
+    pub fn finish(
+        self,
+        commitments: &[DkgProofCommitment<B>],
+        responses: &[DkgProofResponse<B>],
+    ) -> Result<(GroupPublicKey<B>, IssuerKeyShare<B>)> {
+        let [x, ..] = self.shares.ok_or(CredentialError::KeyGeneration)?;
+        let (z, w) = self.z.zip(self.w).ok_or(CredentialError::KeyGeneration)?;
+        if responses.len() != commitments.len() {
+            return Err(CredentialError::KeyGeneration);
+        }
+        let (cx, cy) = self.proof_challenges(commitments)?;
+
+        let g2 = B::g2_generator();
+        let mut sx = zero::<B>();
+        let mut sy = zero::<B>();
+        for commitment in commitments {
+            let response = responses
+                .iter()
+                .find(|response| response.index == commitment.index)
+                .ok_or(CredentialError::KeyGeneration)?;
+            let lambda = lagrange_coefficient::<B>(
+                commitment.index,
+                commitments.iter().map(|commitment| commitment.index),
+            );
+            let public = self.public_share(commitment.index);
+            let checks = [
+                (&response.x, &cx, &public.x, &commitment.x),
+                (&response.y, &cy, &public.y, &commitment.y),
+            ];
+            for (s, c, share, nonce) in checks {
+                let expected = B::g2_add(&B::g2_mul(share, &B::scalar_mul(c, &lambda)), nonce);
+                if !g2_equals::<B>(&B::g2_mul(&g2, s), &expected) {
+                    return Err(CredentialError::KeyGeneration);
+                }
+            }
+            sx = B::scalar_add(&sx, &response.x);
+            sy = B::scalar_add(&sy, &response.y);
+        }
+
+        let (public_x, public_y) = self.public_key();
+        let public_key = GroupPublicKey {
+            x: public_x,
+            y: public_y,
+            cx,
+            sx,
+            cy,
+            sy,
+        };
+        verify_group_public_key::<B>(self.suite, &public_key)
+            .map_err(|_| CredentialError::KeyGeneration)?;
+
+        let share = IssuerKeyShare {
+            suite: self.suite,
+            index: self.index,
+            threshold: self.threshold,
+            nodes: self.nodes,
+            x,
+            z,
+            w,
+        };
+        Ok((public_key, share))
+    }
+}
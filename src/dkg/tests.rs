@@ -0,0 +1,364 @@
+use super::*;
+use crate::issuer::rng;
+use crate::join::{finish_join, start_join};
+use crate::sign::sign;
+use crate::threshold::combine_join_response;
+use crate::verify::verify;
+use crate::Bn254 as Backend;
+use crate::{
+    DkgCommitments, DkgParty, DkgProductShare, DkgReshareCommitments, DkgShare, GroupPublicKey,
+    IssuerKeyShare, StartJoinResult,
+};
+
+type Scalar = <Backend as PairingBackend>::Scalar;
+type G1 = <Backend as PairingBackend>::G1;
+
+const CHALLENGE: &[u8] = b"challenge";
+
+fn one() -> Scalar {
+    scalar_from_u64::<Backend>(1)
+}
+
+/// Misbehaviour injected into the messages in flight.
+#[derive(Default)]
+struct Faults {
+    /// (dealer, recipient, answers honestly): the dealer sends the recipient a
+    /// wrong share, and answers the complaint with the right or a wrong one.
+    bad_shares: Vec<(u16, u16, bool)>,
+    /// Parties that open a wrong product share.
+    bad_products: Vec<u16>,
+    /// Parties that reshare a wrong value under consistent commitments.
+    bad_reshares: Vec<u16>,
+}
+
+/// What every party ends up with, and the broadcasts of the first, third
+/// and fourth rounds.
+struct Outcome {
+    dealings: Vec<DkgCommitments>,
+    products: Vec<DkgProductShare>,
+    reshares: Vec<DkgReshareCommitments>,
+    keys: Vec<GroupPublicKey>,
+    shares: Vec<IssuerKeyShare>,
+}
+
+/// Runs every round between `nodes` parties in memory, routing each share
+/// to its recipient only and every other message to all parties.
+fn simulate(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    threshold: u16,
+    nodes: u16,
+    faults: &Faults,
+) -> Result<Outcome> {
+    let mut parties = Vec::new();
+    let mut dealings = Vec::new();
+    let mut shares = Vec::new();
+    for index in 1..=nodes {
+        let (party, commitments, dealt) = DkgParty::new(rng, suite, index, threshold, nodes)?;
+        parties.push(party);
+        dealings.push(commitments);
+        shares.extend(dealt);
+    }
+    for &(dealer, recipient, _) in &faults.bad_shares {
+        for share in shares.iter_mut() {
+            if share.dealer == dealer && share.recipient == recipient {
+                share.values[0] = Backend::scalar_add(&share.values[0], &one());
+            }
+        }
+    }
+
+    let private = |party: &DkgParty, shares: &[DkgShare]| -> Vec<DkgShare> {
+        shares
+            .iter()
+            .filter(|share| share.recipient == party.index())
+            .cloned()
+            .collect()
+    };
+
+    let mut complaints = Vec::new();
+    for party in parties.iter_mut() {
+        let mine = private(party, &shares);
+        complaints.extend(party.receive_dealings(&dealings, &mine));
+    }
+    let mut answers: Vec<_> = parties
+        .iter()
+        .flat_map(|party| party.answer_complaints(&complaints))
+        .collect();
+    for &(dealer, _, honest) in &faults.bad_shares {
+        for answer in answers.iter_mut() {
+            if !honest && answer.dealer == dealer {
+                answer.values[0] = Backend::scalar_add(&answer.values[0], &one());
+            }
+        }
+    }
+
+    let mut products = Vec::new();
+    for party in parties.iter_mut() {
+        let mut product = party.resolve_complaints(rng, &complaints, &answers)?;
+        if faults.bad_products.contains(&product.index) {
+            product.mu = Backend::scalar_add(&product.mu, &one());
+        }
+        products.push(product);
+    }
+
+    let mut reshares = Vec::new();
+    let mut shares = Vec::new();
+    for party in parties.iter_mut() {
+        let (mut commitments, mut dealt) = party.reshare(rng, &products)?;
+        if faults.bad_reshares.contains(&party.index()) {
+            party.polynomials[0][0] = Backend::scalar_add(&party.polynomials[0][0], &one());
+            commitments.coefficients =
+                pedersen_commit::<Backend>(&party.h, &party.polynomials[0], &party.polynomials[1]);
+            dealt = party.deal();
+        }
+        reshares.push(commitments);
+        shares.extend(dealt);
+    }
+    let mut complaints = Vec::new();
+    for party in parties.iter_mut() {
+        let mine = private(party, &shares);
+        complaints.extend(party.receive_reshares(&reshares, &mine)?);
+    }
+    let answers: Vec<_> = parties
+        .iter()
+        .flat_map(|party| party.answer_complaints(&complaints))
+        .collect();
+
+    let mut commitments = Vec::new();
+    for party in parties.iter_mut() {
+        commitments.push(party.resolve_reshare_complaints(rng, &complaints, &answers)?);
+    }
+    let mut responses = Vec::new();
+    for party in parties.iter_mut() {
+        responses.push(party.prove(&commitments)?);
+    }
+    let (keys, shares) = parties
+        .into_iter()
+        .map(|party| party.finish(&commitments, &responses))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+    Ok(Outcome {
+        dealings,
+        products,
+        reshares,
+        keys,
+        shares,
+    })
+}
+
+/// Issues credentials with the nodes at `indices` and checks them.
+fn issue_and_sign(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    public_key: &GroupPublicKey,
+    shares: &[IssuerKeyShare],
+    indices: &[u16],
+) {
+    let StartJoinResult { gsk, join_msg } = start_join(rng, suite, CHALLENGE);
+    let (nonces, commitments): (Vec<_>, Vec<_>) = indices
+        .iter()
+        .map(|&i| {
+            shares[i as usize - 1]
+                .commit(rng, CHALLENGE, &join_msg)
+                .unwrap()
+        })
+        .unzip();
+    let partials: Vec<_> = indices
+        .iter()
+        .zip(nonces)
+        .map(|(&i, nonce)| {
+            shares[i as usize - 1]
+                .respond(nonce, &join_msg, &commitments)
+                .unwrap()
+        })
+        .collect();
+    let response =
+        combine_join_response(suite, public_key, &join_msg, &commitments, &partials).unwrap();
+
+    let credentials = finish_join(suite, public_key, &gsk, response).unwrap();
+    let signature = sign(rng, suite, &gsk, &credentials, b"message", b"bsn");
+    verify(suite, public_key, &signature, b"message", b"bsn").unwrap();
+}
+
+/// Interpolates a secret from the shares at `indices`.
+fn interpolate(
+    shares: &[IssuerKeyShare],
+    indices: &[u16],
+    value: fn(&IssuerKeyShare) -> Scalar,
+) -> Scalar {
+    indices.iter().fold(zero::<Backend>(), |sum, &index| {
+        let lambda = lagrange_coefficient::<Backend>(index, indices.iter().copied());
+        let share = value(&shares[index as usize - 1]);
+        Backend::scalar_add(&sum, &Backend::scalar_mul(&lambda, &share))
+    })
+}
+
+#[test]
+fn test_dkg() {
+    for (suite, threshold, nodes) in [
+        (Ciphersuite::default(), 2, 3),
+        (Ciphersuite::Bn254Sha256V1, 3, 5),
+    ] {
+        let mut rng = rng();
+        let Outcome { keys, shares, .. } =
+            simulate(&mut rng, suite, threshold, nodes, &Faults::default()).unwrap();
+        let public_key = &keys[0];
+        for other in &keys {
+            assert_eq!(other.to_bytes(), public_key.to_bytes());
+        }
+
+        // The shares are of x, 1 / y and x / y for the public key
+        let indices: Vec<_> = (1..=threshold).collect();
+        let g2 = Backend::g2_generator();
+        let x = interpolate(&shares, &indices, |share| share.x);
+        let z = interpolate(&shares, &indices, |share| share.z);
+        let w = interpolate(&shares, &indices, |share| share.w);
+        assert!(g2_equals::<Backend>(
+            &Backend::g2_mul(&g2, &x),
+            &public_key.x
+        ));
+        assert!(g2_equals::<Backend>(
+            &Backend::g2_mul(&public_key.y, &z),
+            &g2
+        ));
+        assert!(Backend::scalar_equals(&w, &Backend::scalar_mul(&x, &z)));
+
+        issue_and_sign(&mut rng, suite, public_key, &shares, &indices);
+        let others: Vec<_> = (nodes - threshold + 1..=nodes).rev().collect();
+        issue_and_sign(&mut rng, suite, public_key, &shares, &others);
+    }
+}
+
+#[test]
+fn test_dkg_complaints() {
+    let suite = Ciphersuite::Bn254Sha256V1;
+    let mut rng = rng();
+    let faults = Faults {
+        bad_shares: Vec::from([(2, 4, true), (3, 1, false)]),
+        bad_products: Vec::from([1]),
+        bad_reshares: Vec::from([2]),
+    };
+    let Outcome {
+        dealings,
+        keys,
+        shares,
+        ..
+    } = simulate(&mut rng, suite, 2, 5, &faults).unwrap();
+
+    // Dealer 3 is disqualified, dealer 2 answered its complaint
+    let public_key = &keys[0];
+    let x = [1, 2, 4, 5]
+        .iter()
+        .map(|&dealer| dealings[dealer - 1].x[0].clone())
+        .reduce(|sum, point| Backend::g2_add(&sum, &point))
+        .unwrap();
+    assert!(g2_equals::<Backend>(&x, &public_key.x));
+    issue_and_sign(&mut rng, suite, public_key, &shares, &[1, 3]);
+    issue_and_sign(&mut rng, suite, public_key, &shares, &[2, 5]);
+}
+
+#[test]
+fn test_dkg_broadcasts_hide_rho() {
+    let suite = Ciphersuite::Bn254Sha256V1;
+    let (threshold, nodes) = (2, 3);
+    let mut rng = rng();
+    let Outcome {
+        dealings,
+        products,
+        reshares,
+        shares,
+        ..
+    } = simulate(&mut rng, suite, threshold, nodes, &Faults::default()).unwrap();
+
+    // G1 / y and x / y * G1 would let anyone forge a credential for gsk = 0
+    let indices: Vec<_> = (1..=threshold).collect();
+    let g1 = Backend::g1_generator();
+    let z = interpolate(&shares, &indices, |share| share.z);
+    let w = interpolate(&shares, &indices, |share| share.w);
+    let forged = [Backend::g1_mul(&g1, &z), Backend::g1_mul(&g1, &w)];
+    let leaks = |point: &G1| {
+        forged
+            .iter()
+            .any(|target| g1_equals::<Backend>(point, target))
+    };
+
+    // The public mu = rho * y from 2t - 1 product shares
+    let all: Vec<_> = (1..=nodes).collect();
+    let mu = products.iter().fold(zero::<Backend>(), |mu, product| {
+        let lambda = lagrange_coefficient::<Backend>(product.index, all.iter().copied());
+        Backend::scalar_add(&mu, &Backend::scalar_mul(&lambda, &product.mu))
+    });
+    let inverse = Backend::scalar_inv(&mu);
+
+    // Summed constant commitments to rho, divided by mu
+    let rho = dealings
+        .iter()
+        .map(|dealing| dealing.rho[0].clone())
+        .reduce(|sum, point| Backend::g1_add(&sum, &point))
+        .unwrap();
+    assert!(!leaks(&Backend::g1_mul(&rho, &inverse)));
+
+    // Constant commitments of the reshares, interpolated so that o' vanishes
+    let constant = reshares
+        .iter()
+        .map(|reshare| {
+            let lambda = lagrange_coefficient::<Backend>(reshare.dealer, all.iter().copied());
+            Backend::g1_mul(&reshare.coefficients[0], &lambda)
+        })
+        .reduce(|sum, point| Backend::g1_add(&sum, &point))
+        .unwrap();
+    assert!(!leaks(&constant));
+
+    // Nor is any single broadcast point, as is or divided by mu
+    let points = dealings
+        .iter()
+        .flat_map(|dealing| {
+            dealing
+                .rho
+                .iter()
+                .chain(&dealing.mask)
+                .chain(&dealing.product_mask)
+        })
+        .chain(reshares.iter().flat_map(|reshare| &reshare.coefficients));
+    for point in points {
+        assert!(!leaks(point));
+        assert!(!leaks(&Backend::g1_mul(point, &inverse)));
+    }
+}
+
+#[test]
+fn test_dkg_needs_honest_majority() {
+    let suite = Ciphersuite::Bn254Sha256V1;
+    let mut rng = rng();
+    assert!(DkgParty::new(&mut rng, suite, 1, 0, 3).is_err());
+    assert!(DkgParty::new(&mut rng, suite, 1, 3, 4).is_err());
+    assert!(DkgParty::new(&mut rng, suite, 0, 2, 3).is_err());
+    assert!(DkgParty::new(&mut rng, suite, 4, 2, 3).is_err());
+
+    // Mu and w each need 2t - 1 parties that follow the protocol
+    for faults in [
+        Faults {
+            bad_products: Vec::from([3]),
+            ..Faults::default()
+        },
+        Faults {
+            bad_reshares: Vec::from([1]),
+            ..Faults::default()
+        },
+    ] {
+        assert!(matches!(
+            simulate(&mut rng, suite, 2, 3, &faults),
+            Err(CredentialError::KeyGeneration)
+        ));
+    }
+
+    // A dealer that has shares rejected by everyone is dropped
+    let faults = Faults {
+        bad_shares: Vec::from([(1, 1, false), (1, 2, false), (1, 3, false)]),
+        ..Faults::default()
+    };
+    let Outcome { dealings, keys, .. } = simulate(&mut rng, suite, 2, 3, &faults).unwrap();
+    let x = Backend::g2_add(&dealings[1].x[0], &dealings[2].x[0]);
+    assert!(g2_equals::<Backend>(&x, &keys[0].x));
+}
@@ -0,0 +1,349 @@
+use std::vec::Vec;
+
+use brave_miracl::rand::RAND;
+
+#[cfg(feature = "alloc")]
+use super::attributes::{
+    finish_join_with_attributes, Attribute, AttributeCredentials, AttributeGroupPublicKey,
+    AttributeJoinResponse, AttributeKey, CertifiedAttribute,
+};
+use super::backend::{Bn254, PairingBackend, BIG_SIZE};
+use super::ciphersuite::Ciphersuite;
+use super::data::{
+    CredentialBIG, CredentialMetadata, EpochGroupPublicKey, GroupPublicKey, JoinRequest,
+    JoinResponse, StartJoinResult, UserCredentials,
+};
+use super::join::{
+    finish_join, join_request_is_valid, join_response_transcript, make_ecp2_proof,
+    make_group_public_key, start_join,
+};
+use super::migrate::assign_epoch;
+use super::util::make_ecp_proof_equals;
+use super::Result;
+
+const CHALLENGE: &[u8] = b"challenge";
+
+/// Random number generator with a fixed seed.
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+pub fn rng() -> RAND {
+    let mut rng = RAND::new();
+    rng.seed(1, &[1]);
+    rng
+}
+
+/// Adds the curve order of `B` to the big-endian scalar `bytes`, giving a
+/// second encoding of the same value mod the order.
+pub fn add_curve_order<B: PairingBackend>(bytes: &mut [u8]) {
+    // r - 1 = -1, so add -1 and then 1
+    let mut one = [0u8; BIG_SIZE];
+    one[BIG_SIZE - 1] = 1;
+    let mut minus_one = [0u8; BIG_SIZE];
+    B::scalar_to_bytes(&B::scalar_neg(&B::scalar_from_bytes(&one)), &mut minus_one);
+
+    let mut carry = 1u16;
+    for (byte, addend) in bytes.iter_mut().zip(minus_one).rev() {
+        let sum = u16::from(*byte) + u16::from(addend) + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+    assert_eq!(carry, 0, "scalar overflows {} bytes", BIG_SIZE);
+}
+
+/// Join response with the (A_i, B_i) of each certified attribute.
+type CertifiedResponse<B> = (
+    JoinResponse<B>,
+    Vec<(<B as PairingBackend>::G1, <B as PairingBackend>::G1)>,
+);
+
+/// Minimal issuer used to produce group keys and join responses for tests.
+pub struct Issuer<B: PairingBackend = Bn254> {
+    suite: Ciphersuite,
+    x: B::Scalar,
+    y: B::Scalar,
+    // z_i of each attribute, with Z_i and its proof
+    z: Vec<B::Scalar>,
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    attribute_keys: Vec<(B::G2, B::Scalar, B::Scalar)>,
+    public_key: GroupPublicKey<B>,
+}
+
+impl<B: PairingBackend> Issuer<B> {
+    pub fn new(rng: &mut RAND, suite: Ciphersuite) -> Self {
+        Self::new_with_attributes(rng, suite, 0)
+    }
+
+    /// Creates an issuer certifying `count` attributes per member. The
+    /// plain key is drawn first, so it matches `new` for the same `rng`.
+    pub fn new_with_attributes(rng: &mut RAND, suite: Ciphersuite, count: usize) -> Self {
+        let x = B::scalar_random(rng);
+        let y = B::scalar_random(rng);
+
+        let public_key = make_group_public_key::<B>(rng, suite, &x, &y);
+
+        let g2 = B::g2_generator();
+
+        let z: Vec<_> = (0..count).map(|_| B::scalar_random(rng)).collect();
+        let attribute_keys = z
+            .iter()
+            .map(|z| {
+                let (c, s) = make_ecp2_proof::<B>(rng, suite, z);
+                (B::g2_mul(&g2, z), c, s)
+            })
+            .collect();
+
+        Self {
+            suite,
+            x,
+            y,
+            z,
+            attribute_keys,
+            public_key,
+        }
+    }
+
+    pub fn public_key(&self) -> &GroupPublicKey<B> {
+        &self.public_key
+    }
+
+    /// The public key as the key of `epoch`.
+    pub fn epoch_public_key(&self, rng: &mut RAND, epoch: u64) -> EpochGroupPublicKey<B> {
+        assign_epoch(
+            rng,
+            self.suite,
+            epoch,
+            self.public_key.clone(),
+            &self.x,
+            &self.y,
+        )
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn attribute_public_key(&self) -> AttributeGroupPublicKey<B> {
+        AttributeGroupPublicKey {
+            base: self.public_key.clone(),
+            attributes: self
+                .attribute_keys
+                .iter()
+                .map(|(z, c, s)| AttributeKey {
+                    z: z.clone(),
+                    c: *c,
+                    s: *s,
+                })
+                .collect(),
+        }
+    }
+
+    /// The secret exponents (x, y) behind the public key.
+    pub fn secret_key(&self) -> (&B::Scalar, &B::Scalar) {
+        (&self.x, &self.y)
+    }
+
+    /// Issues credentials for a member, or `None` if the join request proof
+    /// does not verify against `challenge`.
+    pub fn join(
+        &self,
+        rng: &mut RAND,
+        challenge: &[u8],
+        req: &JoinRequest<B>,
+    ) -> Option<JoinResponse<B>> {
+        if !join_request_is_valid::<B>(self.suite, challenge, req) {
+            return None;
+        }
+
+        let r = B::scalar_random(rng);
+        Some(self.issue(rng, &req.q, &r))
+    }
+
+    /// Like `join`, binding the response to `metadata`.
+    pub fn join_with_metadata(
+        &self,
+        rng: &mut RAND,
+        challenge: &[u8],
+        req: &JoinRequest<B>,
+        metadata: &CredentialMetadata,
+    ) -> Option<JoinResponse<B>> {
+        if !join_request_is_valid::<B>(self.suite, challenge, req) {
+            return None;
+        }
+
+        let r = B::scalar_random(rng);
+        Some(
+            self.issue_certifying(rng, &req.q, &r, &[], Some(metadata))
+                .0,
+        )
+    }
+
+    /// Issues credentials certifying `values`, one per attribute of the
+    /// key, or `None` if the join request proof does not verify.
+    #[cfg(feature = "alloc")]
+    pub fn join_with_attributes(
+        &self,
+        rng: &mut RAND,
+        challenge: &[u8],
+        req: &JoinRequest<B>,
+        values: &[Attribute<B>],
+    ) -> Option<AttributeJoinResponse<B>> {
+        if !join_request_is_valid::<B>(self.suite, challenge, req) {
+            return None;
+        }
+
+        let r = B::scalar_random(rng);
+        Some(self.issue_with_attributes(rng, &req.q, &r, values))
+    }
+
+    /// Like `issue`, certifying `values` as `join_with_attributes` does.
+    #[cfg(feature = "alloc")]
+    pub fn issue_with_attributes(
+        &self,
+        rng: &mut RAND,
+        q: &B::G1,
+        r: &B::Scalar,
+        values: &[Attribute<B>],
+    ) -> AttributeJoinResponse<B> {
+        assert_eq!(values.len(), self.z.len());
+        let values: Vec<_> = values.iter().map(|value| value.0).collect();
+        let (base, certified) = self.issue_certifying(rng, q, r, &values, None);
+        AttributeJoinResponse {
+            base,
+            attributes: certified
+                .into_iter()
+                .zip(values)
+                .map(|((a, b), value)| CertifiedAttribute { a, b, value })
+                .collect(),
+        }
+    }
+
+    /// Issues credentials for the member key `q` = G1 ** gsk with the
+    /// randomness `r`, without checking a join request.
+    pub fn issue(&self, rng: &mut RAND, q: &B::G1, r: &B::Scalar) -> JoinResponse<B> {
+        self.issue_certifying(rng, q, r, &[], None).0
+    }
+
+    /// Like `issue`, also certifying the first `values.len()` attributes and
+    /// returning their (A_i, B_i), and binding the proof to `metadata` if
+    /// given.
+    fn issue_certifying(
+        &self,
+        rng: &mut RAND,
+        q: &B::G1,
+        r: &B::Scalar,
+        values: &[B::Scalar],
+        metadata: Option<&CredentialMetadata>,
+    ) -> CertifiedResponse<B> {
+        let k = B::scalar_mul(r, &self.y);
+
+        // A = G1 ** r, B = A ** y, D = Q ** (r * y), C = (A + D) ** x
+        let g1 = B::g1_generator();
+        let a = B::g1_mul(&g1, r);
+        let b = B::g1_mul(&g1, &k);
+        let d = B::g1_mul(q, &k);
+
+        // A_i = A ** z_i, B_i = A_i ** y, and C gains m_i * B_i per attribute
+        let certified: Vec<_> = self
+            .z
+            .iter()
+            .zip(values)
+            .map(|(z, _)| {
+                let a_i = B::g1_mul(&a, z);
+                let b_i = B::g1_mul(&a_i, &self.y);
+                (a_i, b_i)
+            })
+            .collect();
+        let sum = certified
+            .iter()
+            .zip(values)
+            .fold(B::g1_add(&a, &d), |sum, ((_, b_i), value)| {
+                B::g1_add(&sum, &B::g1_mul(b_i, value))
+            });
+        let c = B::g1_mul(&sum, &self.x);
+
+        let proof = make_ecp_proof_equals::<B>(
+            rng,
+            join_response_transcript::<B>(self.suite, metadata),
+            &g1,
+            q,
+            &b,
+            &d,
+            &k,
+        );
+
+        (
+            JoinResponse {
+                cred: UserCredentials { a, b, c, d },
+                proof,
+            },
+            certified,
+        )
+    }
+}
+
+/// An issuer on bn254 and a member of its group holding `credentials`,
+/// with a random number generator seeded by the suite.
+pub struct Fixture<C = UserCredentials<Bn254>> {
+    pub suite: Ciphersuite,
+    pub issuer: Issuer<Bn254>,
+    pub gsk: CredentialBIG<Bn254>,
+    pub join_msg: JoinRequest<Bn254>,
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    pub credentials: C,
+    pub rng: RAND,
+}
+
+impl Fixture {
+    pub fn new(suite: Ciphersuite) -> Self {
+        Self::joined(suite, 0, |rng, issuer, gsk, join_msg| {
+            let response = issuer.join(rng, CHALLENGE, join_msg).unwrap();
+            finish_join(suite, issuer.public_key(), gsk, response).unwrap()
+        })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Fixture<AttributeCredentials<Bn254>> {
+    /// Like `new`, with credentials certifying `values`.
+    pub fn new_with_attributes(suite: Ciphersuite, values: &[Attribute<Bn254>]) -> Self {
+        Self::joined(suite, values.len(), |rng, issuer, gsk, join_msg| {
+            let response = issuer
+                .join_with_attributes(rng, CHALLENGE, join_msg, values)
+                .unwrap();
+            finish_join_with_attributes(suite, &issuer.attribute_public_key(), gsk, response)
+                .unwrap()
+        })
+    }
+}
+
+impl<C> Fixture<C> {
+    /// Sets up an issuer certifying `count` attributes and a join request,
+    /// and gets the credentials from `join`.
+    fn joined(
+        suite: Ciphersuite,
+        count: usize,
+        join: impl FnOnce(&mut RAND, &Issuer<Bn254>, &CredentialBIG<Bn254>, &JoinRequest<Bn254>) -> C,
+    ) -> Self {
+        let mut rng = RAND::new();
+        rng.seed(1, &[suite.id()]);
+
+        let issuer = Issuer::new_with_attributes(&mut rng, suite, count);
+        let StartJoinResult { gsk, join_msg } = start_join(&mut rng, suite, CHALLENGE);
+        let credentials = join(&mut rng, &issuer, &gsk, &join_msg);
+        Self {
+            suite,
+            issuer,
+            gsk,
+            join_msg,
+            credentials,
+            rng,
+        }
+    }
+
+    /// Issues another response to the member's join request.
+    pub fn response(&mut self) -> JoinResponse<Bn254> {
+        self.issuer
+            .join(&mut self.rng, CHALLENGE, &self.join_msg)
+            .unwrap()
+    }
+
+    pub fn finish(&self, response: JoinResponse<Bn254>) -> Result<UserCredentials<Bn254>> {
+        finish_join(self.suite, self.issuer.public_key(), &self.gsk, response)
+    }
+}
@@ -1,135 +1,200 @@
-use brave_miracl::{
-    bn254::{
-        big::BIG,
-        ecp::ECP,
-        ecp2::ECP2,
-        fp12::FP12,
-        pair::{g1mul, g2mul},
-    },
-    rand::RAND,
-};
+use brave_miracl::rand::RAND;
 
+use super::backend::{PairingBackend, BIG_SIZE};
+use super::ciphersuite::Ciphersuite;
 use super::data::{
-    ecp2_to_compat_bytes, CredentialBIG, ECPProof, GroupPublicKey, JoinRequest, JoinResponse,
-    StartJoinResult, UserCredentials, BIG_SIZE, ECP2_COMPAT_SIZE, ECP_SIZE,
+    CredentialBIG, CredentialMetadata, ECPProof, GroupPublicKey, JoinRequest, JoinResponse,
+    StartJoinResult, UserCredentials,
 };
-use super::util::{
-    ecp_challenge_equals, hash256, pair_normalized_triple_ate, random_mod_curve_order,
-    CURVE_ORDER_BIG, G1_ECP, G2_ECP,
+use super::transcript::{
+    Transcript, PROTOCOL_ISSUER_KEY, PROTOCOL_JOIN_REQUEST, PROTOCOL_JOIN_RESPONSE,
 };
+#[cfg(feature = "alloc")]
+use super::util::g1_equals;
+use super::util::verify_ecp_proof_equals;
+#[cfg(feature = "alloc")]
+use super::verify::randomized_credentials_are_valid;
 use super::{CredentialError, Result};
 
-fn ecp_challenge(message: &[u8; BIG_SIZE], y: &ECP, g: &ECP, gr: &ECP) -> BIG {
-    let mut all_bytes = [0u8; ECP_SIZE * 3 + BIG_SIZE];
-
-    all_bytes[..BIG_SIZE].copy_from_slice(message);
-
-    y.tobytes(&mut all_bytes[BIG_SIZE..], false);
-    g.tobytes(&mut all_bytes[ECP_SIZE + BIG_SIZE..], false);
-    gr.tobytes(&mut all_bytes[ECP_SIZE * 2 + BIG_SIZE..], false);
-
-    let hash = hash256(&all_bytes);
-
-    let mut c = BIG::frombytes(&hash);
-    c.rmod(&CURVE_ORDER_BIG);
-    c
+pub(crate) fn ecp_challenge<B: PairingBackend>(
+    suite: Ciphersuite,
+    message: &[u8; BIG_SIZE],
+    y: &B::G1,
+    g: &B::G1,
+    gr: &B::G1,
+) -> B::Scalar {
+    let mut transcript = Transcript::<B>::new(suite, PROTOCOL_JOIN_REQUEST);
+    transcript.append_bytes(b"challenge", message);
+    transcript.append_ecp(b"y", y);
+    transcript.append_ecp(b"g", g);
+    transcript.append_ecp(b"gr", gr);
+    transcript.challenge()
 }
 
-fn make_ecp_proof(rng: &mut RAND, y: &ECP, x: &BIG, message: &[u8; BIG_SIZE]) -> ECPProof {
-    let r = random_mod_curve_order(rng);
-
-    let g = G1_ECP.clone();
-    let gr = g1mul(&g, &r);
-    let c = ecp_challenge(message, y, &g, &gr);
-    let mut s = BIG::modmul(&c, x, &CURVE_ORDER_BIG);
-    s.add(&r);
-    s.rmod(&CURVE_ORDER_BIG);
+fn make_ecp_proof<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    y: &B::G1,
+    x: &B::Scalar,
+    message: &[u8; BIG_SIZE],
+) -> ECPProof<B> {
+    let r = B::scalar_random(rng);
+
+    let g = B::g1_generator();
+    let gr = B::g1_mul(&g, &r);
+    let c = ecp_challenge::<B>(suite, message, y, &g, &gr);
+    let s = B::scalar_add(&B::scalar_mul(&c, x), &r);
     ECPProof { c, s }
 }
 
-fn verify_ecp_proof_equals(a: &ECP, b: &ECP, y: &ECP, z: &ECP, proof: &ECPProof) -> bool {
-    let cn = BIG::modneg(&proof.c, &CURVE_ORDER_BIG);
+pub(crate) fn verify_aux_fast<B: PairingBackend>(
+    a: &B::G1,
+    b: &B::G1,
+    c: &B::G1,
+    d: &B::G1,
+    x: &B::G2,
+    y: &B::G2,
+    rng: &mut RAND,
+) -> bool {
+    if B::g1_is_infinity(a) {
+        return false;
+    }
 
-    let mut r#as = g1mul(a, &proof.s);
-    let yc = g1mul(y, &cn);
-    let mut bs = g1mul(b, &proof.s);
-    let zc = g1mul(z, &cn);
+    let e1 = B::scalar_random(rng);
+    let e2 = B::scalar_random(rng);
+    let ne1 = B::scalar_neg(&e1);
+    let ne2 = B::scalar_neg(&e2);
 
-    r#as.add(&yc);
-    bs.add(&zc);
+    // AA = e1 * A
+    let aa = B::g1_mul(a, &e1);
 
-    let cc = ecp_challenge_equals(None, &y, &z, &a, &b, &r#as, &bs);
+    // BB = (-e1 * B) + (-e2 * C)
+    let bb = B::g1_add(&B::g1_mul(b, &ne1), &B::g1_mul(c, &ne2));
 
-    BIG::comp(&proof.c, &cc) == 0
+    // CC = e2 * (A + D)
+    let cc = B::g1_mul(&B::g1_add(a, d), &e2);
+
+    // w = e(e1·A, Y)·e((-e1·B) + (-e2·C), G2)·e(e2·(A + D), X)
+    B::pairing_product_is_one(&[(y, &aa), (&B::g2_generator(), &bb), (x, &cc)])
 }
 
-fn verify_aux_fast(a: &ECP, b: &ECP, c: &ECP, d: &ECP, x: &ECP2, y: &ECP2, rng: &mut RAND) -> bool {
-    if a.is_infinity() {
-        return false;
+pub fn start_join<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    challenge: &[u8],
+) -> StartJoinResult<B> {
+    let gsk = B::scalar_random(rng);
+    let join_msg = join_request::<B>(rng, suite, &gsk, challenge);
+
+    StartJoinResult {
+        gsk: CredentialBIG(gsk),
+        join_msg,
     }
+}
 
-    let e1 = random_mod_curve_order(rng);
-    let e2 = random_mod_curve_order(rng);
-    let ne1 = BIG::modneg(&e1, &CURVE_ORDER_BIG);
-    let ne2 = BIG::modneg(&e2, &CURVE_ORDER_BIG);
+/// Join request for an existing `gsk`, proving knowledge of it against
+/// `challenge`.
+pub(crate) fn join_request<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    gsk: &B::Scalar,
+    challenge: &[u8],
+) -> JoinRequest<B> {
+    let q = B::g1_mul(&B::g1_generator(), gsk);
 
-    // AA = e1 * A
-    let aa = g1mul(a, &e1);
+    let challenge_hash = suite.hash(challenge);
 
-    // BB = -e1 * B
-    let mut bb = g1mul(b, &ne1);
+    let proof = make_ecp_proof::<B>(rng, suite, &q, gsk, &challenge_hash);
 
-    // CC = -e2 * C
-    let mut cc = g1mul(c, &ne2);
+    JoinRequest { q, proof }
+}
 
-    // BB = (-e1 * B) + (-e2 * C)
-    bb.add(&cc);
+/// Checks the proof of knowledge of gsk in a join request, and that gsk is
+/// not 0, which would give credentials that verify without a member key.
+pub(crate) fn join_request_is_valid<B: PairingBackend>(
+    suite: Ciphersuite,
+    challenge: &[u8],
+    req: &JoinRequest<B>,
+) -> bool {
+    if B::g1_is_infinity(&req.q) {
+        return false;
+    }
 
-    // CC = e2 * (A + D)
-    cc.copy(a);
-    cc.add(d);
-    cc = g1mul(&cc, &e2);
+    let cn = B::scalar_neg(&req.proof.c);
 
-    // w = e(e1·A, Y)·e((-e1·B) + (-e2·C), G2)·e(e2·(A + D), X)
-    let w = pair_normalized_triple_ate(y, &aa, &G2_ECP, &bb, x, &cc);
+    let g1 = B::g1_generator();
+    let gs = B::g1_add(&B::g1_mul(&g1, &req.proof.s), &B::g1_mul(&req.q, &cn));
 
-    let mut fp12_one = FP12::new();
-    fp12_one.one();
+    let cc = ecp_challenge::<B>(suite, &suite.hash(challenge), &req.q, &g1, &gs);
 
-    w.equals(&fp12_one)
+    B::scalar_equals(&req.proof.c, &cc)
 }
 
-pub fn start_join(rng: &mut RAND, challenge: &[u8]) -> StartJoinResult {
-    let gsk = random_mod_curve_order(rng);
-    let q = g1mul(&G1_ECP, &gsk);
-
-    let challenge_hash = hash256(challenge);
+/// Transcript of the issuer's proof in a join response, which also covers
+/// the issuance metadata if there is any. Issuers outside this crate follow
+/// the layout documented on `CredentialMetadata`.
+pub(crate) fn join_response_transcript<B: PairingBackend>(
+    suite: Ciphersuite,
+    metadata: Option<&CredentialMetadata>,
+) -> Transcript<B> {
+    let mut transcript = Transcript::<B>::new(suite, PROTOCOL_JOIN_RESPONSE);
+    if let Some(metadata) = metadata {
+        transcript.append_bytes(b"metadata", &metadata.to_bytes());
+    }
+    transcript
+}
 
-    let proof = make_ecp_proof(rng, &q, &gsk, &challenge_hash);
+pub fn finish_join<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &GroupPublicKey<B>,
+    gsk: &CredentialBIG<B>,
+    resp: JoinResponse<B>,
+) -> Result<UserCredentials<B>> {
+    verify_join_response(suite, pub_key, gsk, resp, None)
+}
 
-    StartJoinResult {
-        gsk: CredentialBIG(gsk),
-        join_msg: JoinRequest { q, proof },
+/// Like `finish_join`, for a response whose proof is bound to `metadata`
+/// for the group of `pub_key`.
+pub fn finish_join_with_metadata<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &GroupPublicKey<B>,
+    gsk: &CredentialBIG<B>,
+    resp: JoinResponse<B>,
+    metadata: &CredentialMetadata,
+) -> Result<UserCredentials<B>> {
+    if metadata.group != pub_key.id() {
+        return Err(CredentialError::JoinResponseValidation);
     }
+    verify_join_response(suite, pub_key, gsk, resp, Some(metadata))
 }
 
-pub fn finish_join(
-    pub_key: &GroupPublicKey,
-    gsk: &CredentialBIG,
-    resp: JoinResponse,
-) -> Result<UserCredentials> {
-    verify_group_public_key(pub_key)?;
+fn verify_join_response<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &GroupPublicKey<B>,
+    gsk: &CredentialBIG<B>,
+    resp: JoinResponse<B>,
+    metadata: Option<&CredentialMetadata>,
+) -> Result<UserCredentials<B>> {
+    verify_group_public_key(suite, pub_key)?;
 
-    let q = g1mul(&G1_ECP, &gsk.0);
+    let g1 = B::g1_generator();
+    let q = B::g1_mul(&g1, &gsk.0);
 
     let mut rng = RAND::new();
     rng.seed(BIG_SIZE, &gsk.to_bytes());
 
-    if !verify_ecp_proof_equals(&G1_ECP, &q, &resp.cred.b, &resp.cred.d, &resp.proof) {
+    if !verify_ecp_proof_equals(
+        join_response_transcript(suite, metadata),
+        &g1,
+        &q,
+        &resp.cred.b,
+        &resp.cred.d,
+        &resp.proof,
+    ) {
         return Err(CredentialError::JoinResponseValidation);
     }
 
-    if !verify_aux_fast(
+    if !verify_aux_fast::<B>(
         &resp.cred.a,
         &resp.cred.b,
         &resp.cred.c,
@@ -144,37 +209,316 @@ pub fn finish_join(
     Ok(resp.cred)
 }
 
-fn ecp2_challenge(y: &ECP2, g: &ECP2, gr: &ECP2) -> BIG {
-    let mut all_bytes = [0u8; ECP2_COMPAT_SIZE * 3];
-
-    all_bytes[..ECP2_COMPAT_SIZE].copy_from_slice(&ecp2_to_compat_bytes(y));
-    all_bytes[ECP2_COMPAT_SIZE..ECP2_COMPAT_SIZE * 2].copy_from_slice(&ecp2_to_compat_bytes(g));
-    all_bytes[ECP2_COMPAT_SIZE * 2..].copy_from_slice(&ecp2_to_compat_bytes(gr));
+/// Checks credentials that did not come straight from `finish_join`: they
+/// must satisfy the same relations under `pub_key`, and D = gsk * B.
+#[cfg(feature = "alloc")]
+pub(crate) fn verify_credentials<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &GroupPublicKey<B>,
+    gsk: &CredentialBIG<B>,
+    cred: &UserCredentials<B>,
+) -> Result<()> {
+    verify_group_public_key(suite, pub_key)?;
+    let d = B::g1_mul(&cred.b, &gsk.0);
+    if !g1_equals::<B>(&d, &cred.d)
+        || !randomized_credentials_are_valid(pub_key, &cred.a, &cred.b, &cred.c, &cred.d)
+    {
+        return Err(CredentialError::CredentialsValidation);
+    }
+    Ok(())
+}
 
-    let hash = hash256(&all_bytes);
+pub(crate) fn ecp2_challenge<B: PairingBackend>(
+    suite: Ciphersuite,
+    y: &B::G2,
+    g: &B::G2,
+    gr: &B::G2,
+) -> B::Scalar {
+    let mut transcript = Transcript::<B>::new(suite, PROTOCOL_ISSUER_KEY);
+    transcript.append_ecp2(b"y", y);
+    transcript.append_ecp2(b"g", g);
+    transcript.append_ecp2(b"gr", gr);
+    transcript.challenge()
+}
 
-    let mut c = BIG::frombytes(&hash);
-    c.rmod(&CURVE_ORDER_BIG);
-    c
+/// Proof of knowledge of x for G2 ** x, as (c, s).
+pub(crate) fn make_ecp2_proof<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    x: &B::Scalar,
+) -> (B::Scalar, B::Scalar) {
+    let r = B::scalar_random(rng);
+
+    let g2 = B::g2_generator();
+    let y = B::g2_mul(&g2, x);
+    let gr = B::g2_mul(&g2, &r);
+    let c = ecp2_challenge::<B>(suite, &y, &g2, &gr);
+    let s = B::scalar_add(&B::scalar_mul(&c, x), &r);
+    (c, s)
 }
 
-fn verify_ecp2_proof(y: &ECP2, c: &BIG, s: &BIG) -> bool {
-    let cn = BIG::modneg(c, &CURVE_ORDER_BIG);
+/// Group public key for the secret (x, y), with its proofs.
+pub(crate) fn make_group_public_key<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    x: &B::Scalar,
+    y: &B::Scalar,
+) -> GroupPublicKey<B> {
+    let (cx, sx) = make_ecp2_proof::<B>(rng, suite, x);
+    let (cy, sy) = make_ecp2_proof::<B>(rng, suite, y);
+
+    let g2 = B::g2_generator();
+    GroupPublicKey {
+        x: B::g2_mul(&g2, x),
+        y: B::g2_mul(&g2, y),
+        cx,
+        sx,
+        cy,
+        sy,
+    }
+}
 
-    let mut gs = g2mul(&G2_ECP, s);
-    let yc = g2mul(y, &cn);
+pub(crate) fn verify_ecp2_proof<B: PairingBackend>(
+    suite: Ciphersuite,
+    y: &B::G2,
+    c: &B::Scalar,
+    s: &B::Scalar,
+) -> bool {
+    let cn = B::scalar_neg(c);
 
-    gs.add(&yc);
+    let g2 = B::g2_generator();
+    let gs = B::g2_add(&B::g2_mul(&g2, s), &B::g2_mul(y, &cn));
 
-    let cc = ecp2_challenge(y, &G2_ECP, &gs);
+    let cc = ecp2_challenge::<B>(suite, y, &g2, &gs);
 
-    BIG::comp(c, &cc) == 0
+    B::scalar_equals(c, &cc)
 }
 
-fn verify_group_public_key(key: &GroupPublicKey) -> Result<()> {
-    match verify_ecp2_proof(&key.x, &key.cx, &key.sx) && verify_ecp2_proof(&key.y, &key.cy, &key.sy)
+pub(crate) fn verify_group_public_key<B: PairingBackend>(
+    suite: Ciphersuite,
+    key: &GroupPublicKey<B>,
+) -> Result<()> {
+    match verify_ecp2_proof::<B>(suite, &key.x, &key.cx, &key.sx)
+        && verify_ecp2_proof::<B>(suite, &key.y, &key.cy, &key.sy)
     {
         true => Ok(()),
         false => Err(CredentialError::BadGroupPublicKey),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    // Malicious join responses and group public keys, each of which
+    // `finish_join` must reject with a specific error.
+
+    use super::*;
+    use crate::backend::BIG_SIZE;
+    use crate::ciphersuite::Curve;
+    use crate::issuer::{Fixture, Issuer};
+    use crate::Bn254 as Backend;
+    use crate::{GroupPublicKey, JoinResponse, UserCredentials, ECP2_COMPAT_SIZE};
+
+    impl Fixture {
+        fn finish_with_key(&mut self, public_key: &GroupPublicKey) -> Result<UserCredentials> {
+            let response = self.response();
+            finish_join(self.suite, public_key, &self.gsk, response)
+        }
+    }
+
+    fn for_each_suite(test: impl Fn(Fixture)) {
+        for suite in Curve::Bn254.suites() {
+            test(Fixture::new(suite));
+        }
+    }
+
+    fn one() -> <Backend as PairingBackend>::Scalar {
+        let mut bytes = [0u8; BIG_SIZE];
+        bytes[BIG_SIZE - 1] = 1;
+        Backend::scalar_from_bytes(&bytes)
+    }
+
+    fn assert_join_rejected(result: Result<UserCredentials>) {
+        assert!(matches!(
+            result,
+            Err(CredentialError::JoinResponseValidation)
+        ));
+    }
+
+    fn assert_key_rejected(result: Result<UserCredentials>) {
+        assert!(matches!(result, Err(CredentialError::BadGroupPublicKey)));
+    }
+
+    #[test]
+    fn test_honest_join_response_is_accepted() {
+        for_each_suite(|mut fixture| {
+            let response = fixture.response();
+            assert!(fixture.finish(response).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_swapped_credentials() {
+        for_each_suite(|mut fixture| {
+            let mut response = fixture.response();
+            core::mem::swap(&mut response.cred.a, &mut response.cred.c);
+            assert_join_rejected(fixture.finish(response));
+
+            // B and D are covered by the proof of equality
+            let mut response = fixture.response();
+            core::mem::swap(&mut response.cred.b, &mut response.cred.d);
+            assert_join_rejected(fixture.finish(response));
+
+            // Credentials from another response, with this response's proof
+            let mut response = fixture.response();
+            response.cred = fixture.response().cred;
+            assert_join_rejected(fixture.finish(response));
+        });
+    }
+
+    #[test]
+    fn test_wrong_proof() {
+        for_each_suite(|mut fixture| {
+            let mut response = fixture.response();
+            response.proof.c = Backend::scalar_add(&response.proof.c, &one());
+            assert_join_rejected(fixture.finish(response));
+
+            let mut response = fixture.response();
+            response.proof.s = Backend::scalar_add(&response.proof.s, &one());
+            assert_join_rejected(fixture.finish(response));
+
+            let mut response = fixture.response();
+            core::mem::swap(&mut response.proof.c, &mut response.proof.s);
+            assert_join_rejected(fixture.finish(response));
+        });
+    }
+
+    #[test]
+    fn test_credentials_for_another_gsk() {
+        for_each_suite(|mut fixture| {
+            let other = start_join(&mut fixture.rng, fixture.suite, b"challenge");
+            let response = fixture
+                .issuer
+                .join(&mut fixture.rng, b"challenge", &other.join_msg)
+                .unwrap();
+            assert_join_rejected(fixture.finish(response));
+        });
+    }
+
+    #[test]
+    fn test_credentials_from_another_issuer() {
+        for_each_suite(|mut fixture| {
+            let other_issuer = Issuer::new(&mut fixture.rng, fixture.suite);
+            let response = other_issuer
+                .join(&mut fixture.rng, b"challenge", &fixture.join_msg)
+                .unwrap();
+            assert_join_rejected(fixture.finish(response));
+        });
+    }
+
+    #[test]
+    fn test_infinity_credentials() {
+        for_each_suite(|mut fixture| {
+            // With r = 0 every credential point is infinity, which satisfies
+            // the proof and every pairing equation; only the explicit check on
+            // A rejects it
+            let zero = Backend::scalar_from_bytes(&[0u8; BIG_SIZE]);
+            let response = fixture
+                .issuer
+                .issue(&mut fixture.rng, &fixture.join_msg.q, &zero);
+            assert!(Backend::g1_is_infinity(&response.cred.c));
+            assert_join_rejected(fixture.finish(response));
+
+            let mut response = fixture.response();
+            response.cred.a = Backend::g1_mul(&response.cred.a, &zero);
+            assert_join_rejected(fixture.finish(response));
+        });
+    }
+
+    #[test]
+    fn test_zero_gsk_join_request() {
+        for_each_suite(|mut fixture| {
+            // Q = G1 ** 0 is at infinity, and its proof checks out
+            let zero = Backend::scalar_from_bytes(&[0u8; BIG_SIZE]);
+            let q = Backend::g1_infinity();
+            let challenge = fixture.suite.hash(b"challenge");
+            let proof =
+                make_ecp_proof::<Backend>(&mut fixture.rng, fixture.suite, &q, &zero, &challenge);
+            let req = JoinRequest { q, proof };
+            assert!(!join_request_is_valid(fixture.suite, b"challenge", &req));
+            assert!(fixture
+                .issuer
+                .join(&mut fixture.rng, b"challenge", &req)
+                .is_none());
+        });
+    }
+
+    #[test]
+    fn test_forged_key_proofs() {
+        for_each_suite(|mut fixture| {
+            let honest_key = fixture.issuer.public_key().clone();
+
+            let mut key = honest_key.clone();
+            key.cx = Backend::scalar_add(&key.cx, &one());
+            assert_key_rejected(fixture.finish_with_key(&key));
+
+            let mut key = honest_key.clone();
+            key.sx = Backend::scalar_add(&key.sx, &one());
+            assert_key_rejected(fixture.finish_with_key(&key));
+
+            let mut key = honest_key.clone();
+            key.sy = Backend::scalar_random(&mut fixture.rng);
+            assert_key_rejected(fixture.finish_with_key(&key));
+
+            // Y's proof presented for X
+            let mut key = honest_key.clone();
+            key.cx = key.cy;
+            key.sx = key.sy;
+            assert_key_rejected(fixture.finish_with_key(&key));
+
+            // A key the issuer does not know the discrete log of, with the
+            // proofs of the honest key
+            let mut key = honest_key.clone();
+            key.x = Backend::g2_add(&key.x, &Backend::g2_generator());
+            assert_key_rejected(fixture.finish_with_key(&key));
+        });
+    }
+
+    #[test]
+    fn test_key_with_invalid_points() {
+        for_each_suite(|mut fixture| {
+            let mut bytes = fixture.issuer.public_key().to_bytes();
+            bytes[..ECP2_COMPAT_SIZE].fill(0);
+            let key: GroupPublicKey = bytes.as_slice().try_into().unwrap();
+            assert_key_rejected(fixture.finish_with_key(&key));
+
+            let mut bytes = fixture.issuer.public_key().to_bytes();
+            bytes[ECP2_COMPAT_SIZE + 1] ^= 1;
+            let key: GroupPublicKey = bytes.as_slice().try_into().unwrap();
+            assert_key_rejected(fixture.finish_with_key(&key));
+        });
+    }
+
+    // Needs two suites of the same curve
+    #[test]
+    fn test_key_for_another_ciphersuite() {
+        let mut legacy = Fixture::new(Ciphersuite::Bn254Sha256Legacy);
+        let v1 = Fixture::new(Ciphersuite::Bn254Sha256V1);
+        assert_key_rejected(legacy.finish_with_key(v1.issuer.public_key()));
+    }
+
+    #[test]
+    fn test_malformed_encodings() {
+        let mut fixture = Fixture::new(Ciphersuite::default());
+        let response = fixture.response().to_bytes();
+        let key = fixture.issuer.public_key().to_bytes();
+
+        assert!(matches!(
+            JoinResponse::try_from(&response[1..]),
+            Err(CredentialError::BadJoinResponse)
+        ));
+        assert!(matches!(
+            GroupPublicKey::try_from(&key[..key.len() - 1]),
+            Err(CredentialError::GroupPublicKeyLength)
+        ));
+    }
+}
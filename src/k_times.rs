@@ -0,0 +1,491 @@
+//! k-times anonymous authentication: up to k unlinkable signatures per
+//! context and epoch.
+//!
+//! Counter i < k selects the basename H(context) || epoch || i, hashed to
+//! H_i under the "k-times" domain tag so that k-times nyms never equal the
+//! nym of a plain signature on the same basename, and the signature carries
+//! nym = H_i ** gsk as usual. Instead of revealing i, the
+//! signer proves that log_B(D) = log_H_i(nym) for one of the k basenames,
+//! with a k-way OR proof whose branch challenges sum to the signature's
+//! challenge. A verifier that rejects repeated nyms within a context and
+//! epoch therefore accepts at most k signatures per member.
+//!
+//! Both k and the counter are `u8`s, so a context and epoch allow at most
+//! 255 signatures, which also bounds the size of the OR proof.
+
+use alloc::vec::Vec;
+use brave_miracl::rand::RAND;
+
+use super::backend::{PairingBackend, BIG_SIZE};
+use super::ciphersuite::Ciphersuite;
+use super::data::{
+    big_from_bytes, ecp_from_bytes, impl_fixed_size, push_ecp, CredentialBIG, ECPProof,
+    GroupPublicKey, UserCredentials, ECP_PROOF_SIZE,
+};
+use super::sign::message_hash;
+use super::transcript::{Transcript, PROTOCOL_SIGN_K_TIMES};
+use super::verify::{member_points_are_valid, randomized_credentials_are_valid};
+use super::{CredentialError, Result};
+
+const K_TIMES_TAG: &[u8] = b"k-times";
+const K_TIMES_BASENAME_SIZE: usize = BIG_SIZE + 9;
+
+pub struct KTimesSignature<B: PairingBackend> {
+    pub(crate) a: B::G1,
+    pub(crate) b: B::G1,
+    pub(crate) c: B::G1,
+    pub(crate) d: B::G1,
+    pub(crate) nym: B::G1,
+
+    // One (c_i, s_i) per counter value
+    pub(crate) proofs: Vec<ECPProof<B>>,
+}
+
+/// Basename of counter `i` within `context` and `epoch`.
+pub(crate) fn k_times_basename(
+    suite: Ciphersuite,
+    context: &[u8],
+    epoch: u64,
+    counter: u8,
+) -> [u8; K_TIMES_BASENAME_SIZE] {
+    let mut bsn = [0u8; K_TIMES_BASENAME_SIZE];
+    bsn[..BIG_SIZE].copy_from_slice(&suite.hash(context));
+    bsn[BIG_SIZE..BIG_SIZE + 8].copy_from_slice(&epoch.to_be_bytes());
+    bsn[BIG_SIZE + 8] = counter;
+    bsn
+}
+
+/// Point H_i of counter `i`, hashed from the basename under `K_TIMES_TAG`.
+fn k_times_point<B: PairingBackend>(
+    suite: Ciphersuite,
+    context: &[u8],
+    epoch: u64,
+    counter: u8,
+) -> B::G1 {
+    let mut data = [0u8; K_TIMES_TAG.len() + K_TIMES_BASENAME_SIZE];
+    data[..K_TIMES_TAG.len()].copy_from_slice(K_TIMES_TAG);
+    data[K_TIMES_TAG.len()..].copy_from_slice(&k_times_basename(suite, context, epoch, counter));
+    suite.hash_to_g1::<B>(&data)
+}
+
+fn basename_points<B: PairingBackend>(
+    suite: Ciphersuite,
+    context: &[u8],
+    epoch: u64,
+    k: u8,
+) -> Vec<B::G1> {
+    (0..k)
+        .map(|i| k_times_point::<B>(suite, context, epoch, i))
+        .collect()
+}
+
+fn k_times_challenge<B: PairingBackend>(
+    suite: Ciphersuite,
+    msg: &[u8],
+    context: &[u8],
+    epoch: u64,
+    signature: &KTimesSignature<B>,
+    commitments: &[(B::G1, B::G1)],
+) -> B::Scalar {
+    let mut transcript = Transcript::<B>::labeled(suite, PROTOCOL_SIGN_K_TIMES);
+    transcript.append_bytes(b"message", &message_hash(suite, msg, context));
+    let mut statement = [0u8; 9];
+    statement[..8].copy_from_slice(&epoch.to_be_bytes());
+    statement[8] = commitments.len() as u8;
+    transcript.append_bytes(b"epoch", &statement);
+    for (label, point) in [
+        (&b"a"[..], &signature.a),
+        (b"b", &signature.b),
+        (b"c", &signature.c),
+        (b"d", &signature.d),
+        (b"nym", &signature.nym),
+    ] {
+        transcript.append_ecp(label, point);
+    }
+    for (bsn_r, br) in commitments {
+        transcript.append_ecp(b"bsn_r", bsn_r);
+        transcript.append_ecp(b"br", br);
+    }
+    transcript.challenge()
+}
+
+/// s * p - c * q
+fn schnorr_commitment<B: PairingBackend>(
+    p: &B::G1,
+    s: &B::Scalar,
+    c: &B::Scalar,
+    q: &B::G1,
+) -> B::G1 {
+    B::g1_add(&B::g1_mul(p, s), &B::g1_mul(q, &B::scalar_neg(c)))
+}
+
+/// Signs with the `counter`th of `k` basenames of `context` in `epoch`,
+/// without revealing `counter`. Callers must use each counter at most once
+/// per epoch, as repeating one links the signatures. `k` is capped at
+/// `u8::MAX`.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_k_times<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    gsk: &CredentialBIG<B>,
+    credentials: &UserCredentials<B>,
+    msg: &[u8],
+    context: &[u8],
+    epoch: u64,
+    counter: u8,
+    k: u8,
+) -> Result<KTimesSignature<B>> {
+    if counter >= k {
+        return Err(CredentialError::CounterOutOfRange);
+    }
+
+    let r = B::scalar_random(rng);
+    let bsn_points = basename_points::<B>(suite, context, epoch, k);
+    let mut signature = KTimesSignature {
+        a: B::g1_mul(&credentials.a, &r),
+        b: B::g1_mul(&credentials.b, &r),
+        c: B::g1_mul(&credentials.c, &r),
+        d: B::g1_mul(&credentials.d, &r),
+        nym: B::g1_mul(&bsn_points[counter as usize], &gsk.0),
+        proofs: Vec::with_capacity(k as usize),
+    };
+
+    // The real branch commits with a nonce, the others are simulated from
+    // random challenges and responses
+    let nonce = B::scalar_random(rng);
+    let simulated: Vec<_> = (0..k)
+        .map(|_| ECPProof {
+            c: B::scalar_random(rng),
+            s: B::scalar_random(rng),
+        })
+        .collect();
+    let commitments: Vec<_> = bsn_points
+        .iter()
+        .zip(&simulated)
+        .enumerate()
+        .map(|(i, (bsn_point, proof))| match i == counter as usize {
+            true => (
+                B::g1_mul(bsn_point, &nonce),
+                B::g1_mul(&signature.b, &nonce),
+            ),
+            false => (
+                schnorr_commitment::<B>(bsn_point, &proof.s, &proof.c, &signature.nym),
+                schnorr_commitment::<B>(&signature.b, &proof.s, &proof.c, &signature.d),
+            ),
+        })
+        .collect();
+
+    let c = k_times_challenge::<B>(suite, msg, context, epoch, &signature, &commitments);
+    let c_real = simulated
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != counter as usize)
+        .fold(c, |c, (_, proof)| {
+            B::scalar_add(&c, &B::scalar_neg(&proof.c))
+        });
+    signature.proofs = simulated;
+    signature.proofs[counter as usize] = ECPProof {
+        c: c_real,
+        s: B::scalar_add(&B::scalar_mul(&c_real, &gsk.0), &nonce),
+    };
+
+    Ok(signature)
+}
+
+/// Verifies a signature made with `sign_k_times` for one of `k` counters.
+/// Collectors enforce the limit by accepting each `nym` once per context
+/// and epoch.
+pub fn verify_k_times<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &GroupPublicKey<B>,
+    signature: &KTimesSignature<B>,
+    msg: &[u8],
+    context: &[u8],
+    epoch: u64,
+    k: u8,
+) -> Result<()> {
+    if k == 0
+        || signature.proofs.len() != k as usize
+        || !randomized_credentials_are_valid::<B>(
+            pub_key,
+            &signature.a,
+            &signature.b,
+            &signature.c,
+            &signature.d,
+        )
+        || !member_points_are_valid::<B>(&signature.d, Some(&signature.nym))
+    {
+        return Err(CredentialError::SignatureValidation);
+    }
+
+    let bsn_points = basename_points::<B>(suite, context, epoch, k);
+    let commitments: Vec<_> = bsn_points
+        .iter()
+        .zip(&signature.proofs)
+        .map(|(bsn_point, proof)| {
+            (
+                schnorr_commitment::<B>(bsn_point, &proof.s, &proof.c, &signature.nym),
+                schnorr_commitment::<B>(&signature.b, &proof.s, &proof.c, &signature.d),
+            )
+        })
+        .collect();
+
+    let c = k_times_challenge::<B>(suite, msg, context, epoch, signature, &commitments);
+    let sum = signature
+        .proofs
+        .iter()
+        .skip(1)
+        .fold(signature.proofs[0].c, |sum, proof| {
+            B::scalar_add(&sum, &proof.c)
+        });
+    if !B::scalar_equals(&c, &sum) {
+        return Err(CredentialError::SignatureValidation);
+    }
+
+    Ok(())
+}
+
+impl<B: PairingBackend> KTimesSignature<B> {
+    fn write_nym(&self, bytes: &mut [u8]) {
+        B::g1_to_bytes(&self.nym, bytes);
+    }
+
+    /// Number of counters k the signature was made for.
+    pub fn k(&self) -> u8 {
+        self.proofs.len() as u8
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(B::G1_SIZE * 5 + 1 + self.proofs.len() * ECP_PROOF_SIZE);
+        for point in [&self.a, &self.b, &self.c, &self.d, &self.nym] {
+            push_ecp::<B>(&mut out, point);
+        }
+        out.push(self.proofs.len() as u8);
+        for proof in &self.proofs {
+            out.extend_from_slice(&proof.to_bytes());
+        }
+        out
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for KTimesSignature<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        let header = B::G1_SIZE * 5 + 1;
+        if bytes.len() < header
+            || bytes[header - 1] == 0
+            || bytes.len() != header + bytes[header - 1] as usize * ECP_PROOF_SIZE
+        {
+            return Err(CredentialError::BadKTimesSignature);
+        }
+        let point = |i: usize| ecp_from_bytes::<B>(&bytes[B::G1_SIZE * i..B::G1_SIZE * (i + 1)]);
+
+        Ok(Self {
+            a: point(0)?,
+            b: point(1)?,
+            c: point(2)?,
+            d: point(3)?,
+            nym: point(4)?,
+            proofs: bytes[header..]
+                .chunks(ECP_PROOF_SIZE)
+                .map(|chunk| {
+                    Ok(ECPProof {
+                        c: big_from_bytes::<B>(&chunk[..BIG_SIZE])?,
+                        s: big_from_bytes::<B>(&chunk[BIG_SIZE..])?,
+                    })
+                })
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl_fixed_size! {
+    /// Pseudonym for the context, epoch and counter, which collectors use
+    /// to detect reuse of a counter.
+    KTimesSignature::nym(write_nym) -> ECP_SIZE;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ciphersuite::Curve;
+    use crate::issuer::Fixture;
+    use crate::sign::sign;
+    use crate::verify::verify;
+    use crate::Bn254 as Backend;
+    use crate::{KTimesSignature, ECP_SIZE};
+
+    const MSG: &[u8] = b"message";
+    const CONTEXT: &[u8] = b"submit-query";
+    const EPOCH: u64 = 20_000;
+
+    impl Fixture {
+        fn sign(&mut self, counter: u8, k: u8) -> Result<KTimesSignature> {
+            sign_k_times(
+                &mut self.rng,
+                self.suite,
+                &self.gsk,
+                &self.credentials,
+                MSG,
+                CONTEXT,
+                EPOCH,
+                counter,
+                k,
+            )
+        }
+
+        fn verify(&self, signature: &KTimesSignature, epoch: u64, k: u8) -> Result<()> {
+            verify_k_times(
+                self.suite,
+                self.issuer.public_key(),
+                signature,
+                MSG,
+                CONTEXT,
+                epoch,
+                k,
+            )
+        }
+    }
+
+    #[test]
+    fn test_k_times_sign_verify() {
+        for suite in Curve::Bn254.suites() {
+            let mut fixture = Fixture::new(suite);
+            let k = 4;
+
+            let nyms: Vec<_> = (0..k)
+                .map(|counter| {
+                    let signature = fixture.sign(counter, k).unwrap();
+                    assert!(fixture.verify(&signature, EPOCH, k).is_ok());
+                    assert_eq!(signature.k(), k);
+                    signature.nym()
+                })
+                .collect();
+
+            // Every counter gives a fresh nym, and reusing one repeats it
+            for (i, nym) in nyms.iter().enumerate() {
+                assert!(nyms[i + 1..].iter().all(|other| other != nym));
+            }
+            assert_eq!(fixture.sign(2, k).unwrap().nym(), nyms[2]);
+
+            // The domain tag keeps the nym apart from the one of a plain
+            // signature on the counter's basename
+            let bsn = k_times_basename(suite, CONTEXT, EPOCH, 2);
+            let plain = sign(
+                &mut fixture.rng,
+                suite,
+                &fixture.gsk,
+                &fixture.credentials,
+                MSG,
+                &bsn,
+            );
+            assert!(verify(suite, fixture.issuer.public_key(), &plain, MSG, bsn).is_ok());
+            assert_ne!(plain.nym(), nyms[2]);
+        }
+    }
+
+    #[test]
+    fn test_new_epoch_gives_new_nyms() {
+        let mut fixture = Fixture::new(Ciphersuite::default());
+        let signature = fixture.sign(0, 1).unwrap();
+        let next = sign_k_times(
+            &mut fixture.rng,
+            fixture.suite,
+            &fixture.gsk,
+            &fixture.credentials,
+            MSG,
+            CONTEXT,
+            EPOCH + 1,
+            0,
+            1,
+        )
+        .unwrap();
+        assert!(fixture.verify(&next, EPOCH + 1, 1).is_ok());
+        assert_ne!(signature.nym(), next.nym());
+    }
+
+    #[test]
+    fn test_counter_out_of_range() {
+        let mut fixture = Fixture::new(Ciphersuite::default());
+        for (counter, k) in [(3, 3), (0, 0), (u8::MAX, 1)] {
+            assert!(matches!(
+                fixture.sign(counter, k),
+                Err(CredentialError::CounterOutOfRange)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_wrong_statement_is_rejected() {
+        let mut fixture = Fixture::new(Ciphersuite::default());
+        let signature = fixture.sign(1, 3).unwrap();
+
+        for (epoch, k) in [(EPOCH + 1, 3), (EPOCH, 2), (EPOCH, 4), (EPOCH, 0)] {
+            assert!(matches!(
+                fixture.verify(&signature, epoch, k),
+                Err(CredentialError::SignatureValidation)
+            ));
+        }
+        assert!(matches!(
+            verify_k_times(
+                fixture.suite,
+                fixture.issuer.public_key(),
+                &signature,
+                MSG,
+                b"other context",
+                EPOCH,
+                3,
+            ),
+            Err(CredentialError::SignatureValidation)
+        ));
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let mut fixture = Fixture::new(Ciphersuite::default());
+
+        let mut signature = fixture.sign(1, 3).unwrap();
+        signature.proofs.swap(0, 1);
+        assert!(fixture.verify(&signature, EPOCH, 3).is_err());
+
+        // A nym outside of the k basenames
+        let mut signature = fixture.sign(1, 3).unwrap();
+        let outside = counter_point(fixture.suite, 3);
+        signature.nym = Backend::g1_mul(&outside, &fixture.gsk.0);
+        assert!(fixture.verify(&signature, EPOCH, 3).is_err());
+
+        // Credentials from another signature
+        let mut signature = fixture.sign(1, 3).unwrap();
+        signature.a = fixture.sign(1, 3).unwrap().a;
+        assert!(fixture.verify(&signature, EPOCH, 3).is_err());
+    }
+
+    fn counter_point(suite: Ciphersuite, counter: u8) -> <Backend as PairingBackend>::G1 {
+        k_times_point::<Backend>(suite, CONTEXT, EPOCH, counter)
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut fixture = Fixture::new(Ciphersuite::default());
+        let signature = fixture.sign(2, 5).unwrap();
+
+        let bytes = signature.to_bytes();
+        let decoded = KTimesSignature::try_from(&bytes[..]).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(fixture.verify(&decoded, EPOCH, 5).is_ok());
+
+        let mut no_proofs = bytes[..ECP_SIZE * 5 + 1].to_vec();
+        no_proofs[ECP_SIZE * 5] = 0;
+        for malformed in [
+            &bytes[..bytes.len() - 1],
+            &bytes[..ECP_SIZE * 5],
+            &no_proofs,
+        ] {
+            assert!(matches!(
+                KTimesSignature::try_from(malformed),
+                Err(CredentialError::BadKTimesSignature)
+            ));
+        }
+    }
+}
@@ -0,0 +1,610 @@
+//! Append-only Merkle log of group public keys, which lets auditors watch
+//! which keys an issuer uses.
+//!
+//! The tree is the one of RFC 9162: leaves hash to H(0x00 || entry) and
+//! inner nodes to H(0x01 || left || right), with SHA-256. An entry is
+//! either a group public key or the number of credentials issued under a
+//! group in an epoch. Inclusion proofs show that an entry is in the log at
+//! a checkpoint, and consistency proofs that a later checkpoint only
+//! appended to an earlier one. Checkpoints are not signed: clients obtain
+//! them from auditors, who compare them with each other.
+
+use alloc::vec::Vec;
+use brave_miracl::hash256::HASH256;
+
+use super::backend::PairingBackend;
+use super::data::{push_encoded, EncodingSizes, GroupKeyId, GroupPublicKey, GROUP_KEY_ID_SIZE};
+use super::{CredentialError, Result};
+
+pub const KEY_LOG_HASH_SIZE: usize = 32;
+pub const KEY_LOG_CHECKPOINT_SIZE: usize = 8 + KEY_LOG_HASH_SIZE;
+
+const ENTRY_GROUP_KEY: u8 = 0;
+const ENTRY_ISSUANCE_COUNT: u8 = 1;
+const ISSUANCE_COUNT_SIZE: usize = 1 + GROUP_KEY_ID_SIZE + 16;
+// Enough for a tree of 2^64 leaves
+const MAX_PATH_LEN: usize = 64;
+
+type Hash = [u8; KEY_LOG_HASH_SIZE];
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+pub enum KeyLogEntry<B: PairingBackend> {
+    GroupKey(GroupPublicKey<B>),
+    IssuanceCount {
+        group: GroupKeyId,
+        epoch: u64,
+        count: u64,
+    },
+}
+
+/// Size and root hash of the log at some point in time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyLogCheckpoint {
+    pub size: u64,
+    pub root: [u8; KEY_LOG_HASH_SIZE],
+}
+
+/// Proof that the entry at `index` is in the log of `size` entries.
+pub struct InclusionProof {
+    index: u64,
+    size: u64,
+    path: Vec<Hash>,
+}
+
+/// Proof that the log of `new_size` entries extends the one of `old_size`.
+pub struct ConsistencyProof {
+    old_size: u64,
+    new_size: u64,
+    path: Vec<Hash>,
+}
+
+#[derive(Default)]
+pub struct KeyLog<B: PairingBackend> {
+    entries: Vec<KeyLogEntry<B>>,
+    leaves: Vec<Hash>,
+}
+
+fn leaf_hash(entry: &[u8]) -> Hash {
+    let mut hash = HASH256::new();
+    hash.process_array(&[0]);
+    hash.process_array(entry);
+    hash.hash()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hash = HASH256::new();
+    hash.process_array(&[1]);
+    hash.process_array(left);
+    hash.process_array(right);
+    hash.hash()
+}
+
+/// Largest power of two smaller than `n`, for n > 1.
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+fn root(leaves: &[Hash]) -> Hash {
+    match leaves.len() {
+        0 => HASH256::new().hash(),
+        1 => leaves[0],
+        n => {
+            let k = split(n);
+            node_hash(&root(&leaves[..k]), &root(&leaves[k..]))
+        }
+    }
+}
+
+fn inclusion_path(index: usize, leaves: &[Hash], path: &mut Vec<Hash>) {
+    if leaves.len() <= 1 {
+        return;
+    }
+    let k = split(leaves.len());
+    if index < k {
+        inclusion_path(index, &leaves[..k], path);
+        path.push(root(&leaves[k..]));
+    } else {
+        inclusion_path(index - k, &leaves[k..], path);
+        path.push(root(&leaves[..k]));
+    }
+}
+
+/// SUBPROOF of RFC 9162, where `complete` says whether the old tree is a
+/// subtree of `leaves` whose root the verifier already knows.
+fn consistency_path(old_size: usize, leaves: &[Hash], complete: bool, path: &mut Vec<Hash>) {
+    if old_size == leaves.len() {
+        if !complete {
+            path.push(root(leaves));
+        }
+        return;
+    }
+    let k = split(leaves.len());
+    if old_size <= k {
+        consistency_path(old_size, &leaves[..k], complete, path);
+        path.push(root(&leaves[k..]));
+    } else {
+        consistency_path(old_size - k, &leaves[k..], false, path);
+        path.push(root(&leaves[..k]));
+    }
+}
+
+/// Shifts `a` and `b` right until the lowest bit of `a` is set or `a` is
+/// zero.
+fn shift_while_even(a: &mut u64, b: &mut u64) {
+    while *a & 1 == 0 && *a != 0 {
+        *a >>= 1;
+        *b >>= 1;
+    }
+}
+
+fn encode_proof(first: u64, second: u64, path: &[Hash]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(16 + path.len() * KEY_LOG_HASH_SIZE);
+    out.extend_from_slice(&first.to_be_bytes());
+    out.extend_from_slice(&second.to_be_bytes());
+    for hash in path {
+        out.extend_from_slice(hash);
+    }
+    out
+}
+
+fn decode_proof(bytes: &[u8]) -> Result<(u64, u64, Vec<Hash>)> {
+    if bytes.len() < 16 {
+        return Err(CredentialError::BadKeyLogMessage);
+    }
+    let path = bytes[16..].chunks_exact(KEY_LOG_HASH_SIZE);
+    if !path.remainder().is_empty() || path.len() > MAX_PATH_LEN {
+        return Err(CredentialError::BadKeyLogMessage);
+    }
+    Ok((
+        u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+        u64::from_be_bytes(bytes[8..16].try_into().unwrap()),
+        path.map(|chunk| chunk.try_into().unwrap()).collect(),
+    ))
+}
+
+impl<B: PairingBackend> KeyLogEntry<B> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::GroupKey(key) => {
+                let mut out = Vec::with_capacity(1 + B::GROUP_PUBLIC_KEY_SIZE);
+                out.push(ENTRY_GROUP_KEY);
+                push_encoded(&mut out, B::GROUP_PUBLIC_KEY_SIZE, |bytes| {
+                    key.write_bytes(bytes)
+                });
+                out
+            }
+            Self::IssuanceCount {
+                group,
+                epoch,
+                count,
+            } => {
+                let mut out = Vec::with_capacity(ISSUANCE_COUNT_SIZE);
+                out.push(ENTRY_ISSUANCE_COUNT);
+                out.extend_from_slice(&group.to_bytes());
+                out.extend_from_slice(&epoch.to_be_bytes());
+                out.extend_from_slice(&count.to_be_bytes());
+                out
+            }
+        }
+    }
+
+    /// Hash of the entry as a leaf of the log.
+    pub fn leaf_hash(&self) -> [u8; KEY_LOG_HASH_SIZE] {
+        leaf_hash(&self.to_bytes())
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for KeyLogEntry<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        match bytes.first() {
+            Some(&ENTRY_GROUP_KEY) if bytes.len() == 1 + B::GROUP_PUBLIC_KEY_SIZE => {
+                let key = GroupPublicKey::try_from(&bytes[1..])
+                    .map_err(|_| CredentialError::BadKeyLogMessage)?;
+                Ok(Self::GroupKey(key))
+            }
+            Some(&ENTRY_ISSUANCE_COUNT) if bytes.len() == ISSUANCE_COUNT_SIZE => {
+                let epoch = 1 + GROUP_KEY_ID_SIZE;
+                Ok(Self::IssuanceCount {
+                    group: GroupKeyId(bytes[1..epoch].try_into().unwrap()),
+                    epoch: u64::from_be_bytes(bytes[epoch..epoch + 8].try_into().unwrap()),
+                    count: u64::from_be_bytes(bytes[epoch + 8..].try_into().unwrap()),
+                })
+            }
+            _ => Err(CredentialError::BadKeyLogMessage),
+        }
+    }
+}
+
+impl KeyLogCheckpoint {
+    pub fn to_bytes(&self) -> [u8; KEY_LOG_CHECKPOINT_SIZE] {
+        let mut result = [0u8; KEY_LOG_CHECKPOINT_SIZE];
+        result[..8].copy_from_slice(&self.size.to_be_bytes());
+        result[8..].copy_from_slice(&self.root);
+        result
+    }
+}
+
+impl TryFrom<&[u8]> for KeyLogCheckpoint {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != KEY_LOG_CHECKPOINT_SIZE {
+            return Err(CredentialError::BadKeyLogMessage);
+        }
+        Ok(KeyLogCheckpoint {
+            size: u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            root: bytes[8..].try_into().unwrap(),
+        })
+    }
+}
+
+impl<B: PairingBackend> KeyLog<B> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `entry` and returns its index.
+    pub fn append(&mut self, entry: KeyLogEntry<B>) -> u64 {
+        self.leaves.push(entry.leaf_hash());
+        self.entries.push(entry);
+        self.entries.len() as u64 - 1
+    }
+
+    pub fn len(&self) -> u64 {
+        self.entries.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entry(&self, index: u64) -> Option<&KeyLogEntry<B>> {
+        self.entries.get(usize::try_from(index).ok()?)
+    }
+
+    /// Index of the first entry for `key`.
+    pub fn find_key(&self, key: &GroupPublicKey<B>) -> Option<u64> {
+        let leaf = KeyLogEntry::GroupKey(key.clone()).leaf_hash();
+        let index = self.leaves.iter().position(|hash| *hash == leaf)?;
+        Some(index as u64)
+    }
+
+    pub fn checkpoint(&self) -> KeyLogCheckpoint {
+        KeyLogCheckpoint {
+            size: self.len(),
+            root: root(&self.leaves),
+        }
+    }
+
+    /// Proves that the entry at `index` was in the log when it had `size`
+    /// entries.
+    pub fn inclusion_proof(&self, index: u64, size: u64) -> Result<InclusionProof> {
+        if index >= size || size > self.len() {
+            return Err(CredentialError::KeyLogVerification);
+        }
+        let mut path = Vec::new();
+        inclusion_path(index as usize, &self.leaves[..size as usize], &mut path);
+        Ok(InclusionProof { index, size, path })
+    }
+
+    /// Proves that the log of `new_size` entries extends the one of
+    /// `old_size` entries.
+    pub fn consistency_proof(&self, old_size: u64, new_size: u64) -> Result<ConsistencyProof> {
+        if old_size > new_size || new_size > self.len() {
+            return Err(CredentialError::KeyLogVerification);
+        }
+        let mut path = Vec::new();
+        if old_size > 0 {
+            consistency_path(
+                old_size as usize,
+                &self.leaves[..new_size as usize],
+                true,
+                &mut path,
+            );
+        }
+        Ok(ConsistencyProof {
+            old_size,
+            new_size,
+            path,
+        })
+    }
+}
+
+impl InclusionProof {
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Checks that `entry` is in the log at `checkpoint`, which must have
+    /// the size the proof was made for.
+    pub fn verify<B: PairingBackend>(
+        &self,
+        entry: &KeyLogEntry<B>,
+        checkpoint: &KeyLogCheckpoint,
+    ) -> Result<()> {
+        if self.index >= self.size || self.size != checkpoint.size {
+            return Err(CredentialError::KeyLogVerification);
+        }
+
+        let mut index = self.index;
+        let mut last = self.size - 1;
+        let mut hash = entry.leaf_hash();
+        for sibling in &self.path {
+            if last == 0 {
+                return Err(CredentialError::KeyLogVerification);
+            }
+            if index & 1 == 1 || index == last {
+                hash = node_hash(sibling, &hash);
+                shift_while_even(&mut index, &mut last);
+            } else {
+                hash = node_hash(&hash, sibling);
+            }
+            index >>= 1;
+            last >>= 1;
+        }
+
+        match last == 0 && hash == checkpoint.root {
+            true => Ok(()),
+            false => Err(CredentialError::KeyLogVerification),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_proof(self.index, self.size, &self.path)
+    }
+}
+
+impl TryFrom<&[u8]> for InclusionProof {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        let (index, size, path) = decode_proof(bytes)?;
+        Ok(InclusionProof { index, size, path })
+    }
+}
+
+impl ConsistencyProof {
+    /// Checks that the log at `new` extends the log at `old`.
+    pub fn verify(&self, old: &KeyLogCheckpoint, new: &KeyLogCheckpoint) -> Result<()> {
+        if self.old_size != old.size || self.new_size != new.size || old.size > new.size {
+            return Err(CredentialError::KeyLogVerification);
+        }
+        if old.size == 0 || old.size == new.size {
+            let consistent = self.path.is_empty()
+                && match old.size {
+                    0 => old.root == root(&[]),
+                    _ => old.root == new.root,
+                };
+            return match consistent {
+                true => Ok(()),
+                false => Err(CredentialError::KeyLogVerification),
+            };
+        }
+
+        // The old root starts the path when the old tree is a complete
+        // subtree of the new one
+        let mut path = self.path.iter();
+        let first = match old.size.is_power_of_two() {
+            true => old.root,
+            false => *path.next().ok_or(CredentialError::KeyLogVerification)?,
+        };
+
+        let mut index = old.size - 1;
+        let mut last = new.size - 1;
+        while index & 1 == 1 {
+            index >>= 1;
+            last >>= 1;
+        }
+        let mut old_hash = first;
+        let mut new_hash = first;
+        for hash in path {
+            if last == 0 {
+                return Err(CredentialError::KeyLogVerification);
+            }
+            if index & 1 == 1 || index == last {
+                old_hash = node_hash(hash, &old_hash);
+                new_hash = node_hash(hash, &new_hash);
+                shift_while_even(&mut index, &mut last);
+            } else {
+                new_hash = node_hash(&new_hash, hash);
+            }
+            index >>= 1;
+            last >>= 1;
+        }
+
+        match last == 0 && old_hash == old.root && new_hash == new.root {
+            true => Ok(()),
+            false => Err(CredentialError::KeyLogVerification),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_proof(self.old_size, self.new_size, &self.path)
+    }
+}
+
+impl TryFrom<&[u8]> for ConsistencyProof {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        let (old_size, new_size, path) = decode_proof(bytes)?;
+        Ok(ConsistencyProof {
+            old_size,
+            new_size,
+            path,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ciphersuite::Ciphersuite;
+    use crate::issuer::Issuer;
+    use crate::{KeyLog, KeyLogEntry};
+    use brave_miracl::rand::RAND;
+
+    fn log_with_checkpoints(len: usize) -> (KeyLog, Vec<KeyLogCheckpoint>) {
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let mut log = KeyLog::new();
+        let mut checkpoints = Vec::from([log.checkpoint()]);
+        for i in 0..len {
+            let entry = match i % 3 {
+                0 => KeyLogEntry::GroupKey(
+                    Issuer::new(&mut rng, Ciphersuite::Bn254Sha256V1)
+                        .public_key()
+                        .clone(),
+                ),
+                _ => KeyLogEntry::IssuanceCount {
+                    group: GroupKeyId([i as u8; GROUP_KEY_ID_SIZE]),
+                    epoch: i as u64,
+                    count: 100,
+                },
+            };
+            assert_eq!(log.append(entry), i as u64);
+            checkpoints.push(log.checkpoint());
+        }
+        (log, checkpoints)
+    }
+
+    #[test]
+    fn test_key_log_tree_hash() {
+        // Roots from the RFC 6962 reference implementation's tests
+        let data: [&[u8]; 8] = [
+            b"",
+            b"\x00",
+            b"\x10",
+            b"\x20\x21",
+            b"\x30\x31",
+            b"\x40\x41\x42\x43",
+            b"\x50\x51\x52\x53\x54\x55\x56\x57",
+            b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+        ];
+        let leaves: Vec<_> = data.iter().map(|entry| leaf_hash(entry)).collect();
+        for (size, expected) in [
+            (
+                1,
+                "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+            ),
+            (
+                2,
+                "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+            ),
+            (
+                3,
+                "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+            ),
+            (
+                8,
+                "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+            ),
+        ] {
+            assert_eq!(hex::encode(root(&leaves[..size])), expected);
+        }
+    }
+
+    #[test]
+    fn test_key_log_inclusion() {
+        let (log, checkpoints) = log_with_checkpoints(13);
+        // SHA-256 of the empty string
+        assert_eq!(checkpoints[0].root[..4], [0xe3, 0xb0, 0xc4, 0x42]);
+
+        for size in 1..=log.len() {
+            let checkpoint = &checkpoints[size as usize];
+            for index in 0..size {
+                let entry = log.entry(index).unwrap();
+                let proof = log.inclusion_proof(index, size).unwrap();
+                proof.verify(entry, checkpoint).unwrap();
+
+                let other = log.entry((index + 1) % log.len()).unwrap();
+                assert!(proof.verify(other, checkpoint).is_err());
+                let stale = &checkpoints[size as usize - 1];
+                assert!(proof.verify(entry, stale).is_err());
+            }
+        }
+        assert!(log.inclusion_proof(3, 3).is_err());
+        assert!(log.inclusion_proof(0, 14).is_err());
+
+        let key = match log.entry(9).unwrap() {
+            KeyLogEntry::GroupKey(key) => key.clone(),
+            _ => unreachable!(),
+        };
+        assert_eq!(log.find_key(&key), Some(9));
+    }
+
+    #[test]
+    fn test_key_log_consistency() {
+        let (log, checkpoints) = log_with_checkpoints(13);
+
+        for new_size in 0..=log.len() {
+            let new = &checkpoints[new_size as usize];
+            for old_size in 0..=new_size {
+                let old = &checkpoints[old_size as usize];
+                let proof = log.consistency_proof(old_size, new_size).unwrap();
+                proof.verify(old, new).unwrap();
+
+                // A log that rewrote an entry is caught
+                if old_size > 0 && new_size > old_size {
+                    let mut forked = *old;
+                    forked.root[0] ^= 1;
+                    assert!(proof.verify(&forked, new).is_err());
+                    let mut forked = *new;
+                    forked.root[0] ^= 1;
+                    assert!(proof.verify(old, &forked).is_err());
+                }
+            }
+        }
+        assert!(log.consistency_proof(4, 3).is_err());
+        assert!(log.consistency_proof(3, 14).is_err());
+    }
+
+    #[test]
+    fn test_key_log_encoding() {
+        let (log, checkpoints) = log_with_checkpoints(6);
+
+        for index in 0..log.len() {
+            let bytes = log.entry(index).unwrap().to_bytes();
+            let entry = KeyLogEntry::try_from(bytes.as_slice()).unwrap();
+            assert_eq!(entry.to_bytes(), bytes);
+            assert!(matches!(
+                KeyLogEntry::try_from(&bytes[1..]),
+                Err(CredentialError::BadKeyLogMessage)
+            ));
+        }
+
+        let bytes = checkpoints[6].to_bytes();
+        assert_eq!(
+            KeyLogCheckpoint::try_from(bytes.as_slice()).unwrap(),
+            checkpoints[6]
+        );
+        assert!(matches!(
+            KeyLogCheckpoint::try_from(&bytes[1..]),
+            Err(CredentialError::BadKeyLogMessage)
+        ));
+
+        let bytes = log.inclusion_proof(2, 6).unwrap().to_bytes();
+        let proof = InclusionProof::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(proof.index(), 2);
+        assert_eq!(proof.to_bytes(), bytes);
+        proof
+            .verify(log.entry(2).unwrap(), &checkpoints[6])
+            .unwrap();
+        assert!(matches!(
+            InclusionProof::try_from(&bytes[1..]),
+            Err(CredentialError::BadKeyLogMessage)
+        ));
+
+        let bytes = log.consistency_proof(3, 6).unwrap().to_bytes();
+        let proof = ConsistencyProof::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(proof.to_bytes(), bytes);
+        proof.verify(&checkpoints[3], &checkpoints[6]).unwrap();
+        assert!(matches!(
+            ConsistencyProof::try_from(&bytes[1..]),
+            Err(CredentialError::BadKeyLogMessage)
+        ));
+    }
+}
@@ -1,95 +1,282 @@
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+// Points are only `Clone` for the protocol code, but arkworks points are `Copy`
+#![cfg_attr(
+    any(feature = "arkworks", feature = "bls12-381"),
+    allow(clippy::clone_on_copy)
+)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+mod attributes;
+mod backend;
+#[cfg(feature = "alloc")]
+mod basename;
+#[cfg(feature = "bls12-381")]
+pub mod bls12_381;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod ciphersuite;
 mod data;
+#[cfg(feature = "alloc")]
+mod dkg;
+#[cfg(test)]
+mod issuer;
 mod join;
+#[cfg(feature = "alloc")]
+mod k_times;
+#[cfg(feature = "alloc")]
+mod key_log;
+mod manager;
+mod migrate;
+#[cfg(test)]
+mod proptests;
 mod sign;
+mod streaming;
+#[cfg(test)]
+mod test_vectors;
+#[cfg(feature = "alloc")]
+mod threshold;
+mod transcript;
 mod util;
+mod verify;
+#[cfg(feature = "alloc")]
+mod wallet;
+
+use core::fmt;
 
-use brave_miracl::rand::RAND;
-use rand::{rngs::OsRng, RngCore};
-use thiserror::Error;
+#[cfg(feature = "alloc")]
+pub use self::attributes::{
+    finish_join_with_attributes, sign_with_attribute_ranges, sign_with_attributes,
+    verify_with_attributes, AttributeRange, MAX_ATTRIBUTES, MAX_RANGE_BITS,
+};
+#[cfg(feature = "arkworks")]
+pub use self::backend::Arkworks;
+pub use self::backend::{Bn254, Miracl, PairingBackend, BIG_SIZE};
+#[cfg(feature = "alloc")]
+pub use self::basename::{Basename, Epoch, BASENAME_TAG};
+pub use self::ciphersuite::{Ciphersuite, Curve, HashFunction};
+pub use self::data::{
+    CredentialMetadata, GroupKeyId, CREDENTIAL_METADATA_SIZE, ECP_PROOF_SIZE, GROUP_KEY_ID_SIZE,
+};
+#[cfg(feature = "alloc")]
+pub use self::dkg::DkgComplaint;
+#[cfg(feature = "alloc")]
+pub use self::k_times::{sign_k_times, verify_k_times};
+#[cfg(feature = "alloc")]
+pub use self::key_log::{
+    ConsistencyProof, InclusionProof, KeyLogCheckpoint, KEY_LOG_CHECKPOINT_SIZE, KEY_LOG_HASH_SIZE,
+};
+pub use self::migrate::{make_epoch_group_public_key, verify_migration_request};
+pub use self::streaming::MessageHasher;
+#[cfg(feature = "alloc")]
+pub use self::threshold::{combine_join_response, deal_epoch_issuer_key, deal_issuer_key};
+pub use self::verify::{verify, verify_prehashed, verify_unlinkable};
 
-pub use self::data::*;
-use self::join::{finish_join, start_join};
-use self::sign::sign;
+/// Declares the types of the crate for one backend, as aliases of the types
+/// that are generic over it, and the sizes of their encodings on its curve.
+macro_rules! backend_types {
+    ($backend:ty) => {
+        pub type JoinRequest = $crate::data::JoinRequest<$backend>;
+        pub type JoinResponse = $crate::data::JoinResponse<$backend>;
+        pub type UserCredentials = $crate::data::UserCredentials<$backend>;
+        pub type Signature = $crate::data::Signature<$backend>;
+        pub type UnlinkableSignature = $crate::data::UnlinkableSignature<$backend>;
+        pub type GroupPublicKey = $crate::data::GroupPublicKey<$backend>;
+        pub type EpochGroupPublicKey = $crate::data::EpochGroupPublicKey<$backend>;
+        pub type MigrationRequest = $crate::data::MigrationRequest<$backend>;
+        pub type ECPProof = $crate::data::ECPProof<$backend>;
+        pub type CredentialBIG = $crate::data::CredentialBIG<$backend>;
+        pub type StartJoinResult = $crate::data::StartJoinResult<$backend>;
+        pub type Signer<'a> = $crate::streaming::Signer<'a, $backend>;
+        pub type CredentialManager = $crate::manager::CredentialManager<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type Attribute = $crate::attributes::Attribute<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type AttributeGroupPublicKey = $crate::attributes::AttributeGroupPublicKey<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type AttributeJoinResponse = $crate::attributes::AttributeJoinResponse<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type AttributeCredentials = $crate::attributes::AttributeCredentials<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type AttributeSignature = $crate::attributes::AttributeSignature<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type KTimesSignature = $crate::k_times::KTimesSignature<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type KeyLog = $crate::key_log::KeyLog<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type KeyLogEntry = $crate::key_log::KeyLogEntry<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type IssuerKeyShare = $crate::threshold::IssuerKeyShare<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type JoinCommitment = $crate::threshold::JoinCommitment<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type JoinNonce = $crate::threshold::JoinNonce<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type PartialJoinResponse = $crate::threshold::PartialJoinResponse<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type DkgParty = $crate::dkg::DkgParty<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type DkgCommitments = $crate::dkg::DkgCommitments<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type DkgShare = $crate::dkg::DkgShare<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type DkgProductShare = $crate::dkg::DkgProductShare<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type DkgReshareCommitments = $crate::dkg::DkgReshareCommitments<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type DkgProofCommitment = $crate::dkg::DkgProofCommitment<$backend>;
+        #[cfg(feature = "alloc")]
+        pub type DkgProofResponse = $crate::dkg::DkgProofResponse<$backend>;
 
-#[derive(Error, Debug)]
+        pub const ECP_SIZE: usize = <$backend as $crate::PairingBackend>::G1_SIZE;
+        pub const ECP2_COMPAT_SIZE: usize = <$backend as $crate::PairingBackend>::G2_SIZE;
+        pub const JOIN_REQUEST_SIZE: usize =
+            <$backend as $crate::data::EncodingSizes>::JOIN_REQUEST_SIZE;
+        pub const USER_CREDENTIALS_SIZE: usize =
+            <$backend as $crate::data::EncodingSizes>::USER_CREDENTIALS_SIZE;
+        pub const JOIN_RESPONSE_SIZE: usize =
+            <$backend as $crate::data::EncodingSizes>::JOIN_RESPONSE_SIZE;
+        pub const GROUP_PUBLIC_KEY_SIZE: usize =
+            <$backend as $crate::data::EncodingSizes>::GROUP_PUBLIC_KEY_SIZE;
+        pub const SIGNATURE_SIZE: usize = <$backend as $crate::data::EncodingSizes>::SIGNATURE_SIZE;
+        pub const UNLINKABLE_SIGNATURE_SIZE: usize =
+            <$backend as $crate::data::EncodingSizes>::UNLINKABLE_SIGNATURE_SIZE;
+        pub const MIGRATION_REQUEST_SIZE: usize =
+            <$backend as $crate::data::EncodingSizes>::MIGRATION_REQUEST_SIZE;
+        pub const EPOCH_GROUP_PUBLIC_KEY_SIZE: usize =
+            <$backend as $crate::data::EncodingSizes>::EPOCH_GROUP_PUBLIC_KEY_SIZE;
+        #[cfg(feature = "alloc")]
+        pub const JOIN_COMMITMENT_SIZE: usize =
+            <$backend as $crate::threshold::ThresholdSizes>::JOIN_COMMITMENT_SIZE;
+        #[cfg(feature = "alloc")]
+        pub const PARTIAL_JOIN_RESPONSE_SIZE: usize =
+            <$backend as $crate::threshold::ThresholdSizes>::PARTIAL_JOIN_RESPONSE_SIZE;
+    };
+}
+
+#[cfg(feature = "bls12-381")]
+pub(crate) use backend_types;
+
+backend_types!(Bn254);
+
+#[derive(Debug)]
 pub enum CredentialError {
-    #[error("ECP should be {0} bytes", ECP_SIZE)]
     BadECP,
-    #[error("ECP2 should be {0} bytes", ECP2_COMPAT_SIZE)]
     BadECP2,
-    #[error("BIG should be {0} bytes", BIG_SIZE)]
     BadBIG,
-    #[error("ECP proof should be {0} bytes", ECP_PROOF_SIZE)]
     BadECPProof,
-    #[error("User credentials should be {0} bytes", USER_CREDENTIALS_SIZE)]
     BadUserCredentials,
-    #[error("Join response should be {0} bytes", JOIN_RESPONSE_SIZE)]
     BadJoinResponse,
-    #[error("Group public key should be {0} bytes", GROUP_PUBLIC_KEY_SIZE)]
     GroupPublicKeyLength,
-    #[error("Join response validation failed")]
     JoinResponseValidation,
-    #[error("Private key and/or credentials not set")]
     CredentialsNotSet,
-    #[error("Group public key verification failed")]
     BadGroupPublicKey,
+    UnknownCiphersuite,
+    BadSignature,
+    SignatureValidation,
+    BadJoinRequest,
+    BadAttributes,
+    AttributeOutOfRange,
+    CounterOutOfRange,
+    BadKTimesSignature,
+    BadBasename,
+    BadUnlinkableSignature,
+    CredentialsValidation,
+    BadMigrationRequest,
+    MigrationValidation,
+    BadCredentialMetadata,
+    CredentialsExpired,
+    BadThresholdMessage,
+    ThresholdIssuance,
+    KeyGeneration,
+    BadKeyLogMessage,
+    KeyLogVerification,
 }
 
-pub type Result<T> = std::result::Result<T, CredentialError>;
-
-pub struct CredentialManager {
-    rng: RAND,
-    gsk_and_credentials: Option<(CredentialBIG, UserCredentials)>,
-}
-
-impl CredentialManager {
-    pub fn new() -> Self {
-        let mut entropy = [0u8; 128];
-        OsRng::default().fill_bytes(&mut entropy);
-        Self::new_with_seed(&entropy)
-    }
-
-    pub fn new_with_seed(entropy: &[u8]) -> Self {
-        let mut rng = RAND::new();
-
-        rng.seed(entropy.len(), entropy);
-
-        Self {
-            rng,
-            gsk_and_credentials: None,
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadECP => write!(f, "ECP should be {} bytes", ECP_SIZE),
+            Self::BadECP2 => write!(f, "ECP2 should be {} bytes", ECP2_COMPAT_SIZE),
+            Self::BadBIG => write!(
+                f,
+                "BIG should be {} bytes and below the curve order",
+                BIG_SIZE
+            ),
+            Self::BadECPProof => write!(f, "ECP proof should be {} bytes", ECP_PROOF_SIZE),
+            Self::BadUserCredentials => write!(
+                f,
+                "User credentials should be {} bytes",
+                USER_CREDENTIALS_SIZE
+            ),
+            Self::BadJoinResponse => {
+                write!(f, "Join response should be {} bytes", JOIN_RESPONSE_SIZE)
+            }
+            Self::GroupPublicKeyLength => write!(
+                f,
+                "Group public key should be {} bytes",
+                GROUP_PUBLIC_KEY_SIZE
+            ),
+            Self::JoinResponseValidation => write!(f, "Join response validation failed"),
+            Self::CredentialsNotSet => write!(f, "Private key and/or credentials not set"),
+            Self::BadGroupPublicKey => write!(f, "Group public key verification failed"),
+            Self::UnknownCiphersuite => write!(f, "Unknown ciphersuite"),
+            Self::BadSignature => write!(f, "Signature should be {} bytes", SIGNATURE_SIZE),
+            Self::SignatureValidation => write!(f, "Signature verification failed"),
+            Self::BadJoinRequest => write!(f, "Join request should be {} bytes", JOIN_REQUEST_SIZE),
+            Self::BadAttributes => {
+                write!(
+                    f,
+                    "Attributes are malformed or do not match the group public key"
+                )
+            }
+            Self::AttributeOutOfRange => write!(f, "Attribute is outside of the requested range"),
+            Self::CounterOutOfRange => write!(f, "Counter must be less than the limit k"),
+            Self::BadKTimesSignature => write!(f, "K-times signature is malformed"),
+            Self::BadBasename => write!(f, "Basename field is longer than 4 GiB"),
+            Self::BadUnlinkableSignature => write!(
+                f,
+                "Unlinkable signature should be {} bytes",
+                UNLINKABLE_SIGNATURE_SIZE
+            ),
+            Self::CredentialsValidation => write!(f, "Credentials validation failed"),
+            Self::BadMigrationRequest => write!(
+                f,
+                "Migration request should be {} bytes",
+                MIGRATION_REQUEST_SIZE
+            ),
+            Self::MigrationValidation => write!(f, "Migration request validation failed"),
+            Self::BadCredentialMetadata => write!(
+                f,
+                "Credential metadata should be {} bytes",
+                CREDENTIAL_METADATA_SIZE
+            ),
+            Self::CredentialsExpired => write!(f, "Credentials have expired"),
+            Self::BadThresholdMessage => write!(f, "Threshold issuance message is malformed"),
+            Self::ThresholdIssuance => write!(f, "Threshold issuance failed"),
+            Self::KeyGeneration => write!(f, "Distributed key generation failed"),
+            Self::BadKeyLogMessage => write!(f, "Key log proof or entry is malformed"),
+            Self::KeyLogVerification => write!(f, "Key log verification failed"),
         }
     }
+}
 
-    pub fn start_join(&mut self, challenge: &[u8]) -> StartJoinResult {
-        start_join(&mut self.rng, challenge)
-    }
-
-    pub fn finish_join(
-        &mut self,
-        public_key: &GroupPublicKey,
-        gsk: &CredentialBIG,
-        join_resp: JoinResponse,
-    ) -> Result<UserCredentials> {
-        finish_join(public_key, gsk, join_resp)
-    }
+#[cfg(feature = "std")]
+impl std::error::Error for CredentialError {}
 
-    pub fn set_gsk_and_credentials(&mut self, gsk: CredentialBIG, credentials: UserCredentials) {
-        self.gsk_and_credentials = Some((gsk, credentials));
-    }
-
-    pub fn sign(&mut self, msg: &[u8], basename: &[u8]) -> Result<Signature> {
-        match &self.gsk_and_credentials {
-            Some((gsk, credentials)) => Ok(sign(&mut self.rng, gsk, credentials, msg, basename)),
-            None => Err(CredentialError::CredentialsNotSet),
-        }
-    }
-}
+pub type Result<T> = core::result::Result<T, CredentialError>;
 
 #[cfg(test)]
 mod tests {
+    use super::issuer::Issuer;
     use super::*;
+    use brave_miracl::rand::RAND;
+    use core::sync::atomic::{AtomicU64, Ordering};
     use lazy_static::lazy_static;
 
+    // Outputs of the original C library, which only supports BN254
     lazy_static! {
         static ref EXPECTED_GSK: Vec<u8> =
             hex::decode("0198c86f99ede0ca2ed30b8e4ae6cac831c9b398445422a41d95abb4d3f03499")
@@ -125,6 +312,46 @@ mod tests {
             bd494be6a551768c8",
         )
         .unwrap();
+        static ref JOIN_RESPONSE: Vec<u8> = hex::decode(
+            "04246220e5a9d48d359178c9e0994cc10f7288b50\
+            cd059c24c5a26fc5919682e8017b66ca6185d62bf2\
+            bed7cf02503157ab93ff79d8d34ab3c48669954b7e\
+            2b69c041d98fde59abcd8c0f22790e8d40e253c124\
+            0f3697c161d18a9d04ca24ba2b01f0d100b28b3d52\
+            9939ec717f4f39e114337878f03c9066afc2250332\
+            76f162b4904248822cb548ccb8167480e23f019813\
+            4d1547b005ac84c2a7101a4d39c924ee50298022d7\
+            dd7c9f0006eab2576635a36af81e0f781437c4ee35\
+            b8672511089830401074ad73c4e9e9aed541bdc5a2\
+            df2ee815a3ac4f6297b73da35db2a646e19720475c\
+            fe50eb2465833b50758f6c8f09fdf645643a4b3ef5\
+            bd494be6a551768c81677932196184249f179d319f\
+            eba43b32da42501daa355d3cde30615a08ac687188\
+            a8c6e3b8a330f76c233e900acd6ef31c50796b9192\
+            9cfc16b4fcad40b5309",
+        )
+        .unwrap();
+        static ref EXPECTED_SIGNATURE: Vec<u8> = hex::decode(
+            "0406cb022fcc3dcaef1e4c62dad349bfd263581126c\
+            f17b293d1a41e4d96f840da00ad85e4a97aad1247a19\
+            a425da6f96978fdac180136f0f486bad0fce0a9ada20\
+            401074ad73c4e9e9aed541bdc5a2df2ee815a3ac4f62\
+            97b73da35db2a646e19720475cfe50eb2465833b5075\
+            8f6c8f09fdf645643a4b3ef5bd494be6a551768c8042\
+            4a006154937bcd3b8f94f12a4672d9a9411928846adc\
+            9132737600089a65915121160cbd4e417435e4acfe66\
+            57840c50584bc8dca420544879fe7fe9c03bc0f0418e\
+            e65a71c262c5301d782b20e7f3f252e938282b98a2f8\
+            6a7447e2aa424005819835a0a954d4f6dc53ae4c8bad\
+            2d192a70fcb8883403f69989e43ff66caad0104208cd\
+            8eb5e25486eb754e44f4e2f3b6f5153f5aa73d7eab8c\
+            2f5c867a157276b0463ced7f409f86ef91a4548cf4bb\
+            519392cd657505475e585a3ea0348b6266b1fd3d4ab1\
+            253392386bf2f08afe36072abe575e07865272c3014a\
+            b067f5051181fe574571f34d278e2c9359294ca44aa3\
+            3568c546082e4e8d921541e5ccc6c81",
+        )
+        .unwrap();
     }
 
     const CHALLENGE: &[u8] = b"challenge";
@@ -154,34 +381,26 @@ mod tests {
         assert_eq!(result.join_msg.to_bytes().as_slice(), &expected_join_msg);
     }
 
+    #[test]
+    fn test_join_request_round_trip() {
+        let mut cm = manager_with_fixed_seed();
+        let bytes = cm.start_join(CHALLENGE).join_msg.to_bytes();
+
+        let join_msg: JoinRequest = bytes.as_slice().try_into().unwrap();
+        assert_eq!(join_msg.to_bytes(), bytes);
+        assert!(matches!(
+            JoinRequest::try_from(&bytes[1..]),
+            Err(CredentialError::BadJoinRequest)
+        ));
+    }
+
     #[test]
     fn test_finish_join_credentials() {
         let mut cm = manager_with_fixed_seed();
 
         let group_pub_key: GroupPublicKey = GROUP_PUB_KEY.as_slice().try_into().unwrap();
         let gsk: CredentialBIG = EXPECTED_GSK.as_slice().try_into().unwrap();
-        let join_response: JoinResponse = hex::decode(
-            "04246220e5a9d48d359178c9e0994cc10f7288b50\
-            cd059c24c5a26fc5919682e8017b66ca6185d62bf2\
-            bed7cf02503157ab93ff79d8d34ab3c48669954b7e\
-            2b69c041d98fde59abcd8c0f22790e8d40e253c124\
-            0f3697c161d18a9d04ca24ba2b01f0d100b28b3d52\
-            9939ec717f4f39e114337878f03c9066afc2250332\
-            76f162b4904248822cb548ccb8167480e23f019813\
-            4d1547b005ac84c2a7101a4d39c924ee50298022d7\
-            dd7c9f0006eab2576635a36af81e0f781437c4ee35\
-            b8672511089830401074ad73c4e9e9aed541bdc5a2\
-            df2ee815a3ac4f6297b73da35db2a646e19720475c\
-            fe50eb2465833b50758f6c8f09fdf645643a4b3ef5\
-            bd494be6a551768c81677932196184249f179d319f\
-            eba43b32da42501daa355d3cde30615a08ac687188\
-            a8c6e3b8a330f76c233e900acd6ef31c50796b9192\
-            9cfc16b4fcad40b5309",
-        )
-        .unwrap()
-        .as_slice()
-        .try_into()
-        .unwrap();
+        let join_response: JoinResponse = JOIN_RESPONSE.as_slice().try_into().unwrap();
 
         let credentials = cm
             .finish_join(&group_pub_key, &gsk, join_response)
@@ -199,30 +418,1188 @@ mod tests {
         let credentials = EXPECTED_CREDENTIALS.as_slice().try_into().unwrap();
         cm.set_gsk_and_credentials(gsk, credentials);
 
-        let expected_signature = hex::decode(
-            "0406cb022fcc3dcaef1e4c62dad349bfd263581126c\
-            f17b293d1a41e4d96f840da00ad85e4a97aad1247a19\
-            a425da6f96978fdac180136f0f486bad0fce0a9ada20\
-            401074ad73c4e9e9aed541bdc5a2df2ee815a3ac4f62\
-            97b73da35db2a646e19720475cfe50eb2465833b5075\
-            8f6c8f09fdf645643a4b3ef5bd494be6a551768c8042\
-            4a006154937bcd3b8f94f12a4672d9a9411928846adc\
-            9132737600089a65915121160cbd4e417435e4acfe66\
-            57840c50584bc8dca420544879fe7fe9c03bc0f0418e\
-            e65a71c262c5301d782b20e7f3f252e938282b98a2f8\
-            6a7447e2aa424005819835a0a954d4f6dc53ae4c8bad\
-            2d192a70fcb8883403f69989e43ff66caad0104208cd\
-            8eb5e25486eb754e44f4e2f3b6f5153f5aa73d7eab8c\
-            2f5c867a157276b0463ced7f409f86ef91a4548cf4bb\
-            519392cd657505475e585a3ea0348b6266b1fd3d4ab1\
-            253392386bf2f08afe36072abe575e07865272c3014a\
-            b067f5051181fe574571f34d278e2c9359294ca44aa3\
-            3568c546082e4e8d921541e5ccc6c81",
+        let signature_bytes = cm.sign(b"message", b"basename").unwrap().to_bytes();
+
+        assert_eq!(signature_bytes, EXPECTED_SIGNATURE.as_slice());
+    }
+
+    #[test]
+    fn test_prehashed_and_streaming_signatures() {
+        let gsk: CredentialBIG = EXPECTED_GSK.as_slice().try_into().unwrap();
+        let credentials: UserCredentials = EXPECTED_CREDENTIALS.as_slice().try_into().unwrap();
+        let mut cm = manager_with_fixed_seed();
+        assert!(matches!(
+            cm.signer(),
+            Err(CredentialError::CredentialsNotSet)
+        ));
+        cm.set_gsk_and_credentials(gsk, credentials);
+
+        let suite = cm.ciphersuite();
+        let mut hasher = MessageHasher::new(suite);
+        hasher.update(b"mes");
+        hasher.update(b"");
+        hasher.update(b"sage");
+        let digest = hasher.finalize();
+        let signature = cm.sign_prehashed(&digest, b"basename").unwrap();
+        assert_eq!(signature.to_bytes(), EXPECTED_SIGNATURE.as_slice());
+
+        let mut cm = manager_with_fixed_seed();
+        let gsk = EXPECTED_GSK.as_slice().try_into().unwrap();
+        let credentials = EXPECTED_CREDENTIALS.as_slice().try_into().unwrap();
+        cm.set_gsk_and_credentials(gsk, credentials);
+        let mut signer = cm.signer().unwrap();
+        for chunk in b"message".chunks(3) {
+            signer.update(chunk);
+        }
+        let signature = signer.finalize(b"basename").unwrap();
+        assert_eq!(signature.to_bytes(), EXPECTED_SIGNATURE.as_slice());
+
+        let group_pub_key: GroupPublicKey = GROUP_PUB_KEY.as_slice().try_into().unwrap();
+        verify_prehashed(suite, &group_pub_key, &signature, &digest, b"basename").unwrap();
+        let mut hasher = MessageHasher::new(suite);
+        hasher.update(b"message2");
+        assert!(matches!(
+            verify_prehashed(
+                suite,
+                &group_pub_key,
+                &signature,
+                &hasher.finalize(),
+                b"basename"
+            ),
+            Err(CredentialError::SignatureValidation)
+        ));
+    }
+
+    #[test]
+    fn test_unlinkable_signature() {
+        let mut cm = manager_with_fixed_seed();
+        assert!(matches!(
+            cm.sign_unlinkable(b"message"),
+            Err(CredentialError::CredentialsNotSet)
+        ));
+        let gsk = EXPECTED_GSK.as_slice().try_into().unwrap();
+        let credentials = EXPECTED_CREDENTIALS.as_slice().try_into().unwrap();
+        cm.set_gsk_and_credentials(gsk, credentials);
+
+        let group_pub_key: GroupPublicKey = GROUP_PUB_KEY.as_slice().try_into().unwrap();
+        let suite = cm.ciphersuite();
+        let signature = cm.sign_unlinkable(b"message").unwrap();
+        verify_unlinkable(suite, &group_pub_key, &signature, b"message").unwrap();
+        assert!(matches!(
+            verify_unlinkable(suite, &group_pub_key, &signature, b"message2"),
+            Err(CredentialError::SignatureValidation)
+        ));
+
+        // Every element is rerandomized, so no two signatures share any bytes
+        // an observer could link
+        let other = cm.sign_unlinkable(b"message").unwrap().to_bytes();
+        let bytes = signature.to_bytes();
+        for i in 0..4 {
+            let point = ECP_SIZE * i + 1..ECP_SIZE * (i + 1);
+            assert_ne!(bytes[point.clone()], other[point]);
+        }
+
+        let decoded = UnlinkableSignature::try_from(&bytes[..]).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        verify_unlinkable(suite, &group_pub_key, &decoded, b"message").unwrap();
+        assert!(matches!(
+            UnlinkableSignature::try_from(&bytes[1..]),
+            Err(CredentialError::BadUnlinkableSignature)
+        ));
+
+        // Proofs are not interchangeable between the two formats
+        let linkable = cm.sign(b"message", b"basename").unwrap();
+        let mut forged = bytes;
+        forged[ECP_SIZE * 4..].copy_from_slice(&linkable.to_bytes()[ECP_SIZE * 5..]);
+        let forged = UnlinkableSignature::try_from(&forged[..]).unwrap();
+        assert!(verify_unlinkable(suite, &group_pub_key, &forged, b"message").is_err());
+
+        let mut tampered = bytes;
+        tampered[UNLINKABLE_SIGNATURE_SIZE - 1] ^= 1;
+        let tampered = UnlinkableSignature::try_from(&tampered[..]).unwrap();
+        assert!(verify_unlinkable(suite, &group_pub_key, &tampered, b"message").is_err());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_structured_basename() {
+        let mut cm = manager_with_fixed_seed();
+        let gsk = EXPECTED_GSK.as_slice().try_into().unwrap();
+        let credentials = EXPECTED_CREDENTIALS.as_slice().try_into().unwrap();
+        cm.set_gsk_and_credentials(gsk, credentials);
+
+        let group_pub_key: GroupPublicKey = GROUP_PUB_KEY.as_slice().try_into().unwrap();
+        let suite = cm.ciphersuite();
+        let today = Basename::new("wdp", "query", Epoch::daily(1_709_208_000)).unwrap();
+        let tomorrow = Basename::new("wdp", "query", Epoch::daily(1_709_208_000 + 86_400)).unwrap();
+
+        let signature = cm.sign(b"message", &today).unwrap();
+        verify(suite, &group_pub_key, &signature, b"message", &today).unwrap();
+        assert!(matches!(
+            verify(suite, &group_pub_key, &signature, b"message", &tomorrow),
+            Err(CredentialError::SignatureValidation)
+        ));
+
+        // Same nym as signing the encoding directly, and none across epochs
+        let raw = cm.sign(b"message", today.as_bytes()).unwrap();
+        assert_eq!(signature.nym(), raw.nym());
+        assert_ne!(
+            signature.nym(),
+            cm.sign(b"message", &tomorrow).unwrap().nym()
+        );
+
+        // Attribute signatures take a Basename too, and link the same way
+        let fixture = issuer::Fixture::new_with_attributes(suite, &[Attribute::from(7)]);
+        let key = fixture.issuer.attribute_public_key();
+        cm.set_gsk_and_attribute_credentials(fixture.gsk, fixture.credentials);
+        let signature = cm.sign_with_attributes(b"message", &today, &[0]).unwrap();
+        verify_with_attributes(suite, &key, &signature, b"message", &today).unwrap();
+        assert!(matches!(
+            verify_with_attributes(suite, &key, &signature, b"message", &tomorrow),
+            Err(CredentialError::SignatureValidation)
+        ));
+        let ranged = cm
+            .sign_with_attribute_ranges(b"message", &today, &[], &[])
+            .unwrap();
+        assert_eq!(signature.nym(), ranged.nym());
+    }
+
+    #[test]
+    fn test_v1_join_roundtrip() {
+        let mut cm = manager_with_fixed_seed();
+        cm.set_ciphersuite(Ciphersuite::Bn254Sha256V1).unwrap();
+
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let issuer = Issuer::new(&mut rng, Ciphersuite::Bn254Sha256V1);
+
+        let result = cm.start_join(CHALLENGE);
+        let join_response = issuer.join(&mut rng, CHALLENGE, &result.join_msg).unwrap();
+
+        let credentials = cm
+            .finish_join(issuer.public_key(), &result.gsk, join_response)
+            .unwrap();
+        cm.set_gsk_and_credentials(result.gsk, credentials);
+        assert!(cm.sign(b"message", b"basename").is_ok());
+    }
+
+    #[cfg(feature = "bls12-381")]
+    #[test]
+    fn test_bls12_381_alongside_bn254() {
+        let mut cm = manager_with_fixed_seed();
+        let gsk = EXPECTED_GSK.as_slice().try_into().unwrap();
+        let credentials = EXPECTED_CREDENTIALS.as_slice().try_into().unwrap();
+        cm.set_gsk_and_credentials(gsk, credentials);
+
+        let mut bls_cm = bls12_381::CredentialManager::new_with_seed(&[0]);
+        let suite = bls_cm.ciphersuite();
+        assert_eq!(suite, Ciphersuite::Bls12381Sha256V1);
+        assert!(matches!(
+            bls_cm.set_ciphersuite(Ciphersuite::Bn254Sha256V1),
+            Err(CredentialError::UnknownCiphersuite)
+        ));
+
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let issuer = Issuer::<bls12_381::Bls12381>::new(&mut rng, suite);
+        let result = bls_cm.start_join(CHALLENGE);
+        let join_response = issuer.join(&mut rng, CHALLENGE, &result.join_msg).unwrap();
+        let credentials = bls_cm
+            .finish_join(issuer.public_key(), &result.gsk, join_response)
+            .unwrap();
+        bls_cm.set_gsk_and_credentials(result.gsk, credentials);
+
+        let signature = bls_cm.sign(b"message", b"basename").unwrap();
+        assert_eq!(signature.to_bytes().len(), bls12_381::SIGNATURE_SIZE);
+        verify(
+            suite,
+            issuer.public_key(),
+            &signature,
+            b"message",
+            b"basename",
         )
         .unwrap();
+        assert!(matches!(
+            verify(
+                Ciphersuite::Bn254Sha256V1,
+                issuer.public_key(),
+                &signature,
+                b"message",
+                b"basename"
+            ),
+            Err(CredentialError::UnknownCiphersuite)
+        ));
 
+        // The bn254 manager is unaffected
         let signature_bytes = cm.sign(b"message", b"basename").unwrap().to_bytes();
+        assert_eq!(signature_bytes, EXPECTED_SIGNATURE.as_slice());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_wallet() {
+        let mut cm = manager_with_fixed_seed();
+
+        let legacy_key: GroupPublicKey = GROUP_PUB_KEY.as_slice().try_into().unwrap();
+        cm.add_credentials(
+            &legacy_key,
+            EXPECTED_GSK.as_slice().try_into().unwrap(),
+            EXPECTED_CREDENTIALS.as_slice().try_into().unwrap(),
+        )
+        .unwrap();
+
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let v1_issuer = Issuer::new(&mut rng, Ciphersuite::Bn254Sha256V1);
+        cm.set_ciphersuite(Ciphersuite::Bn254Sha256V1).unwrap();
+        let result = cm.start_join(CHALLENGE);
+        let join_response = v1_issuer
+            .join(&mut rng, CHALLENGE, &result.join_msg)
+            .unwrap();
+        let credentials = cm
+            .finish_join(v1_issuer.public_key(), &result.gsk, join_response)
+            .unwrap();
+        assert!(matches!(
+            cm.add_credentials(
+                v1_issuer.public_key(),
+                EXPECTED_GSK.as_slice().try_into().unwrap(),
+                EXPECTED_CREDENTIALS.as_slice().try_into().unwrap(),
+            ),
+            Err(CredentialError::CredentialsValidation)
+        ));
+        cm.add_credentials(v1_issuer.public_key(), result.gsk, credentials)
+            .unwrap();
+
+        let legacy_id = legacy_key.id();
+        let v1_id = v1_issuer.public_key().id();
+        let mut expected = vec![legacy_id, v1_id];
+        expected.sort();
+        assert_eq!(cm.groups(), expected);
+
+        // Each group signs under the ciphersuite it joined with, whatever
+        // the manager's current one
+        let signature = cm
+            .sign_for_group(&legacy_id, b"message", b"basename")
+            .unwrap();
+        let suite = Ciphersuite::Bn254Sha256Legacy;
+        verify(suite, &legacy_key, &signature, b"message", b"basename").unwrap();
+
+        let signature = cm.sign_for_group(&v1_id, b"message", b"basename").unwrap();
+        let suite = Ciphersuite::Bn254Sha256V1;
+        verify(
+            suite,
+            v1_issuer.public_key(),
+            &signature,
+            b"message",
+            b"basename",
+        )
+        .unwrap();
+        assert!(matches!(
+            verify(suite, &legacy_key, &signature, b"message", b"basename"),
+            Err(CredentialError::SignatureValidation)
+        ));
 
-        assert_eq!(signature_bytes, expected_signature.as_slice());
+        // The single credential slot is separate from the wallet
+        assert!(matches!(
+            cm.sign(b"message", b"basename"),
+            Err(CredentialError::CredentialsNotSet)
+        ));
+
+        assert!(cm.remove_credentials(&legacy_id));
+        assert!(!cm.remove_credentials(&legacy_id));
+        assert_eq!(cm.groups(), [v1_id]);
+        assert!(matches!(
+            cm.sign_for_group(&legacy_id, b"message", b"basename"),
+            Err(CredentialError::CredentialsNotSet)
+        ));
+    }
+
+    #[test]
+    fn test_migration() {
+        let suite = Ciphersuite::default();
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let old_issuer = Issuer::new(&mut rng, suite);
+        let new_issuer = Issuer::new(&mut rng, suite);
+        let old = old_issuer.epoch_public_key(&mut rng, 7);
+        // An issuer outside the test helpers makes its key from (x, y)
+        let (x, y) = new_issuer.secret_key();
+        let new = make_epoch_group_public_key(&mut rng, suite, x, y, 8).unwrap();
+        assert!(matches!(
+            make_epoch_group_public_key::<Bn254>(&mut rng, Ciphersuite::Bls12381Sha256V1, x, y, 8),
+            Err(CredentialError::UnknownCiphersuite)
+        ));
+
+        let mut cm = manager_with_fixed_seed();
+        assert!(matches!(
+            cm.start_migration(&old, &new, CHALLENGE),
+            Err(CredentialError::CredentialsNotSet)
+        ));
+        let result = cm.start_join(CHALLENGE);
+        let join_response = old_issuer
+            .join(&mut rng, CHALLENGE, &result.join_msg)
+            .unwrap();
+        let credentials = cm
+            .finish_join(&old.key, &result.gsk, join_response)
+            .unwrap();
+        cm.set_gsk_and_credentials(result.gsk, credentials);
+        let old_nym = cm.sign(b"message", b"basename").unwrap().nym();
+
+        assert!(matches!(
+            cm.start_migration(&new, &old, CHALLENGE),
+            Err(CredentialError::MigrationValidation)
+        ));
+        let bytes = cm
+            .start_migration(&old, &new, CHALLENGE)
+            .unwrap()
+            .to_bytes();
+        let request: MigrationRequest = bytes.as_slice().try_into().unwrap();
+        assert_eq!(request.to_bytes(), bytes);
+        assert!(matches!(
+            MigrationRequest::try_from(&bytes[1..]),
+            Err(CredentialError::BadMigrationRequest)
+        ));
+
+        // The request is bound to the challenge and to both epochs, and
+        // proves credentials under the old key only
+        let later = new_issuer.epoch_public_key(&mut rng, 9);
+        let earlier = old_issuer.epoch_public_key(&mut rng, 6);
+        for (old_key, new_key, challenge) in [
+            (&old, &new, b"other".as_slice()),
+            (&new, &old, CHALLENGE),
+            (&new, &later, CHALLENGE),
+            (&earlier, &new, CHALLENGE),
+        ] {
+            assert!(matches!(
+                verify_migration_request(suite, old_key, new_key, challenge, &request),
+                Err(CredentialError::MigrationValidation)
+            ));
+        }
+
+        let join_msg = verify_migration_request(suite, &old, &new, CHALLENGE, &request).unwrap();
+        let join_response = new_issuer.join(&mut rng, CHALLENGE, join_msg).unwrap();
+        cm.finish_migration(&new, join_response).unwrap();
+
+        // Same gsk, so the nym survives the rotation
+        let signature = cm.sign(b"message", b"basename").unwrap();
+        verify(suite, &new.key, &signature, b"message", b"basename").unwrap();
+        assert!(matches!(
+            verify(suite, &old.key, &signature, b"message", b"basename"),
+            Err(CredentialError::SignatureValidation)
+        ));
+        assert_eq!(signature.nym(), old_nym);
+    }
+
+    #[test]
+    fn test_epoch_group_public_key_encoding() {
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        for suite in Curve::Bn254.suites() {
+            let issuer: Issuer = Issuer::new(&mut rng, suite);
+            let bytes = issuer.epoch_public_key(&mut rng, 7).to_bytes();
+
+            let key = EpochGroupPublicKey::try_from(&bytes[..]).unwrap();
+            assert_eq!(key.to_bytes(), bytes);
+            assert_eq!(key.suite(), suite);
+            assert_eq!(key.epoch(), 7);
+            assert_eq!(key.key().id(), issuer.public_key().id());
+
+            // The proof is bound to the epoch
+            let mut other_epoch = bytes;
+            other_epoch[8] ^= 1;
+            assert!(matches!(
+                EpochGroupPublicKey::try_from(&other_epoch[..]),
+                Err(CredentialError::BadGroupPublicKey)
+            ));
+
+            let mut unknown_suite = bytes;
+            unknown_suite[0] = u8::MAX;
+            assert!(matches!(
+                EpochGroupPublicKey::try_from(&unknown_suite[..]),
+                Err(CredentialError::UnknownCiphersuite)
+            ));
+            assert!(matches!(
+                EpochGroupPublicKey::try_from(&bytes[1..]),
+                Err(CredentialError::GroupPublicKeyLength)
+            ));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_group_migration() {
+        let suite = Ciphersuite::default();
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let old_issuer = Issuer::new(&mut rng, suite);
+        let new_issuer = Issuer::new(&mut rng, suite);
+        let old = old_issuer.epoch_public_key(&mut rng, 0);
+        let new = new_issuer.epoch_public_key(&mut rng, 1);
+
+        let mut cm = manager_with_fixed_seed();
+        let result = cm.start_join(CHALLENGE);
+        let join_response = old_issuer
+            .join(&mut rng, CHALLENGE, &result.join_msg)
+            .unwrap();
+        let credentials = cm
+            .finish_join(&old.key, &result.gsk, join_response)
+            .unwrap();
+        cm.add_credentials(&old.key, result.gsk, credentials)
+            .unwrap();
+
+        let request = cm.start_group_migration(&old, &new, CHALLENGE).unwrap();
+        let join_msg = verify_migration_request(suite, &old, &new, CHALLENGE, &request).unwrap();
+        let join_response = new_issuer.join(&mut rng, CHALLENGE, join_msg).unwrap();
+        cm.finish_group_migration(&old, &new, join_response)
+            .unwrap();
+
+        let mut expected = vec![old.key.id(), new.key.id()];
+        expected.sort();
+        assert_eq!(cm.groups(), expected);
+
+        let old_signature = cm
+            .sign_for_group(&old.key.id(), b"message", b"basename")
+            .unwrap();
+        let new_signature = cm
+            .sign_for_group(&new.key.id(), b"message", b"basename")
+            .unwrap();
+        verify(suite, &new.key, &new_signature, b"message", b"basename").unwrap();
+        assert_eq!(old_signature.nym(), new_signature.nym());
+    }
+
+    #[test]
+    fn test_credential_expiry() {
+        let suite = Ciphersuite::default();
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let issuer = Issuer::new(&mut rng, suite);
+        let metadata = CredentialMetadata::new(1_000, 2_000, issuer.public_key().id());
+
+        let bytes = metadata.to_bytes();
+        assert_eq!(
+            CredentialMetadata::try_from(bytes.as_slice()).unwrap(),
+            metadata
+        );
+        assert!(matches!(
+            CredentialMetadata::try_from(&bytes[1..]),
+            Err(CredentialError::BadCredentialMetadata)
+        ));
+
+        let mut cm = manager_with_fixed_seed();
+        let result = cm.start_join(CHALLENGE);
+        let response = issuer
+            .join_with_metadata(&mut rng, CHALLENGE, &result.join_msg, &metadata)
+            .unwrap()
+            .to_bytes();
+
+        // The response only verifies with the metadata it was issued with
+        let other_group = GroupKeyId([1; GROUP_KEY_ID_SIZE]);
+        for other in [
+            CredentialMetadata::new(1_000, 3_000, metadata.group),
+            CredentialMetadata::new(1_000, 2_000, other_group),
+        ] {
+            assert!(matches!(
+                cm.finish_join_with_metadata(
+                    issuer.public_key(),
+                    &result.gsk,
+                    response.as_slice().try_into().unwrap(),
+                    &other,
+                ),
+                Err(CredentialError::JoinResponseValidation)
+            ));
+        }
+        assert!(matches!(
+            cm.finish_join(
+                issuer.public_key(),
+                &result.gsk,
+                response.as_slice().try_into().unwrap()
+            ),
+            Err(CredentialError::JoinResponseValidation)
+        ));
+
+        let credentials = cm
+            .finish_join_with_metadata(
+                issuer.public_key(),
+                &result.gsk,
+                response.as_slice().try_into().unwrap(),
+                &metadata,
+            )
+            .unwrap();
+        cm.set_gsk_and_credentials_with_metadata(result.gsk, credentials, metadata);
+
+        cm.set_clock(|| 2_000);
+        let signature = cm.sign(b"message", b"basename").unwrap();
+        verify(
+            suite,
+            issuer.public_key(),
+            &signature,
+            b"message",
+            b"basename",
+        )
+        .unwrap();
+
+        cm.set_clock(|| 2_001);
+        assert!(matches!(
+            cm.sign(b"message", b"basename"),
+            Err(CredentialError::CredentialsExpired)
+        ));
+        assert!(matches!(
+            cm.sign_unlinkable(b"message"),
+            Err(CredentialError::CredentialsExpired)
+        ));
+        assert!(matches!(
+            cm.signer(),
+            Err(CredentialError::CredentialsExpired)
+        ));
+    }
+
+    #[test]
+    fn test_expiry_is_checked_again_when_streaming_finishes() {
+        static NOW: AtomicU64 = AtomicU64::new(2_000);
+        let gsk = EXPECTED_GSK.as_slice().try_into().unwrap();
+        let credentials = EXPECTED_CREDENTIALS.as_slice().try_into().unwrap();
+        let metadata = CredentialMetadata::new(1_000, 2_000, GroupKeyId([0; GROUP_KEY_ID_SIZE]));
+
+        let mut cm = manager_with_fixed_seed();
+        cm.set_clock(|| NOW.load(Ordering::Relaxed));
+        cm.set_gsk_and_credentials_with_metadata(gsk, credentials, metadata);
+
+        let mut signer = cm.signer().unwrap();
+        signer.update(b"message");
+        NOW.store(2_001, Ordering::Relaxed);
+        assert!(matches!(
+            signer.finalize(b"basename"),
+            Err(CredentialError::CredentialsExpired)
+        ));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_attribute_credential_expiry() {
+        let suite = Ciphersuite::default();
+        let fixture = issuer::Fixture::new_with_attributes(suite, &[Attribute::from(7)]);
+        let metadata = CredentialMetadata::new(1_000, 2_000, fixture.issuer.public_key().id());
+
+        let mut cm = manager_with_fixed_seed();
+        cm.set_clock(|| 2_000);
+        cm.set_gsk_and_attribute_credentials_with_metadata(
+            fixture.gsk,
+            fixture.credentials,
+            metadata,
+        );
+        let signature = cm
+            .sign_with_attributes(b"message", b"basename", &[0])
+            .unwrap();
+        verify_with_attributes(
+            suite,
+            &fixture.issuer.attribute_public_key(),
+            &signature,
+            b"message",
+            b"basename",
+        )
+        .unwrap();
+
+        cm.set_clock(|| 2_001);
+        assert!(matches!(
+            cm.sign_with_attributes(b"message", b"basename", &[0]),
+            Err(CredentialError::CredentialsExpired)
+        ));
+        assert!(matches!(
+            cm.sign_with_attribute_ranges(b"message", b"basename", &[], &[]),
+            Err(CredentialError::CredentialsExpired)
+        ));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_wallet_expiry() {
+        let suite = Ciphersuite::Bn254Sha256Legacy;
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let issuer = Issuer::new(&mut rng, suite);
+        let metadata = CredentialMetadata::new(1_000, 2_000, issuer.public_key().id());
+
+        let mut cm = manager_with_fixed_seed();
+        let result = cm.start_join(CHALLENGE);
+        let response = issuer
+            .join_with_metadata(&mut rng, CHALLENGE, &result.join_msg, &metadata)
+            .unwrap();
+        let credentials = cm
+            .finish_join_with_metadata(issuer.public_key(), &result.gsk, response, &metadata)
+            .unwrap();
+        cm.add_credentials_with_metadata(issuer.public_key(), result.gsk, credentials, metadata)
+            .unwrap();
+
+        // Credentials without metadata never expire
+        let legacy_key: GroupPublicKey = GROUP_PUB_KEY.as_slice().try_into().unwrap();
+        cm.add_credentials(
+            &legacy_key,
+            EXPECTED_GSK.as_slice().try_into().unwrap(),
+            EXPECTED_CREDENTIALS.as_slice().try_into().unwrap(),
+        )
+        .unwrap();
+
+        cm.set_clock(|| 1_500);
+        let mut expected = vec![metadata.group, legacy_key.id()];
+        expected.sort();
+        assert_eq!(cm.groups(), expected);
+        assert!(cm
+            .sign_for_group(&metadata.group, b"message", b"basename")
+            .is_ok());
+
+        cm.set_clock(|| 2_001);
+        assert_eq!(cm.groups(), [legacy_key.id()]);
+        assert!(matches!(
+            cm.sign_for_group(&metadata.group, b"message", b"basename"),
+            Err(CredentialError::CredentialsExpired)
+        ));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_migration_keeps_expiry() {
+        let suite = Ciphersuite::default();
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let old_issuer = Issuer::new(&mut rng, suite);
+        let new_issuer = Issuer::new(&mut rng, suite);
+        let old = old_issuer.epoch_public_key(&mut rng, 0);
+        let new = new_issuer.epoch_public_key(&mut rng, 1);
+        let metadata = CredentialMetadata::new(1_000, 2_000, old.key.id());
+        let renewed = CredentialMetadata::new(1_900, 3_000, new.key.id());
+
+        let mut cm = manager_with_fixed_seed();
+        cm.set_clock(|| 2_001);
+        let result = cm.start_join(CHALLENGE);
+        let response = old_issuer
+            .join_with_metadata(&mut rng, CHALLENGE, &result.join_msg, &metadata)
+            .unwrap();
+        let credentials = cm
+            .finish_join_with_metadata(&old.key, &result.gsk, response, &metadata)
+            .unwrap();
+        let copy = UserCredentials::try_from(credentials.to_bytes().as_slice()).unwrap();
+        cm.add_credentials_with_metadata(
+            &old.key,
+            data::CredentialBIG(result.gsk.0),
+            copy,
+            metadata,
+        )
+        .unwrap();
+        cm.set_gsk_and_credentials_with_metadata(result.gsk, credentials, metadata);
+
+        let mut migrate = |cm: &mut CredentialManager, metadata: Option<&CredentialMetadata>| {
+            let request = cm.start_group_migration(&old, &new, CHALLENGE).unwrap();
+            let join_msg =
+                verify_migration_request(suite, &old, &new, CHALLENGE, &request).unwrap();
+            match metadata {
+                Some(metadata) => new_issuer
+                    .join_with_metadata(&mut rng, CHALLENGE, join_msg, metadata)
+                    .unwrap(),
+                None => new_issuer.join(&mut rng, CHALLENGE, join_msg).unwrap(),
+            }
+        };
+
+        // A plain migration keeps the old expiry
+        let response = migrate(&mut cm, None);
+        cm.finish_migration(&new, response).unwrap();
+        assert!(matches!(
+            cm.sign(b"message", b"basename"),
+            Err(CredentialError::CredentialsExpired)
+        ));
+        let response = migrate(&mut cm, None);
+        cm.finish_group_migration(&old, &new, response).unwrap();
+        assert!(cm.groups().is_empty());
+
+        // A response bound to new metadata renews it
+        let response = migrate(&mut cm, Some(&renewed));
+        assert!(matches!(
+            cm.finish_migration_with_metadata(&new, response, metadata),
+            Err(CredentialError::JoinResponseValidation)
+        ));
+        let response = migrate(&mut cm, Some(&renewed));
+        cm.finish_migration_with_metadata(&new, response, renewed)
+            .unwrap();
+        let signature = cm.sign(b"message", b"basename").unwrap();
+        verify(suite, &new.key, &signature, b"message", b"basename").unwrap();
+        let response = migrate(&mut cm, Some(&renewed));
+        cm.finish_group_migration_with_metadata(&old, &new, response, renewed)
+            .unwrap();
+        assert_eq!(cm.groups(), [new.key.id()]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_finish_join_logged() {
+        let suite = Ciphersuite::Bn254Sha256V1;
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let issuer = Issuer::new(&mut rng, suite);
+        let other_issuer = Issuer::new(&mut rng, suite);
+
+        let mut log = KeyLog::new();
+        let index = log.append(KeyLogEntry::GroupKey(issuer.public_key().clone()));
+        let old = log.checkpoint();
+        log.append(KeyLogEntry::IssuanceCount {
+            group: issuer.public_key().id(),
+            epoch: 1,
+            count: 1,
+        });
+
+        let mut cm = manager_with_fixed_seed();
+        cm.set_ciphersuite(suite).unwrap();
+        let result = cm.start_join(CHALLENGE);
+        let response = issuer
+            .join(&mut rng, CHALLENGE, &result.join_msg)
+            .unwrap()
+            .to_bytes();
+        let proof = log.inclusion_proof(index, log.len()).unwrap();
+
+        // Nothing is logged until a checkpoint is trusted
+        assert!(matches!(
+            cm.finish_join_logged(
+                issuer.public_key(),
+                &result.gsk,
+                response.as_slice().try_into().unwrap(),
+                &proof,
+            ),
+            Err(CredentialError::KeyLogVerification)
+        ));
+
+        // Checkpoints only move forward along the same log
+        cm.set_key_log_checkpoint(old);
+        let mut forked = log.checkpoint();
+        forked.root[0] ^= 1;
+        let consistency = log.consistency_proof(old.size, log.len()).unwrap();
+        assert!(matches!(
+            cm.update_key_log_checkpoint(forked, &consistency),
+            Err(CredentialError::KeyLogVerification)
+        ));
+        assert_eq!(cm.key_log_checkpoint(), Some(old));
+        cm.update_key_log_checkpoint(log.checkpoint(), &consistency)
+            .unwrap();
+        assert_eq!(cm.key_log_checkpoint(), Some(log.checkpoint()));
+
+        // With a checkpoint set, joins only accept logged keys
+        assert!(matches!(
+            cm.finish_join(
+                issuer.public_key(),
+                &result.gsk,
+                response.as_slice().try_into().unwrap(),
+            ),
+            Err(CredentialError::KeyLogVerification)
+        ));
+        assert!(matches!(
+            cm.finish_join_logged(
+                other_issuer.public_key(),
+                &result.gsk,
+                response.as_slice().try_into().unwrap(),
+                &proof,
+            ),
+            Err(CredentialError::KeyLogVerification)
+        ));
+        let credentials = cm
+            .finish_join_logged(
+                issuer.public_key(),
+                &result.gsk,
+                response.as_slice().try_into().unwrap(),
+                &proof,
+            )
+            .unwrap();
+        assert!(cm
+            .finish_join(
+                issuer.public_key(),
+                &result.gsk,
+                response.as_slice().try_into().unwrap(),
+            )
+            .is_ok());
+        cm.set_gsk_and_credentials(result.gsk, credentials);
+        let signature = cm.sign(b"message", b"basename").unwrap();
+        verify(
+            suite,
+            issuer.public_key(),
+            &signature,
+            b"message",
+            b"basename",
+        )
+        .unwrap();
+
+        // Migrations too
+        let old = issuer.epoch_public_key(&mut rng, 0);
+        let new = other_issuer.epoch_public_key(&mut rng, 1);
+        let request = cm.start_migration(&old, &new, CHALLENGE).unwrap();
+        let join_msg = verify_migration_request(suite, &old, &new, CHALLENGE, &request).unwrap();
+        let response = other_issuer.join(&mut rng, CHALLENGE, join_msg).unwrap();
+        assert!(matches!(
+            cm.finish_migration(&new, response),
+            Err(CredentialError::KeyLogVerification)
+        ));
+    }
+
+    #[test]
+    fn test_group_key_id() {
+        let group_pub_key: GroupPublicKey = GROUP_PUB_KEY.as_slice().try_into().unwrap();
+
+        // The proofs do not contribute to the id
+        let mut bytes = GROUP_PUB_KEY.clone();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let other: GroupPublicKey = bytes.as_slice().try_into().unwrap();
+        assert_eq!(group_pub_key.id(), other.id());
+
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let issuer = Issuer::<Bn254>::new(&mut rng, Ciphersuite::Bn254Sha256Legacy);
+        assert_ne!(group_pub_key.id(), issuer.public_key().id());
+    }
+
+    #[test]
+    fn test_ciphersuites_are_domain_separated() {
+        let mut legacy_cm = manager_with_fixed_seed();
+        let mut v1_cm = manager_with_fixed_seed();
+        v1_cm.set_ciphersuite(Ciphersuite::Bn254Sha256V1).unwrap();
+
+        let legacy_join = legacy_cm.start_join(CHALLENGE);
+        let v1_join = v1_cm.start_join(CHALLENGE);
+
+        // Same seed yields the same gsk, but the proofs must differ
+        assert_eq!(legacy_join.gsk.to_bytes(), v1_join.gsk.to_bytes());
+        assert_ne!(legacy_join.join_msg.to_bytes(), v1_join.join_msg.to_bytes());
+
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let v1_issuer = Issuer::new(&mut rng, Ciphersuite::Bn254Sha256V1);
+
+        // A V1 issuer rejects a legacy join proof, and a legacy member
+        // rejects the issuer-key proofs of a V1 key
+        assert!(v1_issuer
+            .join(&mut rng, CHALLENGE, &legacy_join.join_msg)
+            .is_none());
+        let join_response = v1_issuer
+            .join(&mut rng, CHALLENGE, &v1_join.join_msg)
+            .unwrap();
+        assert!(matches!(
+            legacy_cm.finish_join(v1_issuer.public_key(), &legacy_join.gsk, join_response),
+            Err(CredentialError::BadGroupPublicKey)
+        ));
+
+        let gsk: CredentialBIG = EXPECTED_GSK.as_slice().try_into().unwrap();
+        let credentials: UserCredentials = EXPECTED_CREDENTIALS.as_slice().try_into().unwrap();
+        legacy_cm.set_gsk_and_credentials(gsk, credentials);
+        let gsk: CredentialBIG = EXPECTED_GSK.as_slice().try_into().unwrap();
+        let credentials: UserCredentials = EXPECTED_CREDENTIALS.as_slice().try_into().unwrap();
+        v1_cm.set_gsk_and_credentials(gsk, credentials);
+
+        let legacy_sig = legacy_cm.sign(b"message", b"basename").unwrap().to_bytes();
+        let v1_sig = v1_cm.sign(b"message", b"basename").unwrap().to_bytes();
+        assert_ne!(
+            legacy_sig[SIGNATURE_SIZE - ECP_PROOF_SIZE..],
+            v1_sig[SIGNATURE_SIZE - ECP_PROOF_SIZE..]
+        );
+    }
+
+    #[test]
+    fn test_ciphersuite_ids() {
+        for curve in [Curve::Bn254, Curve::Bls12381] {
+            for suite in curve.suites() {
+                assert_eq!(Ciphersuite::try_from(suite.id()).unwrap(), suite);
+                assert_eq!(suite.curve(), curve);
+            }
+        }
+        assert_eq!(Ciphersuite::ALL.len(), 3);
+        assert!(matches!(
+            Ciphersuite::try_from(0xff),
+            Err(CredentialError::UnknownCiphersuite)
+        ));
+    }
+
+    #[cfg(feature = "capi")]
+    #[test]
+    fn test_capi_join_and_sign() {
+        use capi::*;
+
+        let entropy = [0u8; 1];
+        let mut gsk = [0u8; AC_BIG_SIZE];
+        let mut join_msg = [0u8; AC_JOIN_REQUEST_SIZE];
+        let mut signature = [0u8; AC_SIGNATURE_SIZE];
+
+        unsafe {
+            let cm = ac_credential_manager_new_with_seed(entropy.as_ptr(), entropy.len());
+            let result = ac_start_join(
+                cm,
+                CHALLENGE.as_ptr(),
+                CHALLENGE.len(),
+                gsk.as_mut_ptr(),
+                join_msg.as_mut_ptr(),
+            );
+            assert_eq!(result, AcResult::Ok);
+            assert_eq!(gsk.as_slice(), EXPECTED_GSK.as_slice());
+
+            let result = ac_sign(
+                cm,
+                b"message".as_ptr(),
+                7,
+                b"basename".as_ptr(),
+                8,
+                signature.as_mut_ptr(),
+            );
+            assert_eq!(result, AcResult::CredentialsNotSet);
+
+            let result = ac_set_gsk_and_credentials(
+                cm,
+                EXPECTED_GSK.as_ptr(),
+                EXPECTED_GSK.len(),
+                EXPECTED_CREDENTIALS.as_ptr(),
+                EXPECTED_CREDENTIALS.len() - 1,
+            );
+            assert_eq!(result, AcResult::BadUserCredentials);
+
+            let result = ac_sign(
+                cm,
+                core::ptr::null(),
+                0,
+                core::ptr::null(),
+                0,
+                core::ptr::null_mut(),
+            );
+            assert_eq!(result, AcResult::NullPointer);
+
+            ac_credential_manager_free(cm);
+        }
+    }
+
+    #[cfg(feature = "capi")]
+    #[test]
+    fn test_capi_verify() {
+        use capi::*;
+
+        let verify = |msg: &[u8], signature: &[u8]| unsafe {
+            ac_verify(
+                Ciphersuite::Bn254Sha256Legacy.id(),
+                GROUP_PUB_KEY.as_ptr(),
+                GROUP_PUB_KEY.len(),
+                msg.as_ptr(),
+                msg.len(),
+                b"basename".as_ptr(),
+                8,
+                signature.as_ptr(),
+                signature.len(),
+            )
+        };
+        assert_eq!(verify(b"message", &EXPECTED_SIGNATURE), AcResult::Ok);
+        assert_eq!(
+            verify(b"message2", &EXPECTED_SIGNATURE),
+            AcResult::SignatureValidation
+        );
+        assert_eq!(
+            verify(b"message", &EXPECTED_SIGNATURE[1..]),
+            AcResult::BadSignature
+        );
+    }
+
+    #[cfg(feature = "capi")]
+    #[test]
+    fn test_capi_compat() {
+        use capi::*;
+        use core::ffi::c_int;
+        use core::mem::MaybeUninit;
+
+        let mut state = MaybeUninit::<GsState>::uninit();
+        let state = state.as_mut_ptr();
+        let mut join_msg = [0u8; AC_JOIN_REQUEST_SIZE];
+        let mut join_msg_len = join_msg.len() as c_int;
+        let mut credentials = [0u8; GS_USER_CREDENTIALS_SIZE];
+        let mut credentials_len = credentials.len() as c_int - 1;
+        let mut signature = [0u8; AC_SIGNATURE_SIZE];
+        let mut signature_len = signature.len() as c_int;
+
+        unsafe {
+            assert_eq!(GS_getStateSize() as usize, core::mem::size_of::<GsState>());
+            assert_eq!(GS_initState(state), 0);
+            GS_seed(state, [0u8].as_ptr(), 1);
+            let result =
+                GS_loadGroupPubKey(state, GROUP_PUB_KEY.as_ptr(), GROUP_PUB_KEY.len() as c_int);
+            assert_eq!(result, 0);
+
+            let result = GS_startJoin(
+                state,
+                CHALLENGE.as_ptr(),
+                CHALLENGE.len() as c_int,
+                join_msg.as_mut_ptr(),
+                &mut join_msg_len,
+            );
+            assert_eq!(result, 0);
+            assert_eq!(join_msg_len as usize, AC_JOIN_REQUEST_SIZE);
+
+            let finish_join = |credentials: &mut [u8], len: &mut c_int| {
+                GS_finishJoin(
+                    state,
+                    JOIN_RESPONSE.as_ptr(),
+                    JOIN_RESPONSE.len() as c_int,
+                    credentials.as_mut_ptr(),
+                    len,
+                )
+            };
+            assert_eq!(
+                finish_join(&mut credentials, &mut credentials_len),
+                AcResult::BufferTooSmall as c_int
+            );
+            credentials_len += 1;
+            assert_eq!(finish_join(&mut credentials, &mut credentials_len), 0);
+            assert_eq!(&credentials[..AC_BIG_SIZE], EXPECTED_GSK.as_slice());
+            assert_eq!(&credentials[AC_BIG_SIZE..], EXPECTED_CREDENTIALS.as_slice());
+
+            let result = GS_loadUserCredentials(state, credentials.as_ptr(), credentials_len);
+            assert_eq!(result, 0);
+            let result = GS_sign(
+                state,
+                b"message".as_ptr(),
+                7,
+                b"basename".as_ptr(),
+                8,
+                signature.as_mut_ptr(),
+                &mut signature_len,
+            );
+            assert_eq!(result, 0);
+            let verify = |msg: &[u8]| {
+                GS_verify(
+                    state,
+                    msg.as_ptr(),
+                    msg.len() as c_int,
+                    b"basename".as_ptr(),
+                    8,
+                    signature.as_ptr(),
+                    signature_len,
+                )
+            };
+            assert_eq!(verify(b"message"), 0);
+            assert_eq!(verify(b"message2"), AcResult::SignatureValidation as c_int);
+
+            GS_destroyState(state);
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let group_pub_key: GroupPublicKey = GROUP_PUB_KEY.as_slice().try_into().unwrap();
+        let signature: Signature = EXPECTED_SIGNATURE.as_slice().try_into().unwrap();
+
+        assert_eq!(signature.to_bytes(), EXPECTED_SIGNATURE.as_slice());
+        assert_eq!(group_pub_key.to_bytes(), GROUP_PUB_KEY.as_slice());
+
+        let suite = Ciphersuite::Bn254Sha256Legacy;
+        verify(suite, &group_pub_key, &signature, b"message", b"basename").unwrap();
+
+        assert!(matches!(
+            verify(suite, &group_pub_key, &signature, b"message2", b"basename"),
+            Err(CredentialError::SignatureValidation)
+        ));
+        assert!(matches!(
+            verify(suite, &group_pub_key, &signature, b"message", b"basename2"),
+            Err(CredentialError::SignatureValidation)
+        ));
+        assert!(matches!(
+            verify(
+                Ciphersuite::Bn254Sha256V1,
+                &group_pub_key,
+                &signature,
+                b"message",
+                b"basename"
+            ),
+            Err(CredentialError::SignatureValidation)
+        ));
+        assert!(matches!(
+            Signature::try_from(&EXPECTED_SIGNATURE[1..]),
+            Err(CredentialError::BadSignature)
+        ));
+    }
+
+    #[test]
+    fn test_non_canonical_scalars_are_rejected() {
+        // s + r is the same response mod r, so it would verify if decoded
+        let mut bytes = EXPECTED_SIGNATURE.clone();
+        issuer::add_curve_order::<Bn254>(&mut bytes[SIGNATURE_SIZE - BIG_SIZE..]);
+        assert!(matches!(
+            Signature::try_from(bytes.as_slice()),
+            Err(CredentialError::BadBIG)
+        ));
+
+        let mut bytes = GROUP_PUB_KEY.clone();
+        issuer::add_curve_order::<Bn254>(&mut bytes[GROUP_PUBLIC_KEY_SIZE - BIG_SIZE..]);
+        assert!(matches!(
+            GroupPublicKey::try_from(bytes.as_slice()),
+            Err(CredentialError::BadBIG)
+        ));
+    }
+
+    #[test]
+    fn test_zero_gsk_signatures_are_rejected() {
+        // Credentials for Q at infinity have D at infinity too, and satisfy
+        // both pairing equations, as would A = G1 / y, B = G1, C = A ** x
+        let suite = Ciphersuite::Bn254Sha256V1;
+        let mut rng = RAND::new();
+        rng.seed(1, &[1]);
+        let issuer = Issuer::<Bn254>::new(&mut rng, suite);
+        let r = Bn254::scalar_random(&mut rng);
+        let credentials = issuer.issue(&mut rng, &Bn254::g1_infinity(), &r).cred;
+        let gsk = data::CredentialBIG(Bn254::scalar_from_bytes(&[0u8; BIG_SIZE]));
+        let public_key = issuer.public_key();
+
+        let signature = sign::sign(&mut rng, suite, &gsk, &credentials, b"message", b"bsn");
+        assert!(Bn254::g1_is_infinity(&signature.nym));
+        assert!(matches!(
+            verify(suite, public_key, &signature, b"message", b"bsn"),
+            Err(CredentialError::SignatureValidation)
+        ));
+
+        let signature = sign::sign_unlinkable(&mut rng, suite, &gsk, &credentials, b"message");
+        assert!(matches!(
+            verify_unlinkable(suite, public_key, &signature, b"message"),
+            Err(CredentialError::SignatureValidation)
+        ));
+
+        #[cfg(feature = "alloc")]
+        {
+            let signature = sign_k_times(
+                &mut rng,
+                suite,
+                &gsk,
+                &credentials,
+                b"message",
+                b"context",
+                1,
+                0,
+                2,
+            )
+            .unwrap();
+            assert!(matches!(
+                verify_k_times(suite, public_key, &signature, b"message", b"context", 1, 2),
+                Err(CredentialError::SignatureValidation)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_compressed_points_are_rejected() {
+        let group_pub_key: GroupPublicKey = GROUP_PUB_KEY.as_slice().try_into().unwrap();
+
+        // One of the two compressed prefixes decodes to the same point, which
+        // must not give a second valid encoding of the signature
+        for point in 0..5 {
+            for prefix in [0x02, 0x03] {
+                let mut bytes = EXPECTED_SIGNATURE.clone();
+                bytes[point * ECP_SIZE] = prefix;
+                let signature: Signature = bytes.as_slice().try_into().unwrap();
+                assert!(matches!(
+                    verify(
+                        Ciphersuite::Bn254Sha256Legacy,
+                        &group_pub_key,
+                        &signature,
+                        b"message",
+                        b"basename"
+                    ),
+                    Err(CredentialError::SignatureValidation)
+                ));
+            }
+        }
     }
 }
@@ -0,0 +1,682 @@
+//! The member's side of the protocol, over the backend of one curve.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use brave_miracl::rand::RAND;
+#[cfg(feature = "std")]
+use rand::{rngs::OsRng, RngCore};
+
+#[cfg(feature = "alloc")]
+use super::attributes::{
+    finish_join_with_attributes, sign_with_attribute_ranges, AttributeCredentials,
+    AttributeGroupPublicKey, AttributeJoinResponse, AttributeRange, AttributeSignature,
+};
+use super::backend::{PairingBackend, BIG_SIZE};
+use super::ciphersuite::Ciphersuite;
+#[cfg(feature = "alloc")]
+use super::data::GroupKeyId;
+use super::data::{
+    CredentialBIG, CredentialMetadata, EpochGroupPublicKey, GroupPublicKey, JoinResponse,
+    MigrationRequest, Signature, StartJoinResult, UnlinkableSignature, UserCredentials,
+};
+#[cfg(feature = "alloc")]
+use super::join::verify_credentials;
+use super::join::{finish_join, finish_join_with_metadata, start_join};
+#[cfg(feature = "alloc")]
+use super::k_times::{sign_k_times, KTimesSignature};
+#[cfg(feature = "alloc")]
+use super::key_log::{ConsistencyProof, InclusionProof, KeyLogCheckpoint, KeyLogEntry};
+use super::migrate::start_migration;
+use super::sign::{sign, sign_prehashed, sign_unlinkable};
+use super::streaming::Signer;
+#[cfg(feature = "alloc")]
+use super::wallet::{Wallet, WalletEntry};
+use super::{CredentialError, Result};
+
+#[cfg(feature = "std")]
+fn default_clock() -> Option<fn() -> u64> {
+    Some(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    })
+}
+
+#[cfg(not(feature = "std"))]
+fn default_clock() -> Option<fn() -> u64> {
+    None
+}
+
+/// Fails with `CredentialsExpired` if `clock` is past the end of `metadata`.
+pub(crate) fn check_expiry(
+    metadata: Option<&CredentialMetadata>,
+    clock: Option<fn() -> u64>,
+) -> Result<()> {
+    match (metadata, clock) {
+        (Some(metadata), Some(clock)) if metadata.is_expired_at(clock()) => {
+            Err(CredentialError::CredentialsExpired)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Holds a member's gsk and credentials and signs with them. The crate
+/// root and the `bls12_381` module name it for their backends.
+pub struct CredentialManager<B: PairingBackend> {
+    pub(crate) rng: RAND,
+    suite: Ciphersuite,
+    gsk_and_credentials: Option<(CredentialBIG<B>, UserCredentials<B>)>,
+    credential_metadata: Option<CredentialMetadata>,
+    // Seconds since the Unix epoch, for checking expiry
+    clock: Option<fn() -> u64>,
+    #[cfg(feature = "alloc")]
+    gsk_and_attribute_credentials: Option<(CredentialBIG<B>, AttributeCredentials<B>)>,
+    #[cfg(feature = "alloc")]
+    attribute_metadata: Option<CredentialMetadata>,
+    #[cfg(feature = "alloc")]
+    wallet: Wallet<B>,
+    #[cfg(feature = "alloc")]
+    key_log_checkpoint: Option<KeyLogCheckpoint>,
+    // Keys proven to be in the key log, which are all that joins and
+    // migrations accept once a checkpoint is set
+    #[cfg(feature = "alloc")]
+    logged_keys: Vec<GroupKeyId>,
+}
+
+impl<B: PairingBackend> CredentialManager<B> {
+    /// Creates a manager seeded from the operating system's entropy source.
+    /// Without the `std` feature, use `new_with_seed` with caller-supplied
+    /// entropy instead.
+    #[cfg(feature = "std")]
+    pub fn new() -> Self {
+        let mut entropy = [0u8; 128];
+        OsRng.fill_bytes(&mut entropy);
+        Self::new_with_seed(&entropy)
+    }
+
+    pub fn new_with_seed(entropy: &[u8]) -> Self {
+        let mut rng = RAND::new();
+
+        rng.seed(entropy.len(), entropy);
+
+        Self {
+            rng,
+            suite: B::CURVE.default_suite(),
+            gsk_and_credentials: None,
+            credential_metadata: None,
+            clock: default_clock(),
+            #[cfg(feature = "alloc")]
+            gsk_and_attribute_credentials: None,
+            #[cfg(feature = "alloc")]
+            attribute_metadata: None,
+            #[cfg(feature = "alloc")]
+            wallet: Wallet::default(),
+            #[cfg(feature = "alloc")]
+            key_log_checkpoint: None,
+            #[cfg(feature = "alloc")]
+            logged_keys: Vec::new(),
+        }
+    }
+
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.suite
+    }
+
+    /// Switches to another suite of the backend's curve. Fails with
+    /// `UnknownCiphersuite` for a suite of another curve.
+    pub fn set_ciphersuite(&mut self, suite: Ciphersuite) -> Result<()> {
+        suite.check::<B>()?;
+        self.suite = suite;
+        Ok(())
+    }
+
+    /// Sets the source of the current time, in seconds since the Unix epoch,
+    /// that credential expiry is checked against. Defaults to the system
+    /// clock with the `std` feature; without it, expiry is not checked until
+    /// a clock is set.
+    pub fn set_clock(&mut self, clock: fn() -> u64) {
+        self.clock = Some(clock);
+    }
+
+    fn check_expiry(&self, metadata: Option<&CredentialMetadata>) -> Result<()> {
+        check_expiry(metadata, self.clock)
+    }
+
+    /// Once a key log checkpoint is trusted, rejects keys that have not been
+    /// shown to be in the log with `add_logged_key`.
+    #[cfg_attr(not(feature = "alloc"), allow(unused_variables))]
+    fn check_key_logged(&self, public_key: &GroupPublicKey<B>) -> Result<()> {
+        #[cfg(feature = "alloc")]
+        if self.key_log_checkpoint.is_some() && !self.logged_keys.contains(&public_key.id()) {
+            return Err(CredentialError::KeyLogVerification);
+        }
+        Ok(())
+    }
+
+    pub fn start_join(&mut self, challenge: &[u8]) -> StartJoinResult<B> {
+        start_join(&mut self.rng, self.suite, challenge)
+    }
+
+    pub fn finish_join(
+        &mut self,
+        public_key: &GroupPublicKey<B>,
+        gsk: &CredentialBIG<B>,
+        join_resp: JoinResponse<B>,
+    ) -> Result<UserCredentials<B>> {
+        self.check_key_logged(public_key)?;
+        finish_join(self.suite, public_key, gsk, join_resp)
+    }
+
+    /// Like `finish_join`, for a join response bound to the issuance
+    /// `metadata` of the group of `public_key`.
+    pub fn finish_join_with_metadata(
+        &mut self,
+        public_key: &GroupPublicKey<B>,
+        gsk: &CredentialBIG<B>,
+        join_resp: JoinResponse<B>,
+        metadata: &CredentialMetadata,
+    ) -> Result<UserCredentials<B>> {
+        self.check_key_logged(public_key)?;
+        finish_join_with_metadata(self.suite, public_key, gsk, join_resp, metadata)
+    }
+
+    pub fn set_gsk_and_credentials(
+        &mut self,
+        gsk: CredentialBIG<B>,
+        credentials: UserCredentials<B>,
+    ) {
+        self.gsk_and_credentials = Some((gsk, credentials));
+        self.credential_metadata = None;
+    }
+
+    /// Like `set_gsk_and_credentials`, refusing to sign with the credentials
+    /// once `metadata` says they have expired.
+    pub fn set_gsk_and_credentials_with_metadata(
+        &mut self,
+        gsk: CredentialBIG<B>,
+        credentials: UserCredentials<B>,
+        metadata: CredentialMetadata,
+    ) {
+        self.gsk_and_credentials = Some((gsk, credentials));
+        self.credential_metadata = Some(metadata);
+    }
+
+    /// Signs `msg` with the basename's nym. `basename` is either raw bytes or
+    /// a structured `Basename`.
+    pub fn sign(&mut self, msg: &[u8], basename: impl AsRef<[u8]>) -> Result<Signature<B>> {
+        self.check_expiry(self.credential_metadata.as_ref())?;
+        match &self.gsk_and_credentials {
+            Some((gsk, credentials)) => Ok(sign(
+                &mut self.rng,
+                self.suite,
+                gsk,
+                credentials,
+                msg,
+                basename.as_ref(),
+            )),
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Signs the message whose digest is `digest`, as computed by a
+    /// `MessageHasher` for the manager's ciphersuite. Gives the same
+    /// signature as `sign` on the message itself.
+    pub fn sign_prehashed(
+        &mut self,
+        digest: &[u8; BIG_SIZE],
+        basename: impl AsRef<[u8]>,
+    ) -> Result<Signature<B>> {
+        self.check_expiry(self.credential_metadata.as_ref())?;
+        match &self.gsk_and_credentials {
+            Some((gsk, credentials)) => Ok(sign_prehashed(
+                &mut self.rng,
+                self.suite,
+                gsk,
+                credentials,
+                digest,
+                basename.as_ref(),
+            )),
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Starts signing a message that is fed to the returned `Signer` in
+    /// chunks. Expiry is checked both here and in `Signer::finalize`.
+    pub fn signer(&mut self) -> Result<Signer<'_, B>> {
+        self.check_expiry(self.credential_metadata.as_ref())?;
+        match &self.gsk_and_credentials {
+            Some((gsk, credentials)) => Ok(Signer::new(
+                &mut self.rng,
+                self.suite,
+                gsk,
+                credentials,
+                self.credential_metadata.as_ref(),
+                self.clock,
+            )),
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Signs without a basename, so the signature proves group membership
+    /// but cannot be linked to any other signature.
+    pub fn sign_unlinkable(&mut self, msg: &[u8]) -> Result<UnlinkableSignature<B>> {
+        self.check_expiry(self.credential_metadata.as_ref())?;
+        match &self.gsk_and_credentials {
+            Some((gsk, credentials)) => Ok(sign_unlinkable(
+                &mut self.rng,
+                self.suite,
+                gsk,
+                credentials,
+                msg,
+            )),
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Stores credentials for the group of `public_key`, as returned by
+    /// `finish_join` for that key, next to those of other groups. They are
+    /// used with the manager's current ciphersuite and replace any earlier
+    /// credentials for the same group. Fails with `CredentialsValidation`
+    /// unless they verify against `public_key` for `gsk`.
+    #[cfg(feature = "alloc")]
+    pub fn add_credentials(
+        &mut self,
+        public_key: &GroupPublicKey<B>,
+        gsk: CredentialBIG<B>,
+        credentials: UserCredentials<B>,
+    ) -> Result<()> {
+        verify_credentials(self.suite, public_key, &gsk, &credentials)?;
+        self.wallet.insert(
+            public_key.id(),
+            WalletEntry {
+                suite: self.suite,
+                gsk,
+                credentials,
+                metadata: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Like `add_credentials`, for credentials from
+    /// `finish_join_with_metadata`. They are stored for `metadata.group`,
+    /// which must be the id of `public_key`, and no longer used once
+    /// `metadata` says they have expired.
+    #[cfg(feature = "alloc")]
+    pub fn add_credentials_with_metadata(
+        &mut self,
+        public_key: &GroupPublicKey<B>,
+        gsk: CredentialBIG<B>,
+        credentials: UserCredentials<B>,
+        metadata: CredentialMetadata,
+    ) -> Result<()> {
+        if metadata.group != public_key.id() {
+            return Err(CredentialError::BadCredentialMetadata);
+        }
+        verify_credentials(self.suite, public_key, &gsk, &credentials)?;
+        self.wallet.insert(
+            metadata.group,
+            WalletEntry {
+                suite: self.suite,
+                gsk,
+                credentials,
+                metadata: Some(metadata),
+            },
+        );
+        Ok(())
+    }
+
+    /// Drops the credentials for `group`, returning whether there were any.
+    #[cfg(feature = "alloc")]
+    pub fn remove_credentials(&mut self, group: &GroupKeyId) -> bool {
+        self.wallet.remove(group)
+    }
+
+    /// Groups the manager holds unexpired credentials for, in ascending
+    /// order of id.
+    #[cfg(feature = "alloc")]
+    pub fn groups(&self) -> Vec<GroupKeyId> {
+        self.wallet
+            .iter()
+            .filter(|(_, entry)| self.check_expiry(entry.metadata.as_ref()).is_ok())
+            .map(|(group, _)| *group)
+            .collect()
+    }
+
+    /// Like `sign`, with the credentials stored for `group`.
+    #[cfg(feature = "alloc")]
+    pub fn sign_for_group(
+        &mut self,
+        group: &GroupKeyId,
+        msg: &[u8],
+        basename: impl AsRef<[u8]>,
+    ) -> Result<Signature<B>> {
+        let entry = self
+            .wallet
+            .get(group)
+            .ok_or(CredentialError::CredentialsNotSet)?;
+        self.check_expiry(entry.metadata.as_ref())?;
+        Ok(sign(
+            &mut self.rng,
+            entry.suite,
+            &entry.gsk,
+            &entry.credentials,
+            msg,
+            basename.as_ref(),
+        ))
+    }
+
+    /// Requests credentials under the rotated key `new` for the gsk of the
+    /// credentials set with `set_gsk_and_credentials`, which were issued
+    /// under `old`. Keeping the gsk keeps the member's nyms.
+    pub fn start_migration(
+        &mut self,
+        old: &EpochGroupPublicKey<B>,
+        new: &EpochGroupPublicKey<B>,
+        challenge: &[u8],
+    ) -> Result<MigrationRequest<B>> {
+        match &self.gsk_and_credentials {
+            Some((gsk, credentials)) => start_migration(
+                &mut self.rng,
+                self.suite,
+                old,
+                new,
+                gsk,
+                credentials,
+                challenge,
+            ),
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Checks the issuer's answer to a migration request and replaces the
+    /// credentials with those under `new`. The old credentials' expiry, if
+    /// any, carries over to the new ones.
+    pub fn finish_migration(
+        &mut self,
+        new: &EpochGroupPublicKey<B>,
+        join_resp: JoinResponse<B>,
+    ) -> Result<()> {
+        self.check_key_logged(&new.key)?;
+        match &mut self.gsk_and_credentials {
+            Some((gsk, credentials)) => {
+                *credentials = finish_join(self.suite, &new.key, gsk, join_resp)?;
+                Ok(())
+            }
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Like `finish_migration`, for a response bound to `metadata` for the
+    /// group of `new`, which then replaces the old credentials' metadata.
+    pub fn finish_migration_with_metadata(
+        &mut self,
+        new: &EpochGroupPublicKey<B>,
+        join_resp: JoinResponse<B>,
+        metadata: CredentialMetadata,
+    ) -> Result<()> {
+        self.check_key_logged(&new.key)?;
+        match &mut self.gsk_and_credentials {
+            Some((gsk, credentials)) => {
+                *credentials =
+                    finish_join_with_metadata(self.suite, &new.key, gsk, join_resp, &metadata)?;
+                self.credential_metadata = Some(metadata);
+                Ok(())
+            }
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Like `start_migration`, for the credentials stored for the group of
+    /// `old`.
+    #[cfg(feature = "alloc")]
+    pub fn start_group_migration(
+        &mut self,
+        old: &EpochGroupPublicKey<B>,
+        new: &EpochGroupPublicKey<B>,
+        challenge: &[u8],
+    ) -> Result<MigrationRequest<B>> {
+        match self.wallet.get(&old.key.id()) {
+            Some(entry) => start_migration(
+                &mut self.rng,
+                entry.suite,
+                old,
+                new,
+                &entry.gsk,
+                &entry.credentials,
+                challenge,
+            ),
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Checks the issuer's answer to a migration request and stores the
+    /// credentials under the group of `new`, with the expiry of those for
+    /// `old`. Those for `old` are kept until removed, for verifiers that
+    /// still accept the old key.
+    #[cfg(feature = "alloc")]
+    pub fn finish_group_migration(
+        &mut self,
+        old: &EpochGroupPublicKey<B>,
+        new: &EpochGroupPublicKey<B>,
+        join_resp: JoinResponse<B>,
+    ) -> Result<()> {
+        self.migrate_group(old, new, join_resp, None)
+    }
+
+    /// Like `finish_group_migration`, for a response bound to `metadata` for
+    /// the group of `new`, which the new credentials keep instead.
+    #[cfg(feature = "alloc")]
+    pub fn finish_group_migration_with_metadata(
+        &mut self,
+        old: &EpochGroupPublicKey<B>,
+        new: &EpochGroupPublicKey<B>,
+        join_resp: JoinResponse<B>,
+        metadata: CredentialMetadata,
+    ) -> Result<()> {
+        self.migrate_group(old, new, join_resp, Some(metadata))
+    }
+
+    #[cfg(feature = "alloc")]
+    fn migrate_group(
+        &mut self,
+        old: &EpochGroupPublicKey<B>,
+        new: &EpochGroupPublicKey<B>,
+        join_resp: JoinResponse<B>,
+        metadata: Option<CredentialMetadata>,
+    ) -> Result<()> {
+        self.check_key_logged(&new.key)?;
+        let entry = self
+            .wallet
+            .get(&old.key.id())
+            .ok_or(CredentialError::CredentialsNotSet)?;
+        let credentials = match &metadata {
+            Some(metadata) => {
+                finish_join_with_metadata(entry.suite, &new.key, &entry.gsk, join_resp, metadata)?
+            }
+            None => finish_join(entry.suite, &new.key, &entry.gsk, join_resp)?,
+        };
+        let entry = WalletEntry {
+            suite: entry.suite,
+            gsk: CredentialBIG(entry.gsk.0),
+            credentials,
+            metadata: metadata.or(entry.metadata),
+        };
+        self.wallet.insert(new.key.id(), entry);
+        Ok(())
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn finish_join_with_attributes(
+        &mut self,
+        public_key: &AttributeGroupPublicKey<B>,
+        gsk: &CredentialBIG<B>,
+        join_resp: AttributeJoinResponse<B>,
+    ) -> Result<AttributeCredentials<B>> {
+        self.check_key_logged(&public_key.base)?;
+        finish_join_with_attributes(self.suite, public_key, gsk, join_resp)
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn set_gsk_and_attribute_credentials(
+        &mut self,
+        gsk: CredentialBIG<B>,
+        credentials: AttributeCredentials<B>,
+    ) {
+        self.gsk_and_attribute_credentials = Some((gsk, credentials));
+        self.attribute_metadata = None;
+    }
+
+    /// Like `set_gsk_and_attribute_credentials`, refusing to sign with the
+    /// credentials once `metadata` says they have expired.
+    #[cfg(feature = "alloc")]
+    pub fn set_gsk_and_attribute_credentials_with_metadata(
+        &mut self,
+        gsk: CredentialBIG<B>,
+        credentials: AttributeCredentials<B>,
+        metadata: CredentialMetadata,
+    ) {
+        self.gsk_and_attribute_credentials = Some((gsk, credentials));
+        self.attribute_metadata = Some(metadata);
+    }
+
+    /// Signs with the attribute credentials, revealing the attributes at the
+    /// indices in `disclose`. Like `sign`, it takes the basename as raw bytes
+    /// or as a structured `Basename`.
+    #[cfg(feature = "alloc")]
+    pub fn sign_with_attributes(
+        &mut self,
+        msg: &[u8],
+        basename: impl AsRef<[u8]>,
+        disclose: &[usize],
+    ) -> Result<AttributeSignature<B>> {
+        self.sign_with_attribute_ranges(msg, basename, disclose, &[])
+    }
+
+    /// Like `sign_with_attributes`, also proving that hidden attributes lie
+    /// in `ranges`.
+    #[cfg(feature = "alloc")]
+    pub fn sign_with_attribute_ranges(
+        &mut self,
+        msg: &[u8],
+        basename: impl AsRef<[u8]>,
+        disclose: &[usize],
+        ranges: &[AttributeRange],
+    ) -> Result<AttributeSignature<B>> {
+        self.check_expiry(self.attribute_metadata.as_ref())?;
+        match &self.gsk_and_attribute_credentials {
+            Some((gsk, credentials)) => sign_with_attribute_ranges(
+                &mut self.rng,
+                self.suite,
+                gsk,
+                credentials,
+                msg,
+                basename.as_ref(),
+                disclose,
+                ranges,
+            ),
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Signs with the `counter`th of `k` basenames for `context` and
+    /// `epoch`, so that a collector deduplicating nyms accepts at most `k`
+    /// signatures per epoch. Each counter should be used once per epoch.
+    #[cfg(feature = "alloc")]
+    pub fn sign_k_times(
+        &mut self,
+        msg: &[u8],
+        context: &[u8],
+        epoch: u64,
+        counter: u8,
+        k: u8,
+    ) -> Result<KTimesSignature<B>> {
+        self.check_expiry(self.credential_metadata.as_ref())?;
+        match &self.gsk_and_credentials {
+            Some((gsk, credentials)) => sign_k_times(
+                &mut self.rng,
+                self.suite,
+                gsk,
+                credentials,
+                msg,
+                context,
+                epoch,
+                counter,
+                k,
+            ),
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    /// Trusts `checkpoint` of the issuer's key log, as obtained from
+    /// auditors, for `add_logged_key` and `finish_join_logged`.
+    #[cfg(feature = "alloc")]
+    pub fn set_key_log_checkpoint(&mut self, checkpoint: KeyLogCheckpoint) {
+        self.key_log_checkpoint = Some(checkpoint);
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn key_log_checkpoint(&self) -> Option<KeyLogCheckpoint> {
+        self.key_log_checkpoint
+    }
+
+    /// Moves to a newer checkpoint of the key log, given a proof that the
+    /// log only grew since the trusted one.
+    #[cfg(feature = "alloc")]
+    pub fn update_key_log_checkpoint(
+        &mut self,
+        checkpoint: KeyLogCheckpoint,
+        proof: &ConsistencyProof,
+    ) -> Result<()> {
+        let trusted = self
+            .key_log_checkpoint
+            .as_ref()
+            .ok_or(CredentialError::KeyLogVerification)?;
+        proof.verify(trusted, &checkpoint)?;
+        self.key_log_checkpoint = Some(checkpoint);
+        Ok(())
+    }
+
+    /// Checks that `public_key` is in the key log at the trusted checkpoint,
+    /// so that the issuer cannot single the member out with a key that
+    /// auditors never saw. While a checkpoint is set, every join and
+    /// migration fails with `KeyLogVerification` for keys not added here.
+    #[cfg(feature = "alloc")]
+    pub fn add_logged_key(
+        &mut self,
+        public_key: &GroupPublicKey<B>,
+        proof: &InclusionProof,
+    ) -> Result<()> {
+        let checkpoint = self
+            .key_log_checkpoint
+            .as_ref()
+            .ok_or(CredentialError::KeyLogVerification)?;
+        proof.verify(&KeyLogEntry::<B>::GroupKey(public_key.clone()), checkpoint)?;
+        let id = public_key.id();
+        if !self.logged_keys.contains(&id) {
+            self.logged_keys.push(id);
+        }
+        Ok(())
+    }
+
+    /// Like `finish_join`, first adding `public_key` with `add_logged_key`.
+    #[cfg(feature = "alloc")]
+    pub fn finish_join_logged(
+        &mut self,
+        public_key: &GroupPublicKey<B>,
+        gsk: &CredentialBIG<B>,
+        join_resp: JoinResponse<B>,
+        proof: &InclusionProof,
+    ) -> Result<UserCredentials<B>> {
+        self.add_logged_key(public_key, proof)?;
+        finish_join(self.suite, public_key, gsk, join_resp)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: PairingBackend> Default for CredentialManager<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
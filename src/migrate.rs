@@ -0,0 +1,192 @@
+//! Re-issuance of credentials under a rotated issuer key.
+//!
+//! A member holding credentials under the key of one epoch sends a join
+//! request for the same gsk, together with those credentials randomized as
+//! in a signature and a proof that log_G1(Q) = log_B(D). The issuer checks
+//! the credentials against the old key and then answers the join request as
+//! usual, so the member keeps its gsk, and with it its nyms, across the
+//! rotation. As with any join, the issuer learns Q.
+//!
+//! Each epoch's key carries a second proof of knowledge of x and y, under
+//! a transcript that absorbs the epoch, so that the epochs a member
+//! migrates between are the ones the issuer assigned.
+
+use brave_miracl::rand::RAND;
+
+use super::backend::PairingBackend;
+use super::ciphersuite::Ciphersuite;
+use super::data::{
+    CredentialBIG, EpochGroupPublicKey, GroupPublicKey, JoinRequest, MigrationRequest,
+    UserCredentials,
+};
+use super::join::{
+    join_request, join_request_is_valid, make_group_public_key, verify_group_public_key,
+};
+use super::transcript::{Transcript, PROTOCOL_MIGRATE};
+use super::util::{make_ecp_proof_equals, verify_ecp_proof_equals};
+use super::verify::randomized_credentials_are_valid;
+use super::{CredentialError, Result};
+
+fn epoch_key_transcript<B: PairingBackend>(
+    suite: Ciphersuite,
+    epoch: u64,
+    key: &GroupPublicKey<B>,
+    tx: &B::G2,
+    ty: &B::G2,
+) -> Transcript<B> {
+    let mut transcript = Transcript::<B>::labeled(suite, PROTOCOL_MIGRATE);
+    transcript.append_bytes(b"epoch", &epoch.to_be_bytes());
+    transcript.append_ecp2(b"x", &key.x);
+    transcript.append_ecp2(b"y", &key.y);
+    transcript.append_ecp2(b"tx", tx);
+    transcript.append_ecp2(b"ty", ty);
+    transcript
+}
+
+/// Key of `epoch` for an issuer holding the whole secret (x, y), as
+/// `start_migration` and `verify_migration_request` take it. Issuer keys
+/// split between nodes come from `deal_epoch_issuer_key` instead.
+pub fn make_epoch_group_public_key<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    x: &B::Scalar,
+    y: &B::Scalar,
+    epoch: u64,
+) -> Result<EpochGroupPublicKey<B>> {
+    suite.check::<B>()?;
+    let key = make_group_public_key::<B>(rng, suite, x, y);
+    Ok(assign_epoch(rng, suite, epoch, key, x, y))
+}
+
+/// Key of `epoch` for the secret (x, y) behind `key`, proving knowledge of
+/// both under a transcript bound to the epoch.
+pub(crate) fn assign_epoch<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    epoch: u64,
+    key: GroupPublicKey<B>,
+    x: &B::Scalar,
+    y: &B::Scalar,
+) -> EpochGroupPublicKey<B> {
+    let rx = B::scalar_random(rng);
+    let ry = B::scalar_random(rng);
+
+    let g2 = B::g2_generator();
+    let c = epoch_key_transcript(
+        suite,
+        epoch,
+        &key,
+        &B::g2_mul(&g2, &rx),
+        &B::g2_mul(&g2, &ry),
+    )
+    .challenge();
+    EpochGroupPublicKey {
+        suite,
+        epoch,
+        key,
+        c,
+        sx: B::scalar_add(&B::scalar_mul(&c, x), &rx),
+        sy: B::scalar_add(&B::scalar_mul(&c, y), &ry),
+    }
+}
+
+pub(crate) fn epoch_group_public_key_is_valid<B: PairingBackend>(
+    key: &EpochGroupPublicKey<B>,
+) -> bool {
+    let cn = B::scalar_neg(&key.c);
+
+    let g2 = B::g2_generator();
+    let tx = B::g2_add(&B::g2_mul(&g2, &key.sx), &B::g2_mul(&key.key.x, &cn));
+    let ty = B::g2_add(&B::g2_mul(&g2, &key.sy), &B::g2_mul(&key.key.y, &cn));
+
+    let c = epoch_key_transcript(key.suite, key.epoch, &key.key, &tx, &ty).challenge();
+    B::scalar_equals(&key.c, &c) && verify_group_public_key(key.suite, &key.key).is_ok()
+}
+
+fn migration_transcript<B: PairingBackend>(
+    suite: Ciphersuite,
+    old: &EpochGroupPublicKey<B>,
+    new: &EpochGroupPublicKey<B>,
+    challenge: &[u8],
+    cred: &UserCredentials<B>,
+) -> Transcript<B> {
+    let mut transcript = Transcript::<B>::labeled(suite, PROTOCOL_MIGRATE);
+    transcript.append_bytes(b"challenge", &suite.hash(challenge));
+    transcript.append_bytes(b"old_epoch", &old.epoch.to_be_bytes());
+    transcript.append_bytes(b"old_key", &old.key.id().to_bytes());
+    transcript.append_bytes(b"new_epoch", &new.epoch.to_be_bytes());
+    transcript.append_bytes(b"new_key", &new.key.id().to_bytes());
+    transcript.append_ecp(b"a", &cred.a);
+    transcript.append_ecp(b"c", &cred.c);
+    transcript
+}
+
+/// Requests credentials under `new` for the gsk behind `credentials`, which
+/// were issued under `old`.
+pub fn start_migration<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    old: &EpochGroupPublicKey<B>,
+    new: &EpochGroupPublicKey<B>,
+    gsk: &CredentialBIG<B>,
+    credentials: &UserCredentials<B>,
+    challenge: &[u8],
+) -> Result<MigrationRequest<B>> {
+    if new.epoch <= old.epoch || old.suite != suite || new.suite != suite {
+        return Err(CredentialError::MigrationValidation);
+    }
+
+    let join = join_request::<B>(rng, suite, &gsk.0, challenge);
+
+    let r = B::scalar_random(rng);
+    let cred = UserCredentials {
+        a: B::g1_mul(&credentials.a, &r),
+        b: B::g1_mul(&credentials.b, &r),
+        c: B::g1_mul(&credentials.c, &r),
+        d: B::g1_mul(&credentials.d, &r),
+    };
+
+    let proof = make_ecp_proof_equals(
+        rng,
+        migration_transcript(suite, old, new, challenge, &cred),
+        &B::g1_generator(),
+        &cred.b,
+        &join.q,
+        &cred.d,
+        &gsk.0,
+    );
+
+    Ok(MigrationRequest { join, cred, proof })
+}
+
+/// Checks that the sender of `req` holds credentials under `old` for the
+/// gsk of its join request, and returns that join request. The issuer of
+/// `new` answers it as it would any other, with the same `challenge`.
+pub fn verify_migration_request<'a, B: PairingBackend>(
+    suite: Ciphersuite,
+    old: &EpochGroupPublicKey<B>,
+    new: &EpochGroupPublicKey<B>,
+    challenge: &[u8],
+    req: &'a MigrationRequest<B>,
+) -> Result<&'a JoinRequest<B>> {
+    suite.check::<B>()?;
+    let cred = &req.cred;
+    let valid = new.epoch > old.epoch
+        && old.suite == suite
+        && new.suite == suite
+        && join_request_is_valid(suite, challenge, &req.join)
+        && randomized_credentials_are_valid(&old.key, &cred.a, &cred.b, &cred.c, &cred.d)
+        && verify_ecp_proof_equals(
+            migration_transcript(suite, old, new, challenge, cred),
+            &B::g1_generator(),
+            &cred.b,
+            &req.join.q,
+            &cred.d,
+            &req.proof,
+        );
+
+    match valid {
+        true => Ok(&req.join),
+        false => Err(CredentialError::MigrationValidation),
+    }
+}
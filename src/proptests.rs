@@ -0,0 +1,225 @@
+//! Property-based tests of the protocol's correctness and unlinkability
+//! invariants over random seeds, challenges, messages and basenames.
+
+use brave_miracl::rand::RAND;
+use proptest::prelude::*;
+
+use super::ciphersuite::{Ciphersuite, Curve};
+use super::issuer::{add_curve_order, Issuer};
+use super::join::{finish_join, start_join};
+use super::sign::sign;
+use super::verify::verify;
+use super::{
+    Bn254, CredentialBIG, CredentialError, ECPProof, GroupPublicKey, JoinRequest, JoinResponse,
+    Signature, StartJoinResult, UserCredentials, BIG_SIZE, ECP_PROOF_SIZE, JOIN_RESPONSE_SIZE,
+    SIGNATURE_SIZE,
+};
+
+fn rng(seed: &[u8]) -> RAND {
+    let mut rng = RAND::new();
+    rng.seed(seed.len(), seed);
+    rng
+}
+
+fn ciphersuite() -> impl Strategy<Value = Ciphersuite> {
+    prop::sample::select(Curve::Bn254.suites().collect::<Vec<_>>())
+}
+
+fn seed() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), 1..64)
+}
+
+fn bytes() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), 0..64)
+}
+
+struct Member {
+    gsk: CredentialBIG,
+    join_msg: JoinRequest,
+    join_response: JoinResponse,
+}
+
+fn join(suite: Ciphersuite, issuer: &Issuer, member_seed: &[u8], challenge: &[u8]) -> Member {
+    let mut rng = rng(member_seed);
+    let StartJoinResult { gsk, join_msg } = start_join(&mut rng, suite, challenge);
+    let join_response = issuer
+        .join(&mut rng, challenge, &join_msg)
+        .expect("issuer rejected an honest join request");
+    Member {
+        gsk,
+        join_msg,
+        join_response,
+    }
+}
+
+fn finish(suite: Ciphersuite, issuer: &Issuer, member: Member) -> (CredentialBIG, UserCredentials) {
+    let credentials = finish_join(
+        suite,
+        issuer.public_key(),
+        &member.gsk,
+        member.join_response,
+    )
+    .unwrap();
+    (member.gsk, credentials)
+}
+
+fn tampered(bytes: &[u8], index: usize, mask: u8) -> Vec<u8> {
+    let mut bytes = bytes.to_vec();
+    bytes[index] ^= mask;
+    bytes
+}
+
+proptest! {
+    // Every case runs several pairings
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn prop_join_sign_verify(
+        suite in ciphersuite(),
+        issuer_seed in seed(),
+        member_seed in seed(),
+        challenge in bytes(),
+        msg in bytes(),
+        bsn in bytes(),
+    ) {
+        let mut issuer_rng = rng(&issuer_seed);
+        let issuer = Issuer::new(&mut issuer_rng, suite);
+        let member = join(suite, &issuer, &member_seed, &challenge);
+        let (gsk, credentials) = finish(suite, &issuer, member);
+
+        let signature = sign(&mut issuer_rng, suite, &gsk, &credentials, &msg, &bsn);
+        prop_assert!(verify(suite, issuer.public_key(), &signature, &msg, &bsn).is_ok());
+    }
+
+    #[test]
+    fn prop_tampered_join_response_is_rejected(
+        suite in ciphersuite(),
+        issuer_seed in seed(),
+        member_seed in seed(),
+        index in 0..JOIN_RESPONSE_SIZE,
+        mask in 1..=u8::MAX,
+    ) {
+        let issuer = Issuer::new(&mut rng(&issuer_seed), suite);
+        let member = join(suite, &issuer, &member_seed, b"challenge");
+
+        let bytes = tampered(&member.join_response.to_bytes(), index, mask);
+        let result = JoinResponse::try_from(bytes.as_slice())
+            .and_then(|response| finish_join(suite, issuer.public_key(), &member.gsk, response));
+        prop_assert!(result.is_err());
+    }
+
+    #[test]
+    fn prop_tampered_signature_is_rejected(
+        suite in ciphersuite(),
+        issuer_seed in seed(),
+        member_seed in seed(),
+        msg in bytes(),
+        bsn in bytes(),
+        index in 0..SIGNATURE_SIZE,
+        mask in 1..=u8::MAX,
+    ) {
+        let issuer = Issuer::new(&mut rng(&issuer_seed), suite);
+        let member = join(suite, &issuer, &member_seed, b"challenge");
+        let (gsk, credentials) = finish(suite, &issuer, member);
+        let signature = sign(&mut rng(&member_seed), suite, &gsk, &credentials, &msg, &bsn);
+
+        let bytes = tampered(&signature.to_bytes(), index, mask);
+        let result = Signature::try_from(bytes.as_slice())
+            .and_then(|signature| verify(suite, issuer.public_key(), &signature, &msg, &bsn));
+        prop_assert!(result.is_err());
+    }
+
+    #[test]
+    fn prop_non_canonical_scalars_are_rejected(
+        suite in ciphersuite(),
+        issuer_seed in seed(),
+        member_seed in seed(),
+        msg in bytes(),
+        bsn in bytes(),
+        scalar in 0..2usize,
+    ) {
+        let issuer = Issuer::new(&mut rng(&issuer_seed), suite);
+        let member = join(suite, &issuer, &member_seed, b"challenge");
+
+        // c or s of the proof, plus the curve order
+        let proof = |size: usize| size - ECP_PROOF_SIZE + BIG_SIZE * scalar;
+        let mut bytes = member.join_response.to_bytes();
+        let start = proof(JOIN_RESPONSE_SIZE);
+        add_curve_order::<Bn254>(&mut bytes[start..start + BIG_SIZE]);
+        prop_assert!(matches!(
+            JoinResponse::try_from(bytes.as_slice()),
+            Err(CredentialError::BadBIG)
+        ));
+
+        let (gsk, credentials) = finish(suite, &issuer, member);
+        let signature = sign(&mut rng(&member_seed), suite, &gsk, &credentials, &msg, &bsn);
+        let mut bytes = signature.to_bytes();
+        let start = proof(SIGNATURE_SIZE);
+        add_curve_order::<Bn254>(&mut bytes[start..start + BIG_SIZE]);
+        prop_assert!(matches!(
+            Signature::try_from(bytes.as_slice()),
+            Err(CredentialError::BadBIG)
+        ));
+    }
+
+    #[test]
+    fn prop_nym_links_only_same_member_and_basename(
+        suite in ciphersuite(),
+        issuer_seed in seed(),
+        member_seeds in (seed(), seed()),
+        msgs in (bytes(), bytes()),
+        bsns in (bytes(), bytes()),
+    ) {
+        prop_assume!(member_seeds.0 != member_seeds.1 && bsns.0 != bsns.1);
+
+        let mut issuer_rng = rng(&issuer_seed);
+        let issuer = Issuer::new(&mut issuer_rng, suite);
+        let (gsk, credentials) =
+            finish(suite, &issuer, join(suite, &issuer, &member_seeds.0, b"challenge"));
+        let (other_gsk, other_credentials) =
+            finish(suite, &issuer, join(suite, &issuer, &member_seeds.1, b"challenge"));
+
+        let mut sign_with = |gsk: &CredentialBIG, credentials, msg: &[u8], bsn: &[u8]| {
+            sign(&mut issuer_rng, suite, gsk, credentials, msg, bsn).nym()
+        };
+        let nym = sign_with(&gsk, &credentials, &msgs.0, &bsns.0);
+
+        prop_assert_eq!(nym, sign_with(&gsk, &credentials, &msgs.1, &bsns.0));
+        prop_assert_ne!(nym, sign_with(&gsk, &credentials, &msgs.0, &bsns.1));
+        prop_assert_ne!(nym, sign_with(&other_gsk, &other_credentials, &msgs.0, &bsns.0));
+    }
+
+    #[test]
+    fn prop_serialization_round_trips(
+        suite in ciphersuite(),
+        issuer_seed in seed(),
+        member_seed in seed(),
+        msg in bytes(),
+        bsn in bytes(),
+    ) {
+        let issuer = Issuer::new(&mut rng(&issuer_seed), suite);
+        let member = join(suite, &issuer, &member_seed, b"challenge");
+
+        let public_key = issuer.public_key().to_bytes();
+        prop_assert_eq!(GroupPublicKey::try_from(&public_key[..]).unwrap().to_bytes(), public_key);
+        let join_msg = member.join_msg.to_bytes();
+        prop_assert_eq!(JoinRequest::try_from(&join_msg[..]).unwrap().to_bytes(), join_msg);
+        let join_response = member.join_response.to_bytes();
+        prop_assert_eq!(JoinResponse::try_from(&join_response[..]).unwrap().to_bytes(), join_response);
+
+        let (gsk, credentials) = finish(suite, &issuer, member);
+        let gsk_bytes = gsk.to_bytes();
+        prop_assert_eq!(CredentialBIG::try_from(&gsk_bytes[..]).unwrap().to_bytes(), gsk_bytes);
+        let credential_bytes = credentials.to_bytes();
+        prop_assert_eq!(
+            UserCredentials::try_from(&credential_bytes[..]).unwrap().to_bytes(),
+            credential_bytes
+        );
+
+        let signature = sign(&mut rng(&member_seed), suite, &gsk, &credentials, &msg, &bsn);
+        let signature_bytes = signature.to_bytes();
+        prop_assert_eq!(Signature::try_from(&signature_bytes[..]).unwrap().to_bytes(), signature_bytes);
+        let proof = signature.proof.to_bytes();
+        prop_assert_eq!(ECPProof::try_from(&proof[..]).unwrap().to_bytes(), proof);
+    }
+}
@@ -1,62 +1,90 @@
-use brave_miracl::{
-    bn254::{big::BIG, ecp::ECP, pair::g1mul},
-    rand::RAND,
-};
+use brave_miracl::rand::RAND;
 
 use super::{
-    data::{CredentialBIG, ECPProof, Signature, UserCredentials, BIG_SIZE},
-    util::{ecp_challenge_equals, hash256, random_mod_curve_order, CURVE_ORDER_BIG},
+    backend::{PairingBackend, BIG_SIZE},
+    ciphersuite::Ciphersuite,
+    data::{CredentialBIG, ECPProof, Signature, UnlinkableSignature, UserCredentials},
+    transcript::{Transcript, PROTOCOL_SIGN, PROTOCOL_SIGN_UNLINKABLE},
+    util::make_ecp_proof_equals,
 };
 
-fn make_ecp_proof_equals(
-    rng: &mut RAND,
-    message: &[u8; BIG_SIZE],
-    a: &ECP,
-    b: &ECP,
-    y: &ECP,
-    z: &ECP,
-    x: &BIG,
-) -> ECPProof {
-    let r = random_mod_curve_order(rng);
-
-    let ar = g1mul(&a, &r);
-    let br = g1mul(&b, &r);
+/// H(H(msg) || H(bsn)), which every signature proof is bound to.
+#[cfg(any(feature = "alloc", test))]
+pub(crate) fn message_hash(suite: Ciphersuite, msg: &[u8], bsn: &[u8]) -> [u8; BIG_SIZE] {
+    prehashed_message_hash(suite, &suite.hash(msg), bsn)
+}
 
-    let c = ecp_challenge_equals(Some(message), y, z, a, b, &ar, &br);
-    let mut s = BIG::modmul(&c, x, &CURVE_ORDER_BIG);
-    s.add(&r);
-    s.rmod(&CURVE_ORDER_BIG);
+/// `message_hash` given the digest H(msg).
+fn prehashed_message_hash(
+    suite: Ciphersuite,
+    digest: &[u8; BIG_SIZE],
+    bsn: &[u8],
+) -> [u8; BIG_SIZE] {
+    let mut msg_bsn_hash_data = [0u8; BIG_SIZE * 2];
+    msg_bsn_hash_data[..BIG_SIZE].copy_from_slice(digest);
+    msg_bsn_hash_data[BIG_SIZE..].copy_from_slice(&suite.hash(bsn));
+    suite.hash(&msg_bsn_hash_data)
+}
 
-    ECPProof { c, s }
+/// Starts the transcript of a signature's proof of equality, given the
+/// digest H(msg) of the message.
+pub(crate) fn sign_transcript<B: PairingBackend>(
+    suite: Ciphersuite,
+    digest: &[u8; BIG_SIZE],
+    bsn: &[u8],
+) -> Transcript<B> {
+    let mut transcript = Transcript::<B>::new(suite, PROTOCOL_SIGN);
+    transcript.append_bytes(b"message", &prehashed_message_hash(suite, digest, bsn));
+    transcript
 }
 
-pub fn sign(
+pub fn sign<B: PairingBackend>(
     rng: &mut RAND,
-    gsk: &CredentialBIG,
-    credentials: &UserCredentials,
+    suite: Ciphersuite,
+    gsk: &CredentialBIG<B>,
+    credentials: &UserCredentials<B>,
     msg: &[u8],
     bsn: &[u8],
-) -> Signature {
-    // Randomize credentials for signature
-    let r = random_mod_curve_order(rng);
+) -> Signature<B> {
+    sign_prehashed(rng, suite, gsk, credentials, &suite.hash(msg), bsn)
+}
 
-    let a = g1mul(&credentials.a, &r);
-    let b = g1mul(&credentials.b, &r);
-    let c = g1mul(&credentials.c, &r);
-    let d = g1mul(&credentials.d, &r);
+/// Signs the message whose digest under the suite's hash function is
+/// `digest`. Gives the same signature as `sign` on the message itself.
+pub fn sign_prehashed<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    gsk: &CredentialBIG<B>,
+    credentials: &UserCredentials<B>,
+    digest: &[u8; BIG_SIZE],
+    bsn: &[u8],
+) -> Signature<B> {
+    let r = B::scalar_random(rng);
+    sign_randomized(rng, suite, gsk, credentials, &r, digest, bsn)
+}
 
-    // Map basename to point in G1
-    let bsn_hash = hash256(bsn);
-    let bsn_point = ECP::mapit(&bsn_hash);
-    let nym = g1mul(&bsn_point, &gsk.0);
+/// Signs with credentials randomized by `r`, so that callers can randomize
+/// further credential elements by the same factor.
+pub(crate) fn sign_randomized<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    gsk: &CredentialBIG<B>,
+    credentials: &UserCredentials<B>,
+    r: &B::Scalar,
+    digest: &[u8; BIG_SIZE],
+    bsn: &[u8],
+) -> Signature<B> {
+    let a = B::g1_mul(&credentials.a, r);
+    let b = B::g1_mul(&credentials.b, r);
+    let c = B::g1_mul(&credentials.c, r);
+    let d = B::g1_mul(&credentials.d, r);
 
-    // Compute H(H(msg) || H(bsn)) to be used in proof of equality
-    let mut msg_bsn_hash_data = [0u8; BIG_SIZE * 2];
-    msg_bsn_hash_data[..BIG_SIZE].copy_from_slice(&hash256(msg));
-    msg_bsn_hash_data[BIG_SIZE..].copy_from_slice(&hash256(bsn));
-    let msg_bsn_hash = hash256(&msg_bsn_hash_data);
+    // Map basename to point in G1
+    let bsn_point = suite.hash_to_g1::<B>(bsn);
+    let nym = B::g1_mul(&bsn_point, &gsk.0);
 
-    let proof = make_ecp_proof_equals(rng, &msg_bsn_hash, &b, &bsn_point, &d, &nym, &gsk.0);
+    let transcript = sign_transcript(suite, digest, bsn);
+    let proof = make_ecp_proof_equals(rng, transcript, &b, &bsn_point, &d, &nym, &gsk.0);
     Signature {
         a,
         b,
@@ -66,3 +94,50 @@ pub fn sign(
         proof,
     }
 }
+
+/// Challenge of an unlinkable signature's proof that log_B(D) = gsk, bound
+/// to the message and all randomized credentials.
+pub(crate) fn unlinkable_challenge<B: PairingBackend>(
+    suite: Ciphersuite,
+    msg: &[u8],
+    a: &B::G1,
+    b: &B::G1,
+    c: &B::G1,
+    d: &B::G1,
+    br: &B::G1,
+) -> B::Scalar {
+    let mut transcript = Transcript::<B>::labeled(suite, PROTOCOL_SIGN_UNLINKABLE);
+    transcript.append_bytes(b"message", &suite.hash(msg));
+    transcript.append_ecp(b"a", a);
+    transcript.append_ecp(b"b", b);
+    transcript.append_ecp(b"c", c);
+    transcript.append_ecp(b"d", d);
+    transcript.append_ecp(b"br", br);
+    transcript.challenge()
+}
+
+/// Signs without a basename or nym. Only proves that the signer holds
+/// credentials from the group, so no two signatures can be linked.
+pub fn sign_unlinkable<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    gsk: &CredentialBIG<B>,
+    credentials: &UserCredentials<B>,
+    msg: &[u8],
+) -> UnlinkableSignature<B> {
+    let r = B::scalar_random(rng);
+    let a = B::g1_mul(&credentials.a, &r);
+    let b = B::g1_mul(&credentials.b, &r);
+    let c = B::g1_mul(&credentials.c, &r);
+    let d = B::g1_mul(&credentials.d, &r);
+
+    // D = B ** gsk
+    let nonce = B::scalar_random(rng);
+    let br = B::g1_mul(&b, &nonce);
+    let challenge = unlinkable_challenge::<B>(suite, msg, &a, &b, &c, &d, &br);
+    let proof = ECPProof {
+        c: challenge,
+        s: B::scalar_add(&B::scalar_mul(&challenge, &gsk.0), &nonce),
+    };
+    UnlinkableSignature { a, b, c, d, proof }
+}
@@ -0,0 +1,89 @@
+//! Incremental signing and verification of messages too large to hold in
+//! memory at once.
+
+use brave_miracl::hash256::HASH256;
+use brave_miracl::rand::RAND;
+
+use super::backend::{PairingBackend, BIG_SIZE};
+use super::ciphersuite::{Ciphersuite, HashFunction};
+use super::data::{CredentialBIG, CredentialMetadata, Signature, UserCredentials};
+use super::manager::check_expiry;
+use super::sign::sign_prehashed;
+use super::Result;
+
+/// Computes the digest that `sign_prehashed` and `verify_prehashed` expect,
+/// from a message fed in chunks.
+pub struct MessageHasher {
+    hash: HASH256,
+}
+
+impl MessageHasher {
+    pub fn new(suite: Ciphersuite) -> Self {
+        match suite.hash_function() {
+            HashFunction::Sha256 => Self {
+                hash: HASH256::new(),
+            },
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hash.process_array(chunk);
+    }
+
+    pub fn finalize(mut self) -> [u8; BIG_SIZE] {
+        self.hash.hash()
+    }
+}
+
+/// Signs a message fed in chunks, created by `CredentialManager::signer`.
+/// The signature is the same as from `CredentialManager::sign` on the whole
+/// message.
+pub struct Signer<'a, B: PairingBackend> {
+    rng: &'a mut RAND,
+    suite: Ciphersuite,
+    gsk: &'a CredentialBIG<B>,
+    credentials: &'a UserCredentials<B>,
+    // Checked again when signing, as streaming may take a while
+    metadata: Option<&'a CredentialMetadata>,
+    clock: Option<fn() -> u64>,
+    hasher: MessageHasher,
+}
+
+impl<'a, B: PairingBackend> Signer<'a, B> {
+    pub(crate) fn new(
+        rng: &'a mut RAND,
+        suite: Ciphersuite,
+        gsk: &'a CredentialBIG<B>,
+        credentials: &'a UserCredentials<B>,
+        metadata: Option<&'a CredentialMetadata>,
+        clock: Option<fn() -> u64>,
+    ) -> Self {
+        Self {
+            rng,
+            suite,
+            gsk,
+            credentials,
+            metadata,
+            clock,
+            hasher: MessageHasher::new(suite),
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+
+    /// Signs the message fed so far, or fails with `CredentialsExpired` if
+    /// the credentials expired after the `Signer` was created.
+    pub fn finalize(self, basename: impl AsRef<[u8]>) -> Result<Signature<B>> {
+        check_expiry(self.metadata, self.clock)?;
+        Ok(sign_prehashed(
+            self.rng,
+            self.suite,
+            self.gsk,
+            self.credentials,
+            &self.hasher.finalize(),
+            basename.as_ref(),
+        ))
+    }
+}
@@ -0,0 +1,746 @@
+//! Cross-implementation test vectors.
+//!
+//! `test-vectors/*.json` pins the bytes produced at each protocol step so
+//! that ports of this library can check themselves against it, and
+//! `test-vectors/bls12381/*.json` does the same for the BLS12-381 suite.
+//! Every file is an array of cases whose byte strings are hex encoded.
+//! Random values are drawn from MIRACL's `RAND` seeded with the case's
+//! seed, in the order this crate draws them, so a port that follows the
+//! same order reproduces every output exactly. Negative cases carry the
+//! expected `CredentialError` variant name in `error`.
+//!
+//! After an intentional change to an encoding, regenerate the files with
+//! `cargo test generate_test_vectors -- --ignored`, adding
+//! `--features bls12-381` for the BLS12-381 ones.
+
+use std::fs;
+use std::path::PathBuf;
+
+use brave_miracl::rand::RAND;
+use serde_json::{Map, Value};
+
+use super::backend::{PairingBackend, BIG_SIZE};
+use super::ciphersuite::{Ciphersuite, Curve};
+use super::data::{
+    CredentialBIG, CredentialMetadata, EncodingSizes, GroupPublicKey, JoinRequest, JoinResponse,
+    Signature, UserCredentials,
+};
+use super::issuer::Issuer;
+use super::join::{finish_join, finish_join_with_metadata, start_join};
+use super::sign::{message_hash, sign};
+use super::verify::verify;
+use super::Result;
+
+/// Computes the outputs of a case from its inputs and returns the complete
+/// case, inputs first.
+type Step = fn(&Value) -> Value;
+
+fn steps<B: PairingBackend>() -> [(&'static str, Step); 6] {
+    [
+        ("issuer_keygen", issuer_keygen::<B>),
+        ("join_request", join_request::<B>),
+        ("join_response", join_response::<B>),
+        ("finish_join", finish_join_case::<B>),
+        ("sign", sign_case::<B>),
+        ("verify", verify_case::<B>),
+    ]
+}
+
+fn path(curve: Curve, name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-vectors");
+    let dir = match curve {
+        Curve::Bn254 => dir,
+        Curve::Bls12381 => dir.join(curve.name()),
+    };
+    dir.join(format!("{}.json", name))
+}
+
+/// Accessors for the fields of a case, which panic on malformed files.
+trait Case {
+    fn field(&self, key: &str) -> &Value;
+
+    /// Decodes a hex string field.
+    fn hex(&self, key: &str) -> Vec<u8>;
+}
+
+impl Case for Value {
+    fn field(&self, key: &str) -> &Value {
+        self.get(key)
+            .unwrap_or_else(|| panic!("missing field {}", key))
+    }
+
+    fn hex(&self, key: &str) -> Vec<u8> {
+        let value = self.field(key);
+        let hex = value
+            .as_str()
+            .unwrap_or_else(|| panic!("expected string, got {}", value));
+        hex::decode(hex).unwrap()
+    }
+}
+
+fn rng(seed: &[u8]) -> RAND {
+    let mut rng = RAND::new();
+    rng.seed(seed.len(), seed);
+    rng
+}
+
+fn ciphersuite(case: &Value) -> Ciphersuite {
+    Ciphersuite::try_from(case.field("ciphersuite").as_u64().unwrap() as u8).unwrap()
+}
+
+fn bytes(bytes: &[u8]) -> Value {
+    Value::String(hex::encode(bytes))
+}
+
+/// Encoding of `size` bytes written by `write`.
+fn encode(size: usize, write: impl FnOnce(&mut [u8])) -> Vec<u8> {
+    let mut bytes = vec![0u8; size];
+    write(&mut bytes);
+    bytes
+}
+
+fn scalar<B: PairingBackend>(scalar: &B::Scalar) -> Value {
+    let mut result = [0u8; BIG_SIZE];
+    B::scalar_to_bytes(scalar, &mut result);
+    bytes(&result)
+}
+
+fn point<B: PairingBackend>(point: &B::G1) -> Value {
+    let mut result = vec![0u8; B::G1_SIZE];
+    B::g1_to_bytes(point, &mut result);
+    bytes(&result)
+}
+
+/// Splits a result into its `(value, error)` fields.
+fn outcome(result: Result<Vec<u8>>) -> (Value, Value) {
+    match result {
+        Ok(value) => (bytes(&value), Value::Null),
+        Err(err) => (Value::Null, Value::String(format!("{:?}", err))),
+    }
+}
+
+fn with_outputs(case: &Value, inputs: &[&str], outputs: Vec<(&str, Value)>) -> Value {
+    let fields = inputs
+        .iter()
+        .map(|name| (name.to_string(), case.field(name).clone()))
+        .chain(
+            outputs
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value)),
+        )
+        .collect::<Map<_, _>>();
+    Value::Object(fields)
+}
+
+fn issuer_keygen<B: PairingBackend>(case: &Value) -> Value {
+    let issuer = Issuer::<B>::new(&mut rng(&case.hex("seed")), ciphersuite(case));
+    let (x, y) = issuer.secret_key();
+
+    with_outputs(
+        case,
+        &["description", "ciphersuite", "seed"],
+        vec![
+            ("x", scalar::<B>(x)),
+            ("y", scalar::<B>(y)),
+            (
+                "group_public_key",
+                bytes(&encode(B::GROUP_PUBLIC_KEY_SIZE, |out| {
+                    issuer.public_key().write_bytes(out)
+                })),
+            ),
+        ],
+    )
+}
+
+fn join_request<B: PairingBackend>(case: &Value) -> Value {
+    let suite = ciphersuite(case);
+    let challenge = case.hex("challenge");
+    let result = start_join::<B>(&mut rng(&case.hex("seed")), suite, &challenge);
+
+    with_outputs(
+        case,
+        &["description", "ciphersuite", "seed", "challenge"],
+        vec![
+            ("challenge_hash", bytes(&suite.hash(&challenge))),
+            ("gsk", bytes(&result.gsk.to_bytes())),
+            (
+                "join_request",
+                bytes(&encode(B::JOIN_REQUEST_SIZE, |out| {
+                    result.join_msg.write_bytes(out)
+                })),
+            ),
+        ],
+    )
+}
+
+/// Input names of a case, followed by `metadata` if the case has it.
+fn with_metadata<'a>(case: &Value, inputs: &[&'a str]) -> Vec<&'a str> {
+    let mut inputs = inputs.to_vec();
+    if case.get("metadata").is_some() {
+        inputs.push("metadata");
+    }
+    inputs
+}
+
+fn metadata(case: &Value) -> Option<Result<CredentialMetadata>> {
+    case.get("metadata")
+        .map(|_| CredentialMetadata::try_from(case.hex("metadata").as_slice()))
+}
+
+fn join_response<B: PairingBackend>(case: &Value) -> Value {
+    let issuer = Issuer::<B>::new(&mut rng(&case.hex("issuer_seed")), ciphersuite(case));
+    let request: JoinRequest<B> = case.hex("join_request").as_slice().try_into().unwrap();
+    let (rng, challenge) = (&mut rng(&case.hex("seed")), &case.hex("challenge"));
+    let response = match metadata(case) {
+        Some(metadata) => issuer.join_with_metadata(rng, challenge, &request, &metadata.unwrap()),
+        None => issuer.join(rng, challenge, &request),
+    };
+
+    with_outputs(
+        case,
+        &with_metadata(
+            case,
+            &[
+                "description",
+                "ciphersuite",
+                "issuer_seed",
+                "seed",
+                "challenge",
+                "join_request",
+            ],
+        ),
+        vec![
+            (
+                "group_public_key",
+                bytes(&encode(B::GROUP_PUBLIC_KEY_SIZE, |out| {
+                    issuer.public_key().write_bytes(out)
+                })),
+            ),
+            (
+                "join_response",
+                response.map_or(Value::Null, |response| {
+                    bytes(&encode(B::JOIN_RESPONSE_SIZE, |out| {
+                        response.write_bytes(out)
+                    }))
+                }),
+            ),
+        ],
+    )
+}
+
+fn finish_join_case<B: PairingBackend>(case: &Value) -> Value {
+    let result = (|| {
+        let public_key: GroupPublicKey<B> = case.hex("group_public_key").as_slice().try_into()?;
+        let gsk: CredentialBIG<B> = case.hex("gsk").as_slice().try_into()?;
+        let response: JoinResponse<B> = case.hex("join_response").as_slice().try_into()?;
+        let suite = ciphersuite(case);
+        let credentials = match metadata(case) {
+            Some(metadata) => {
+                finish_join_with_metadata(suite, &public_key, &gsk, response, &metadata?)?
+            }
+            None => finish_join(suite, &public_key, &gsk, response)?,
+        };
+        Ok(encode(B::USER_CREDENTIALS_SIZE, |out| {
+            credentials.write_bytes(out)
+        }))
+    })();
+    let (credentials, error) = outcome(result);
+
+    with_outputs(
+        case,
+        &with_metadata(
+            case,
+            &[
+                "description",
+                "ciphersuite",
+                "group_public_key",
+                "gsk",
+                "join_response",
+            ],
+        ),
+        vec![("credentials", credentials), ("error", error)],
+    )
+}
+
+fn sign_case<B: PairingBackend>(case: &Value) -> Value {
+    let suite = ciphersuite(case);
+    let gsk: CredentialBIG<B> = case.hex("gsk").as_slice().try_into().unwrap();
+    let credentials: UserCredentials<B> = case.hex("credentials").as_slice().try_into().unwrap();
+    let (msg, basename) = (case.hex("msg"), case.hex("basename"));
+
+    let signature = sign(
+        &mut rng(&case.hex("seed")),
+        suite,
+        &gsk,
+        &credentials,
+        &msg,
+        &basename,
+    );
+
+    with_outputs(
+        case,
+        &[
+            "description",
+            "ciphersuite",
+            "seed",
+            "gsk",
+            "credentials",
+            "msg",
+            "basename",
+        ],
+        vec![
+            ("message_hash", bytes(&message_hash(suite, &msg, &basename))),
+            (
+                "basename_point",
+                point::<B>(&suite.hash_to_g1::<B>(&basename)),
+            ),
+            (
+                "nym",
+                bytes(&encode(B::G1_SIZE, |out| signature.write_nym(out))),
+            ),
+            (
+                "signature",
+                bytes(&encode(B::SIGNATURE_SIZE, |out| signature.write_bytes(out))),
+            ),
+        ],
+    )
+}
+
+fn verify_case<B: PairingBackend>(case: &Value) -> Value {
+    let result = (|| {
+        let public_key: GroupPublicKey<B> = case.hex("group_public_key").as_slice().try_into()?;
+        let signature: Signature<B> = case.hex("signature").as_slice().try_into()?;
+        verify(
+            ciphersuite(case),
+            &public_key,
+            &signature,
+            &case.hex("msg"),
+            case.hex("basename"),
+        )?;
+        Ok(Vec::new())
+    })();
+    let (_, error) = outcome(result);
+
+    with_outputs(
+        case,
+        &[
+            "description",
+            "ciphersuite",
+            "group_public_key",
+            "signature",
+            "msg",
+            "basename",
+        ],
+        vec![("error", error)],
+    )
+}
+
+#[test]
+fn test_vectors() {
+    check_test_vectors::<super::Bn254>();
+    #[cfg(feature = "bls12-381")]
+    check_test_vectors::<super::bls12_381::Bls12381>();
+}
+
+fn check_test_vectors<B: PairingBackend>() {
+    for (name, step) in steps::<B>() {
+        let contents = fs::read_to_string(path(B::CURVE, name)).unwrap();
+        let cases: Vec<Value> = serde_json::from_str(&contents).unwrap();
+        assert!(!cases.is_empty(), "{} has no cases", name);
+
+        for case in &cases {
+            assert_eq!(step(case), *case, "{}: {}", name, case.field("description"));
+        }
+    }
+}
+
+/// Collects the input fields of a case; `description` and `ciphersuite`
+/// always come first.
+fn input(description: &str, suite: Ciphersuite, fields: Vec<(&str, Value)>) -> Value {
+    let mut case = vec![
+        (
+            "description".to_string(),
+            Value::String(description.to_string()),
+        ),
+        ("ciphersuite".to_string(), Value::from(suite.id())),
+    ];
+    case.extend(
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value)),
+    );
+    Value::Object(case.into_iter().collect())
+}
+
+/// Adds an input field to a case that has no outputs yet.
+fn with_field(case: Value, name: &str, value: Value) -> Value {
+    match case {
+        Value::Object(mut fields) => {
+            fields.insert(name.to_string(), value);
+            Value::Object(fields)
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn tampered(value: &[u8], index: usize) -> Vec<u8> {
+    let mut value = value.to_vec();
+    value[index] ^= 1;
+    value
+}
+
+#[test]
+#[ignore]
+fn generate_test_vectors() {
+    generate::<super::Bn254>();
+    #[cfg(feature = "bls12-381")]
+    generate::<super::bls12_381::Bls12381>();
+}
+
+fn generate<B: PairingBackend>() {
+    let steps = steps::<B>();
+    let mut files: Vec<Vec<Value>> = vec![Vec::new(); steps.len()];
+    let [keygen_cases, request_cases, response_cases, finish_cases, sign_cases, verify_cases] =
+        &mut files[..]
+    else {
+        unreachable!()
+    };
+
+    const ISSUER_SEED: &[u8] = b"issuer";
+    const OTHER_ISSUER_SEED: &[u8] = b"other issuer";
+    const CHALLENGE: &[u8] = b"challenge";
+
+    for suite in B::CURVE.suites() {
+        let name = format!("{:?}", suite);
+
+        // Issuer keys
+        for seed in [ISSUER_SEED, OTHER_ISSUER_SEED] {
+            keygen_cases.push(issuer_keygen::<B>(&input(
+                &format!("{}: issuer key from seed {}", name, hex::encode(seed)),
+                suite,
+                vec![("seed", bytes(seed))],
+            )));
+        }
+        let public_key = keygen_cases[keygen_cases.len() - 2].hex("group_public_key");
+        let other_public_key = keygen_cases[keygen_cases.len() - 1].hex("group_public_key");
+
+        // Join requests
+        let mut members = Vec::new();
+        for (description, seed, challenge) in [
+            ("join request", &[0u8][..], CHALLENGE),
+            ("join request with an empty challenge", &[1u8][..], &b""[..]),
+        ] {
+            let case = join_request::<B>(&input(
+                &format!("{}: {}", name, description),
+                suite,
+                vec![("seed", bytes(seed)), ("challenge", bytes(challenge))],
+            ));
+            members.push((challenge, case.hex("gsk"), case.hex("join_request")));
+            request_cases.push(case);
+        }
+
+        // Join responses
+        let response_input = |description: &str, challenge: &[u8], request: &[u8]| {
+            input(
+                &format!("{}: {}", name, description),
+                suite,
+                vec![
+                    ("issuer_seed", bytes(ISSUER_SEED)),
+                    ("seed", bytes(b"issuance")),
+                    ("challenge", bytes(challenge)),
+                    ("join_request", bytes(request)),
+                ],
+            )
+        };
+        let mut responses = Vec::new();
+        for (i, (challenge, _, request)) in members.iter().enumerate() {
+            let case = join_response::<B>(&response_input(
+                &format!("join response for member {}", i),
+                challenge,
+                request,
+            ));
+            responses.push(case.hex("join_response"));
+            response_cases.push(case);
+        }
+        let (challenge, gsk, request) = &members[0];
+        let group = GroupPublicKey::<B>::try_from(public_key.as_slice())
+            .unwrap()
+            .id();
+        let metadata = CredentialMetadata::new(1_709_208_000, 1_711_886_400, group).to_bytes();
+        let case = join_response::<B>(&with_field(
+            response_input("join response bound to metadata", challenge, request),
+            "metadata",
+            bytes(&metadata),
+        ));
+        let metadata_response = case.hex("join_response");
+        response_cases.push(case);
+        response_cases.push(join_response::<B>(&response_input(
+            "join request for a different challenge is rejected",
+            b"other challenge",
+            request,
+        )));
+        response_cases.push(join_response::<B>(&response_input(
+            "join request with a tampered proof is rejected",
+            challenge,
+            &tampered(request, request.len() - 1),
+        )));
+
+        // Finishing the join
+        let finish_input = |description: &str, public_key: &[u8], gsk: &[u8], response: &[u8]| {
+            finish_join_case::<B>(&input(
+                &format!("{}: {}", name, description),
+                suite,
+                vec![
+                    ("group_public_key", bytes(public_key)),
+                    ("gsk", bytes(gsk)),
+                    ("join_response", bytes(response)),
+                ],
+            ))
+        };
+        for (i, ((_, gsk, _), response)) in members.iter().zip(&responses).enumerate() {
+            finish_cases.push(finish_input(
+                &format!("credentials for member {}", i),
+                &public_key,
+                gsk,
+                response,
+            ));
+        }
+        let credentials = finish_cases[finish_cases.len() - 2].hex("credentials");
+        let response = &responses[0];
+        for (description, public_key, gsk, response) in [
+            (
+                "join response with a tampered proof",
+                public_key.clone(),
+                gsk.clone(),
+                tampered(response, response.len() - 1),
+            ),
+            (
+                "join response issued to another member",
+                public_key.clone(),
+                members[1].1.clone(),
+                response.clone(),
+            ),
+            (
+                "join response checked against another issuer's key",
+                other_public_key.clone(),
+                gsk.clone(),
+                response.clone(),
+            ),
+            (
+                "group public key with a tampered proof",
+                tampered(&public_key, B::GROUP_PUBLIC_KEY_SIZE - 1),
+                gsk.clone(),
+                response.clone(),
+            ),
+            (
+                "truncated join response",
+                public_key.clone(),
+                gsk.clone(),
+                response[1..].to_vec(),
+            ),
+            (
+                "truncated group public key",
+                public_key[1..].to_vec(),
+                gsk.clone(),
+                response.clone(),
+            ),
+        ] {
+            finish_cases.push(finish_input(description, &public_key, &gsk, &response));
+        }
+        let mut later = metadata;
+        later[15] ^= 1;
+        for (description, response, metadata) in [
+            (
+                "credentials bound to metadata",
+                &metadata_response,
+                &metadata[..],
+            ),
+            (
+                "metadata changed after issuance",
+                &metadata_response,
+                &later[..],
+            ),
+            (
+                "join response without metadata checked with metadata",
+                response,
+                &metadata[..],
+            ),
+            ("truncated metadata", &metadata_response, &metadata[1..]),
+        ] {
+            finish_cases.push(finish_join_case::<B>(&input(
+                &format!("{}: {}", name, description),
+                suite,
+                vec![
+                    ("group_public_key", bytes(&public_key)),
+                    ("gsk", bytes(gsk)),
+                    ("join_response", bytes(response)),
+                    ("metadata", bytes(metadata)),
+                ],
+            )));
+        }
+        finish_cases.push(finish_input(
+            "join response bound to metadata checked without it",
+            &public_key,
+            gsk,
+            &metadata_response,
+        ));
+
+        // Signatures
+        let long_msg = vec![0x61u8; 1000];
+        let mut signatures = Vec::new();
+        for (description, msg, basename) in [
+            ("signature", &b"message"[..], &b"basename"[..]),
+            (
+                "second message under the same basename",
+                b"message2",
+                b"basename",
+            ),
+            (
+                "same message under another basename",
+                b"message",
+                b"basename2",
+            ),
+            ("empty message", b"", b"basename"),
+            ("empty basename", b"message", b""),
+            ("long message", &long_msg[..], b"basename"),
+        ] {
+            let case = sign_case::<B>(&input(
+                &format!("{}: {}", name, description),
+                suite,
+                vec![
+                    ("seed", bytes(&[0u8])),
+                    ("gsk", bytes(gsk)),
+                    ("credentials", bytes(&credentials)),
+                    ("msg", bytes(msg)),
+                    ("basename", bytes(basename)),
+                ],
+            ));
+            signatures.push((msg.to_vec(), basename.to_vec(), case.hex("signature")));
+            sign_cases.push(case);
+        }
+
+        // Verification
+        let verify_input = |description: &str,
+                            suite: Ciphersuite,
+                            public_key: &[u8],
+                            signature: &[u8],
+                            msg: &[u8],
+                            basename: &[u8]| {
+            verify_case::<B>(&input(
+                &format!("{}: {}", name, description),
+                suite,
+                vec![
+                    ("group_public_key", bytes(public_key)),
+                    ("signature", bytes(signature)),
+                    ("msg", bytes(msg)),
+                    ("basename", bytes(basename)),
+                ],
+            ))
+        };
+        for (i, (msg, basename, signature)) in signatures.iter().enumerate() {
+            verify_cases.push(verify_input(
+                &format!("valid signature {}", i),
+                suite,
+                &public_key,
+                signature,
+                msg,
+                basename,
+            ));
+        }
+        let (msg, basename, signature) = &signatures[0];
+        // None on curves with a single suite
+        let other_suite = B::CURVE.suites().find(|other| *other != suite);
+        let mut other_nym = signature.clone();
+        let nym = B::G1_SIZE * 4..B::G1_SIZE * 5;
+        other_nym[nym.clone()].copy_from_slice(&signatures[2].2[nym]);
+        let mut infinity_a = signature.clone();
+        infinity_a[1..B::G1_SIZE].fill(0);
+        for (description, suite, public_key, signature, msg, basename) in [
+            (
+                "wrong message",
+                Some(suite),
+                public_key.clone(),
+                signature.clone(),
+                b"message2".to_vec(),
+                basename.clone(),
+            ),
+            (
+                "wrong basename",
+                Some(suite),
+                public_key.clone(),
+                signature.clone(),
+                msg.clone(),
+                b"basename2".to_vec(),
+            ),
+            (
+                "verified under another ciphersuite",
+                other_suite,
+                public_key.clone(),
+                signature.clone(),
+                msg.clone(),
+                basename.clone(),
+            ),
+            (
+                "verified against another issuer's key",
+                Some(suite),
+                other_public_key.clone(),
+                signature.clone(),
+                msg.clone(),
+                basename.clone(),
+            ),
+            (
+                "pseudonym swapped for another basename's",
+                Some(suite),
+                public_key.clone(),
+                other_nym,
+                msg.clone(),
+                basename.clone(),
+            ),
+            (
+                "tampered proof",
+                Some(suite),
+                public_key.clone(),
+                tampered(signature, signature.len() - 1),
+                msg.clone(),
+                basename.clone(),
+            ),
+            (
+                "A is not a curve point",
+                Some(suite),
+                public_key.clone(),
+                infinity_a,
+                msg.clone(),
+                basename.clone(),
+            ),
+            (
+                "truncated signature",
+                Some(suite),
+                public_key.clone(),
+                signature[1..].to_vec(),
+                msg.clone(),
+                basename.clone(),
+            ),
+        ] {
+            let Some(suite) = suite else {
+                continue;
+            };
+            verify_cases.push(verify_input(
+                description,
+                suite,
+                &public_key,
+                &signature,
+                &msg,
+                &basename,
+            ));
+        }
+    }
+
+    fs::create_dir_all(path(B::CURVE, "").parent().unwrap()).unwrap();
+    for ((name, _), cases) in steps.iter().zip(files) {
+        fs::write(
+            path(B::CURVE, name),
+            serde_json::to_string_pretty(&cases).unwrap() + "\n",
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,700 @@
+//! k-of-n threshold issuance.
+//!
+//! A dealer splits the issuer key between n nodes as Shamir shares of x,
+//! z = 1 / y and w = x / y of degree k - 1, and then forgets it. Any k or
+//! more nodes answer a join request in two rounds, relayed by a combiner
+//! that may be the member itself:
+//!
+//! 1. Node i draws k_i and u_i and commits to B_i = k_i * G1, D_i = k_i * Q,
+//!    T_i = u_i * G1 and U_i = u_i * Q, with a proof that
+//!    log_G1(B_i) = log_Q(D_i).
+//! 2. With e_i hashed from all commitments, B = sum(e_i * B_i) and
+//!    D = sum(e_i * D_i), so that no participant can steer B, while
+//!    T = sum(T_i) and U = sum(U_i). Node i returns A_i = z_i * B,
+//!    C_i = w_i * B + x_i * D and s_i = c * e_i * k_i + u_i, where c is the
+//!    challenge of the proof that log_G1(B) = log_Q(D).
+//!
+//! Interpolating A and C and summing s gives A = B / y, C = x * (A + D) and
+//! a proof for k = sum(e_i * k_i): the join response of a single issuer
+//! that drew r = k / y, which `finish_join` accepts unchanged. Nobody learns
+//! r, which would give away y * G1 and, over two joins, the whole key.
+//! Nodes only sign a (B, D) built from commitments whose proofs check out,
+//! so D is always log_G1(B) * Q.
+//!
+//! `deal_issuer_key` uses a trusted dealer, which learns x and y before
+//! splitting them. The `dkg` module generates the shares without one.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use brave_miracl::rand::RAND;
+
+use super::backend::{PairingBackend, BIG_SIZE};
+use super::ciphersuite::Ciphersuite;
+use super::data::{
+    big_from_bytes, ecp_from_bytes, impl_fixed_size, write_ecps, ECPProof, EpochGroupPublicKey,
+    GroupPublicKey, JoinRequest, JoinResponse, UserCredentials, ECP_PROOF_SIZE,
+};
+use super::join::{join_request_is_valid, join_response_transcript, make_group_public_key};
+use super::migrate::assign_epoch;
+use super::transcript::{Transcript, PROTOCOL_THRESHOLD_JOIN};
+use super::util::{
+    ecp_challenge_equals, g1_equals, make_ecp_proof_equals, scalar_from_u64,
+    verify_ecp_proof_equals,
+};
+use super::verify::randomized_credentials_are_valid;
+use super::{CredentialError, Result};
+
+/// Sizes of the messages of threshold issuance on the curve of a backend.
+pub(crate) trait ThresholdSizes: PairingBackend {
+    const JOIN_COMMITMENT_SIZE: usize = 2 + Self::G1_SIZE * 4 + ECP_PROOF_SIZE;
+    const PARTIAL_JOIN_RESPONSE_SIZE: usize = 2 + Self::G1_SIZE * 2 + BIG_SIZE;
+}
+
+impl<B: PairingBackend> ThresholdSizes for B {}
+
+/// One node's share of the issuer key. Nodes are numbered from 1.
+pub struct IssuerKeyShare<B: PairingBackend> {
+    pub(crate) suite: Ciphersuite,
+    pub(crate) index: u16,
+    pub(crate) threshold: u16,
+    pub(crate) nodes: u16,
+    pub(crate) x: B::Scalar,
+    // Shares of 1 / y and x / y
+    pub(crate) z: B::Scalar,
+    pub(crate) w: B::Scalar,
+}
+
+/// First-round message of a node.
+pub struct JoinCommitment<B: PairingBackend> {
+    index: u16,
+    b: B::G1,
+    d: B::G1,
+    t: B::G1,
+    u: B::G1,
+    // Proof that log_G1(b) = log_Q(d)
+    proof: ECPProof<B>,
+}
+
+/// Secret state of a node between the two rounds, tied to the join request
+/// it was committed for. The second round consumes it, so its randomness is
+/// never used twice.
+pub struct JoinNonce<B: PairingBackend> {
+    commitment: Vec<u8>,
+    q: B::G1,
+    k: B::Scalar,
+    u: B::Scalar,
+}
+
+/// Second-round message of a node.
+pub struct PartialJoinResponse<B: PairingBackend> {
+    index: u16,
+    a: B::G1,
+    c: B::G1,
+    s: B::Scalar,
+}
+
+/// Values of a session that every node and the combiner derive from the
+/// same commitments.
+struct Session<B: PairingBackend> {
+    coefficients: Vec<B::Scalar>,
+    b: B::G1,
+    d: B::G1,
+    c: B::Scalar,
+}
+
+pub(crate) fn zero<B: PairingBackend>() -> B::Scalar {
+    B::scalar_from_bytes(&[0u8; BIG_SIZE])
+}
+
+pub(crate) fn evaluate<B: PairingBackend>(coefficients: &[B::Scalar], at: &B::Scalar) -> B::Scalar {
+    coefficients
+        .iter()
+        .rev()
+        .fold(zero::<B>(), |value, coefficient| {
+            B::scalar_add(&B::scalar_mul(&value, at), coefficient)
+        })
+}
+
+/// Lagrange coefficient at 0 of `index` within `indices`.
+pub(crate) fn lagrange_coefficient<B: PairingBackend>(
+    index: u16,
+    indices: impl Iterator<Item = u16>,
+) -> B::Scalar {
+    let i = scalar_from_u64::<B>(index.into());
+    let one = scalar_from_u64::<B>(1);
+    let (numerator, denominator) =
+        indices
+            .filter(|&j| j != index)
+            .fold((one, one), |(numerator, denominator), j| {
+                let j = scalar_from_u64::<B>(j.into());
+                let difference = B::scalar_add(&j, &B::scalar_neg(&i));
+                (
+                    B::scalar_mul(&numerator, &j),
+                    B::scalar_mul(&denominator, &difference),
+                )
+            });
+    B::scalar_mul(&numerator, &B::scalar_inv(&denominator))
+}
+
+/// Splits a fresh issuer key between `nodes` nodes, any `threshold` of
+/// which can issue credentials together. The key itself never leaves this
+/// function, but whoever runs it sees x and y and must be trusted to erase
+/// them; `DkgParty` avoids that.
+pub fn deal_issuer_key<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    threshold: u16,
+    nodes: u16,
+) -> Result<(GroupPublicKey<B>, Vec<IssuerKeyShare<B>>)> {
+    deal::<B, _>(rng, suite, threshold, nodes, |_, public_key, _, _| {
+        public_key
+    })
+}
+
+/// Like `deal_issuer_key`, for the key of `epoch` in a rotation, which
+/// `start_migration` and `verify_migration_request` take.
+pub fn deal_epoch_issuer_key<B: PairingBackend>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    epoch: u64,
+    threshold: u16,
+    nodes: u16,
+) -> Result<(EpochGroupPublicKey<B>, Vec<IssuerKeyShare<B>>)> {
+    deal::<B, _>(rng, suite, threshold, nodes, |rng, public_key, x, y| {
+        assign_epoch(rng, suite, epoch, public_key, x, y)
+    })
+}
+
+fn deal<B: PairingBackend, K>(
+    rng: &mut RAND,
+    suite: Ciphersuite,
+    threshold: u16,
+    nodes: u16,
+    public_key: impl FnOnce(&mut RAND, GroupPublicKey<B>, &B::Scalar, &B::Scalar) -> K,
+) -> Result<(K, Vec<IssuerKeyShare<B>>)> {
+    if threshold == 0 || threshold > nodes {
+        return Err(CredentialError::ThresholdIssuance);
+    }
+
+    let x = B::scalar_random(rng);
+    let y = B::scalar_random(rng);
+    let key = make_group_public_key::<B>(rng, suite, &x, &y);
+    let public_key = public_key(rng, key, &x, &y);
+
+    // Inversion is not constant time, so invert y behind a random mask
+    let mask = B::scalar_random(rng);
+    let z = B::scalar_mul(&mask, &B::scalar_inv(&B::scalar_mul(&y, &mask)));
+    let w = B::scalar_mul(&x, &z);
+
+    let [x, z, w] = [x, z, w].map(|secret| {
+        let mut coefficients = Vec::with_capacity(threshold.into());
+        coefficients.push(secret);
+        coefficients.extend((1..threshold).map(|_| B::scalar_random(rng)));
+        coefficients
+    });
+    let shares = (1..=nodes)
+        .map(|index| {
+            let at = scalar_from_u64::<B>(index.into());
+            IssuerKeyShare {
+                suite,
+                index,
+                threshold,
+                nodes,
+                x: evaluate::<B>(&x, &at),
+                z: evaluate::<B>(&z, &at),
+                w: evaluate::<B>(&w, &at),
+            }
+        })
+        .collect();
+
+    Ok((public_key, shares))
+}
+
+/// Transcript of node `index`'s proof that log_G1(B_i) = log_Q(D_i).
+fn commitment_transcript<B: PairingBackend>(suite: Ciphersuite, index: u16) -> Transcript<B> {
+    let mut transcript = Transcript::<B>::labeled(suite, PROTOCOL_THRESHOLD_JOIN);
+    transcript.append_bytes(b"commitment index", &index.to_be_bytes());
+    transcript
+}
+
+fn session<B: PairingBackend>(
+    suite: Ciphersuite,
+    q: &B::G1,
+    commitments: &[JoinCommitment<B>],
+) -> Result<Session<B>> {
+    let g1 = B::g1_generator();
+    for (i, commitment) in commitments.iter().enumerate() {
+        if commitment.index == 0
+            || commitments[..i]
+                .iter()
+                .any(|other| other.index == commitment.index)
+            || !verify_ecp_proof_equals::<B>(
+                commitment_transcript::<B>(suite, commitment.index),
+                &g1,
+                q,
+                &commitment.b,
+                &commitment.d,
+                &commitment.proof,
+            )
+        {
+            return Err(CredentialError::ThresholdIssuance);
+        }
+    }
+
+    let mut transcript = Transcript::<B>::labeled(suite, PROTOCOL_THRESHOLD_JOIN);
+    transcript.append_ecp(b"q", q);
+    for commitment in commitments {
+        transcript.append_bytes(b"commitment", &commitment.encoded());
+    }
+    let mut digest = [0u8; BIG_SIZE];
+    B::scalar_to_bytes(&transcript.challenge(), &mut digest);
+
+    let coefficients: Vec<_> = commitments
+        .iter()
+        .map(|commitment| {
+            let mut transcript = Transcript::<B>::labeled(suite, PROTOCOL_THRESHOLD_JOIN);
+            transcript.append_bytes(b"commitments", &digest);
+            transcript.append_bytes(b"index", &commitment.index.to_be_bytes());
+            transcript.challenge()
+        })
+        .collect();
+
+    let mut b = B::g1_infinity();
+    let mut d = B::g1_infinity();
+    let mut t = B::g1_infinity();
+    let mut u = B::g1_infinity();
+    for (commitment, e) in commitments.iter().zip(&coefficients) {
+        b = B::g1_add(&b, &B::g1_mul(&commitment.b, e));
+        d = B::g1_add(&d, &B::g1_mul(&commitment.d, e));
+        t = B::g1_add(&t, &commitment.t);
+        u = B::g1_add(&u, &commitment.u);
+    }
+    if B::g1_is_infinity(&b) {
+        return Err(CredentialError::ThresholdIssuance);
+    }
+
+    let c = ecp_challenge_equals::<B>(
+        join_response_transcript::<B>(suite, None),
+        &b,
+        &d,
+        &g1,
+        q,
+        &t,
+        &u,
+    );
+
+    Ok(Session {
+        coefficients,
+        b,
+        d,
+        c,
+    })
+}
+
+impl<B: PairingBackend> IssuerKeyShare<B> {
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// First round: checks the join request against `challenge` and commits
+    /// to this node's randomness for it.
+    pub fn commit(
+        &self,
+        rng: &mut RAND,
+        challenge: &[u8],
+        req: &JoinRequest<B>,
+    ) -> Result<(JoinNonce<B>, JoinCommitment<B>)> {
+        if !join_request_is_valid::<B>(self.suite, challenge, req) {
+            return Err(CredentialError::ThresholdIssuance);
+        }
+
+        let k = B::scalar_random(rng);
+        let u = B::scalar_random(rng);
+        let g1 = B::g1_generator();
+        let b = B::g1_mul(&g1, &k);
+        let d = B::g1_mul(&req.q, &k);
+        let proof = make_ecp_proof_equals::<B>(
+            rng,
+            commitment_transcript::<B>(self.suite, self.index),
+            &g1,
+            &req.q,
+            &b,
+            &d,
+            &k,
+        );
+        let commitment = JoinCommitment {
+            index: self.index,
+            b,
+            d,
+            t: B::g1_mul(&g1, &u),
+            u: B::g1_mul(&req.q, &u),
+            proof,
+        };
+        let nonce = JoinNonce {
+            commitment: commitment.encoded(),
+            q: req.q.clone(),
+            k,
+            u,
+        };
+        Ok((nonce, commitment))
+    }
+
+    /// Second round, given the commitments of every node taking part,
+    /// including this one's. `req` must be the request that `nonce` was
+    /// committed for, and every node index must be at most `nodes`.
+    pub fn respond(
+        &self,
+        nonce: JoinNonce<B>,
+        req: &JoinRequest<B>,
+        commitments: &[JoinCommitment<B>],
+    ) -> Result<PartialJoinResponse<B>> {
+        let position = commitments
+            .iter()
+            .position(|commitment| commitment.index == self.index)
+            .ok_or(CredentialError::ThresholdIssuance)?;
+        if commitments.len() < self.threshold.into()
+            || commitments
+                .iter()
+                .any(|commitment| commitment.index > self.nodes)
+            || commitments[position].encoded() != nonce.commitment
+            || !g1_equals::<B>(&req.q, &nonce.q)
+        {
+            return Err(CredentialError::ThresholdIssuance);
+        }
+
+        let session = session::<B>(self.suite, &req.q, commitments)?;
+        let e = &session.coefficients[position];
+
+        let c = B::g1_add(
+            &B::g1_mul(&session.b, &self.w),
+            &B::g1_mul(&session.d, &self.x),
+        );
+        let ek = B::scalar_mul(e, &nonce.k);
+        Ok(PartialJoinResponse {
+            index: self.index,
+            a: B::g1_mul(&session.b, &self.z),
+            c,
+            s: B::scalar_add(&B::scalar_mul(&session.c, &ek), &nonce.u),
+        })
+    }
+}
+
+/// Assembles the join response from the second-round messages of every node
+/// in `commitments`, and checks it against the group public key.
+pub fn combine_join_response<B: PairingBackend>(
+    suite: Ciphersuite,
+    public_key: &GroupPublicKey<B>,
+    req: &JoinRequest<B>,
+    commitments: &[JoinCommitment<B>],
+    partials: &[PartialJoinResponse<B>],
+) -> Result<JoinResponse<B>> {
+    if partials.len() != commitments.len() {
+        return Err(CredentialError::ThresholdIssuance);
+    }
+    let session = session::<B>(suite, &req.q, commitments)?;
+
+    let mut a = B::g1_infinity();
+    let mut c = B::g1_infinity();
+    let mut s = zero::<B>();
+    for commitment in commitments {
+        let partial = partials
+            .iter()
+            .find(|partial| partial.index == commitment.index)
+            .ok_or(CredentialError::ThresholdIssuance)?;
+        let lambda = lagrange_coefficient::<B>(
+            partial.index,
+            commitments.iter().map(|commitment| commitment.index),
+        );
+        a = B::g1_add(&a, &B::g1_mul(&partial.a, &lambda));
+        c = B::g1_add(&c, &B::g1_mul(&partial.c, &lambda));
+        s = B::scalar_add(&s, &partial.s);
+    }
+
+    let resp = JoinResponse {
+        cred: UserCredentials {
+            a,
+            b: session.b,
+            c,
+            d: session.d,
+        },
+        proof: ECPProof { c: session.c, s },
+    };
+
+    let cred = &resp.cred;
+    match randomized_credentials_are_valid::<B>(public_key, &cred.a, &cred.b, &cred.c, &cred.d)
+        && verify_ecp_proof_equals::<B>(
+            join_response_transcript::<B>(suite, None),
+            &B::g1_generator(),
+            &req.q,
+            &cred.b,
+            &cred.d,
+            &resp.proof,
+        ) {
+        true => Ok(resp),
+        false => Err(CredentialError::ThresholdIssuance),
+    }
+}
+
+impl<B: PairingBackend> JoinCommitment<B> {
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..2].copy_from_slice(&self.index.to_be_bytes());
+        write_ecps::<B>(&[&self.b, &self.d, &self.t, &self.u], &mut bytes[2..]);
+        bytes[2 + B::G1_SIZE * 4..].copy_from_slice(&self.proof.to_bytes());
+    }
+
+    fn encoded(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; B::JOIN_COMMITMENT_SIZE];
+        self.write_bytes(&mut bytes);
+        bytes
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for JoinCommitment<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != B::JOIN_COMMITMENT_SIZE {
+            return Err(CredentialError::BadThresholdMessage);
+        }
+
+        let point =
+            |i: usize| ecp_from_bytes::<B>(&bytes[2 + B::G1_SIZE * i..2 + B::G1_SIZE * (i + 1)]);
+        Ok(JoinCommitment {
+            index: u16::from_be_bytes([bytes[0], bytes[1]]),
+            b: point(0)?,
+            d: point(1)?,
+            t: point(2)?,
+            u: point(3)?,
+            proof: ECPProof::try_from(&bytes[2 + B::G1_SIZE * 4..])?,
+        })
+    }
+}
+
+impl<B: PairingBackend> PartialJoinResponse<B> {
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    fn write_bytes(&self, bytes: &mut [u8]) {
+        bytes[..2].copy_from_slice(&self.index.to_be_bytes());
+        write_ecps::<B>(&[&self.a, &self.c], &mut bytes[2..]);
+        B::scalar_to_bytes(&self.s, &mut bytes[2 + B::G1_SIZE * 2..]);
+    }
+}
+
+impl<B: PairingBackend> TryFrom<&[u8]> for PartialJoinResponse<B> {
+    type Error = CredentialError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != B::PARTIAL_JOIN_RESPONSE_SIZE {
+            return Err(CredentialError::BadThresholdMessage);
+        }
+
+        Ok(PartialJoinResponse {
+            index: u16::from_be_bytes([bytes[0], bytes[1]]),
+            a: ecp_from_bytes::<B>(&bytes[2..2 + B::G1_SIZE])?,
+            c: ecp_from_bytes::<B>(&bytes[2 + B::G1_SIZE..2 + B::G1_SIZE * 2])?,
+            s: big_from_bytes::<B>(&bytes[2 + B::G1_SIZE * 2..])?,
+        })
+    }
+}
+
+impl_fixed_size! {
+    JoinCommitment::to_bytes(write_bytes) -> JOIN_COMMITMENT_SIZE;
+    PartialJoinResponse::to_bytes(write_bytes) -> PARTIAL_JOIN_RESPONSE_SIZE;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ciphersuite::Curve;
+    use crate::issuer::rng;
+    use crate::join::{finish_join, start_join};
+    use crate::sign::sign;
+    use crate::verify::verify;
+    use crate::{
+        IssuerKeyShare, JoinCommitment, JoinRequest, PartialJoinResponse, StartJoinResult, ECP_SIZE,
+    };
+
+    const CHALLENGE: &[u8] = b"challenge";
+
+    /// Runs both rounds with the nodes at `indices`.
+    fn issue(
+        rng: &mut RAND,
+        shares: &[IssuerKeyShare],
+        indices: &[u16],
+        req: &JoinRequest,
+    ) -> (Vec<JoinCommitment>, Vec<PartialJoinResponse>) {
+        let (nonces, commitments): (Vec<_>, Vec<_>) = indices
+            .iter()
+            .map(|&i| shares[i as usize - 1].commit(rng, CHALLENGE, req).unwrap())
+            .unzip();
+        let partials = indices
+            .iter()
+            .zip(nonces)
+            .map(|(&i, nonce)| {
+                shares[i as usize - 1]
+                    .respond(nonce, req, &commitments)
+                    .unwrap()
+            })
+            .collect();
+        (commitments, partials)
+    }
+
+    #[test]
+    fn test_threshold_issuance() {
+        for suite in Curve::Bn254.suites() {
+            let mut rng = rng();
+            let (public_key, shares) = deal_issuer_key(&mut rng, suite, 3, 5).unwrap();
+
+            for indices in [&[1, 3, 5][..], &[5, 2, 4, 3]] {
+                let StartJoinResult { gsk, join_msg } = start_join(&mut rng, suite, CHALLENGE);
+                let (commitments, partials) = issue(&mut rng, &shares, indices, &join_msg);
+                let response =
+                    combine_join_response(suite, &public_key, &join_msg, &commitments, &partials)
+                        .unwrap();
+
+                let credentials = finish_join(suite, &public_key, &gsk, response).unwrap();
+                let signature = sign(&mut rng, suite, &gsk, &credentials, b"message", b"bsn");
+                verify(suite, &public_key, &signature, b"message", b"bsn").unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_threshold_epoch_key() {
+        let suite = Ciphersuite::Bn254Sha256V1;
+        let mut rng = rng();
+        let (epoch_key, shares) =
+            deal_epoch_issuer_key::<crate::Bn254>(&mut rng, suite, 3, 2, 3).unwrap();
+        let bytes = epoch_key.to_bytes();
+        let decoded = crate::EpochGroupPublicKey::try_from(&bytes[..]).unwrap();
+        assert_eq!(decoded.epoch(), 3);
+
+        let public_key = decoded.key();
+        let StartJoinResult { gsk, join_msg } = start_join(&mut rng, suite, CHALLENGE);
+        let (commitments, partials) = issue(&mut rng, &shares, &[1, 3], &join_msg);
+        let response =
+            combine_join_response(suite, public_key, &join_msg, &commitments, &partials).unwrap();
+        assert!(finish_join(suite, public_key, &gsk, response).is_ok());
+    }
+
+    #[test]
+    fn test_threshold_sessions_are_checked() {
+        let suite = Ciphersuite::Bn254Sha256V1;
+        let mut rng = rng();
+        assert!(deal_issuer_key::<crate::Bn254>(&mut rng, suite, 0, 3).is_err());
+        assert!(deal_issuer_key::<crate::Bn254>(&mut rng, suite, 4, 3).is_err());
+        let (public_key, shares) = deal_issuer_key(&mut rng, suite, 2, 3).unwrap();
+        let StartJoinResult { join_msg, .. } = start_join(&mut rng, suite, CHALLENGE);
+
+        assert!(matches!(
+            shares[0].commit(&mut rng, b"other", &join_msg),
+            Err(CredentialError::ThresholdIssuance)
+        ));
+
+        // Too few nodes, or a list that does not hold this node's commitment
+        let (nonce, commitment) = shares[0].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        assert!(matches!(
+            shares[0].respond(nonce, &join_msg, &[commitment]),
+            Err(CredentialError::ThresholdIssuance)
+        ));
+        let (nonce, _) = shares[0].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        let (_, replaced) = shares[0].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        let (_, other) = shares[1].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        assert!(matches!(
+            shares[0].respond(nonce, &join_msg, &[replaced, other]),
+            Err(CredentialError::ThresholdIssuance)
+        ));
+
+        // Duplicate nodes
+        let (nonce, commitment) = shares[0].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        let duplicate = JoinCommitment::try_from(commitment.to_bytes().as_slice()).unwrap();
+        assert!(matches!(
+            shares[0].respond(nonce, &join_msg, &[commitment, duplicate]),
+            Err(CredentialError::ThresholdIssuance)
+        ));
+
+        // A node index beyond the number of nodes dealt to
+        let (nonce, commitment) = shares[0].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        let (_, others) = deal_issuer_key::<crate::Bn254>(&mut rng, suite, 2, 4).unwrap();
+        let (_, outsider) = others[3].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        assert!(matches!(
+            shares[0].respond(nonce, &join_msg, &[commitment, outsider]),
+            Err(CredentialError::ThresholdIssuance)
+        ));
+
+        // A nonce only answers the request it was committed for
+        let StartJoinResult {
+            join_msg: other, ..
+        } = start_join(&mut rng, suite, CHALLENGE);
+        let (nonce, commitment) = shares[0].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        let (_, second) = shares[1].commit(&mut rng, CHALLENGE, &other).unwrap();
+        assert!(matches!(
+            shares[0].respond(nonce, &other, &[commitment, second]),
+            Err(CredentialError::ThresholdIssuance)
+        ));
+
+        // D_i must be k_i * Q for the B_i = k_i * G1 of the commitment
+        let (nonce, commitment) = shares[0].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        let (_, second) = shares[1].commit(&mut rng, CHALLENGE, &join_msg).unwrap();
+        let mut bytes = second.to_bytes();
+        bytes.copy_within(2..2 + ECP_SIZE, 2 + ECP_SIZE);
+        let forged = JoinCommitment::try_from(bytes.as_slice()).unwrap();
+        assert!(matches!(
+            shares[0].respond(nonce, &join_msg, &[commitment, forged]),
+            Err(CredentialError::ThresholdIssuance)
+        ));
+
+        // The combiner needs a partial response from every committed node
+        let (commitments, partials) = issue(&mut rng, &shares, &[1, 3], &join_msg);
+        assert!(matches!(
+            combine_join_response(suite, &public_key, &join_msg, &commitments, &partials[..1]),
+            Err(CredentialError::ThresholdIssuance)
+        ));
+        let mut bytes = partials[1].to_bytes();
+        bytes[1] = 2;
+        let wrong_index = [
+            PartialJoinResponse::try_from(partials[0].to_bytes().as_slice()).unwrap(),
+            PartialJoinResponse::try_from(bytes.as_slice()).unwrap(),
+        ];
+        assert!(matches!(
+            combine_join_response(suite, &public_key, &join_msg, &commitments, &wrong_index),
+            Err(CredentialError::ThresholdIssuance)
+        ));
+        assert!(
+            combine_join_response(suite, &public_key, &join_msg, &commitments, &partials).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_threshold_encoding() {
+        let suite = Ciphersuite::Bn254Sha256V1;
+        let mut rng = rng();
+        let (_, shares) = deal_issuer_key(&mut rng, suite, 2, 2).unwrap();
+        let StartJoinResult { join_msg, .. } = start_join(&mut rng, suite, CHALLENGE);
+        let (commitments, partials) = issue(&mut rng, &shares, &[1, 2], &join_msg);
+
+        let bytes = commitments[1].to_bytes();
+        let commitment = JoinCommitment::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(commitment.index(), 2);
+        assert_eq!(commitment.to_bytes(), bytes);
+        assert!(matches!(
+            JoinCommitment::try_from(&bytes[1..]),
+            Err(CredentialError::BadThresholdMessage)
+        ));
+
+        let bytes = partials[1].to_bytes();
+        let partial = PartialJoinResponse::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(partial.index(), 2);
+        assert_eq!(partial.to_bytes(), bytes);
+        assert!(matches!(
+            PartialJoinResponse::try_from(&bytes[1..]),
+            Err(CredentialError::BadThresholdMessage)
+        ));
+    }
+}
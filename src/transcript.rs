@@ -0,0 +1,143 @@
+use core::marker::PhantomData;
+
+use brave_miracl::hash256::HASH256;
+
+use super::backend::{PairingBackend, MAX_ECP2_SIZE, MAX_ECP_SIZE};
+use super::ciphersuite::{Ciphersuite, HashFunction};
+
+pub const PROTOCOL_JOIN_REQUEST: &[u8] = b"join-request";
+pub const PROTOCOL_JOIN_RESPONSE: &[u8] = b"join-response";
+pub const PROTOCOL_SIGN: &[u8] = b"sign";
+pub const PROTOCOL_ISSUER_KEY: &[u8] = b"issuer-key";
+pub const PROTOCOL_SIGN_UNLINKABLE: &[u8] = b"sign-unlinkable";
+pub const PROTOCOL_MIGRATE: &[u8] = b"migrate";
+#[cfg(feature = "alloc")]
+pub const PROTOCOL_SIGN_ATTRIBUTES: &[u8] = b"sign-attributes";
+#[cfg(feature = "alloc")]
+pub const PROTOCOL_SIGN_K_TIMES: &[u8] = b"sign-k-times";
+#[cfg(feature = "alloc")]
+pub const PROTOCOL_THRESHOLD_JOIN: &[u8] = b"threshold-join";
+#[cfg(feature = "alloc")]
+pub const PROTOCOL_RANGE_PROOF: &[u8] = b"range-proof";
+#[cfg(feature = "alloc")]
+pub const PROTOCOL_DKG_PRODUCT: &[u8] = b"dkg-product";
+
+/// Fiat-Shamir transcript from which proof challenges are derived.
+///
+/// Under a labeled ciphersuite every absorbed value is prefixed with its
+/// length-prefixed label and its own length, after the suite's domain
+/// separator and the protocol name. Under the legacy suite labels are
+/// ignored and values are hashed back to back, which reproduces the byte
+/// layout of the original C library. Protocols that the C library never
+/// had use `labeled`, which labels values under every suite.
+pub struct Transcript<B: PairingBackend> {
+    hash: HASH256,
+    labeled: bool,
+    backend: PhantomData<B>,
+}
+
+impl<B: PairingBackend> Transcript<B> {
+    pub fn new(suite: Ciphersuite, protocol: &[u8]) -> Self {
+        match suite.domain_separator() {
+            Some(_) => Self::labeled(suite, protocol),
+            None => Self::with_labels(suite, false),
+        }
+    }
+
+    /// Transcript that absorbs the protocol name and labels every value,
+    /// even under the legacy suite, so that challenges of different
+    /// protocols never coincide.
+    pub fn labeled(suite: Ciphersuite, protocol: &[u8]) -> Self {
+        let mut transcript = Self::with_labels(suite, true);
+        if let Some(dst) = suite.domain_separator() {
+            transcript.append_bytes(b"dst", dst);
+        }
+        transcript.append_bytes(b"protocol", protocol);
+        transcript
+    }
+
+    fn with_labels(suite: Ciphersuite, labeled: bool) -> Self {
+        let hash = match suite.hash_function() {
+            HashFunction::Sha256 => HASH256::new(),
+        };
+        Self {
+            hash,
+            labeled,
+            backend: PhantomData,
+        }
+    }
+
+    fn append_length(&mut self, len: usize) {
+        self.hash.process_array(&(len as u32).to_be_bytes());
+    }
+
+    pub fn append_bytes(&mut self, label: &[u8], bytes: &[u8]) {
+        if self.labeled {
+            self.append_length(label.len());
+            self.hash.process_array(label);
+            self.append_length(bytes.len());
+        }
+        self.hash.process_array(bytes);
+    }
+
+    pub fn append_ecp(&mut self, label: &[u8], point: &B::G1) {
+        let mut bytes = [0u8; MAX_ECP_SIZE];
+        B::g1_to_bytes(point, &mut bytes[..B::G1_SIZE]);
+        self.append_bytes(label, &bytes[..B::G1_SIZE]);
+    }
+
+    pub fn append_ecp2(&mut self, label: &[u8], point: &B::G2) {
+        let mut bytes = [0u8; MAX_ECP2_SIZE];
+        B::g2_to_bytes(point, &mut bytes[..B::G2_SIZE]);
+        self.append_bytes(label, &bytes[..B::G2_SIZE]);
+    }
+
+    /// Finalizes the transcript into a challenge reduced mod the curve order.
+    pub fn challenge(mut self) -> B::Scalar {
+        B::scalar_from_hash(&self.hash.hash())
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::backend::BIG_SIZE;
+    use crate::ciphersuite::Curve;
+    use crate::Bn254 as Backend;
+
+    const PROTOCOLS: [&[u8]; 7] = [
+        PROTOCOL_SIGN_UNLINKABLE,
+        PROTOCOL_SIGN_K_TIMES,
+        PROTOCOL_MIGRATE,
+        PROTOCOL_THRESHOLD_JOIN,
+        PROTOCOL_RANGE_PROOF,
+        PROTOCOL_SIGN_ATTRIBUTES,
+        PROTOCOL_DKG_PRODUCT,
+    ];
+
+    fn challenge(transcript: Transcript<Backend>) -> [u8; BIG_SIZE] {
+        let mut transcript = transcript;
+        transcript.append_ecp(b"commitment", &Backend::g1_generator());
+        let mut bytes = [0u8; BIG_SIZE];
+        Backend::scalar_to_bytes(&transcript.challenge(), &mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn test_labeled_challenges_differ_across_protocols() {
+        for suite in Curve::Bn254.suites() {
+            let challenges: Vec<_> = PROTOCOLS
+                .iter()
+                .map(|protocol| challenge(Transcript::labeled(suite, protocol)))
+                .collect();
+            for (i, c) in challenges.iter().enumerate() {
+                assert!(challenges[i + 1..].iter().all(|other| other != c));
+            }
+
+            // Only unlabeled legacy transcripts ignore the protocol
+            let sign = challenge(Transcript::new(suite, PROTOCOL_SIGN));
+            let join = challenge(Transcript::new(suite, PROTOCOL_JOIN_REQUEST));
+            assert_eq!(sign == join, suite.domain_separator().is_none());
+        }
+    }
+}
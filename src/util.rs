@@ -1,40 +1,26 @@
-use brave_miracl::{
-    bn254::{
-        big::BIG,
-        ecp::ECP,
-        ecp2::ECP2,
-        fp12::FP12,
-        fp2::FP2,
-        pair::{another, fexp, initmp, miller},
-        rom::{CURVE_GX, CURVE_GY, CURVE_ORDER, CURVE_PXA, CURVE_PXB, CURVE_PYA, CURVE_PYB},
-    },
-    hash256::HASH256,
-    rand::RAND,
-};
-use lazy_static::lazy_static;
+use brave_miracl::{hash256::HASH256, rand::RAND};
 
-use super::data::{BIG_SIZE, ECP_SIZE};
+use super::backend::PairingBackend;
+#[cfg(feature = "alloc")]
+use super::backend::{BIG_SIZE, MAX_ECP_SIZE};
+use super::data::ECPProof;
+use super::transcript::Transcript;
 
-lazy_static! {
-    pub static ref G1_ECP: ECP = {
-        let gx = BIG::new_ints(&CURVE_GX);
-        let gy = BIG::new_ints(&CURVE_GY);
-        ECP::new_bigs(&gx, &gy)
-    };
-    pub static ref G2_ECP: ECP2 = {
-        let pxa = BIG::new_ints(&CURVE_PXA);
-        let pxb = BIG::new_ints(&CURVE_PXB);
-        let pya = BIG::new_ints(&CURVE_PYA);
-        let pyb = BIG::new_ints(&CURVE_PYB);
-        let wx = FP2::new_bigs(&pxa, &pxb);
-        let wy = FP2::new_bigs(&pya, &pyb);
-        ECP2::new_fp2s(&wx, &wy)
-    };
-    pub static ref CURVE_ORDER_BIG: BIG = BIG::new_ints(&CURVE_ORDER);
+#[cfg(feature = "alloc")]
+pub fn scalar_from_u64<B: PairingBackend>(value: u64) -> B::Scalar {
+    let mut bytes = [0u8; BIG_SIZE];
+    bytes[BIG_SIZE - 8..].copy_from_slice(&value.to_be_bytes());
+    B::scalar_from_bytes(&bytes)
 }
 
-pub fn random_mod_curve_order(rng: &mut RAND) -> BIG {
-    BIG::randomnum(&CURVE_ORDER_BIG, rng)
+/// Compares points by their encodings, which are unique.
+#[cfg(feature = "alloc")]
+pub fn g1_equals<B: PairingBackend>(a: &B::G1, b: &B::G1) -> bool {
+    let mut a_bytes = [0u8; MAX_ECP_SIZE];
+    let mut b_bytes = [0u8; MAX_ECP_SIZE];
+    B::g1_to_bytes(a, &mut a_bytes[..B::G1_SIZE]);
+    B::g1_to_bytes(b, &mut b_bytes[..B::G1_SIZE]);
+    a_bytes == b_bytes
 }
 
 pub fn hash256(data: &[u8]) -> [u8; 32] {
@@ -43,43 +29,60 @@ pub fn hash256(data: &[u8]) -> [u8; 32] {
     hash.hash()
 }
 
-pub fn pair_normalized_triple_ate(p: &ECP2, q: &ECP, r: &ECP2, s: &ECP, t: &ECP2, u: &ECP) -> FP12 {
-    let mut rr = initmp();
-    another(&mut rr, p, q);
-    another(&mut rr, r, s);
-    another(&mut rr, t, u);
-    let r = miller(&mut rr);
-    fexp(&r)
+/// Challenge for a proof that log_a(y) == log_b(z), continuing a transcript
+/// that may already contain the message being signed.
+pub fn ecp_challenge_equals<B: PairingBackend>(
+    mut transcript: Transcript<B>,
+    y: &B::G1,
+    z: &B::G1,
+    a: &B::G1,
+    b: &B::G1,
+    ar: &B::G1,
+    br: &B::G1,
+) -> B::Scalar {
+    transcript.append_ecp(b"y", y);
+    transcript.append_ecp(b"z", z);
+    transcript.append_ecp(b"a", a);
+    transcript.append_ecp(b"b", b);
+    transcript.append_ecp(b"ar", ar);
+    transcript.append_ecp(b"br", br);
+    transcript.challenge()
 }
 
-pub fn ecp_challenge_equals(
-    message: Option<&[u8; BIG_SIZE]>,
-    y: &ECP,
-    z: &ECP,
-    a: &ECP,
-    b: &ECP,
-    ar: &ECP,
-    br: &ECP,
-) -> BIG {
-    let (mut all_bytes, msg_len) = match message {
-        Some(message) => {
-            let mut all_bytes = vec![0u8; ECP_SIZE * 6 + BIG_SIZE];
-            all_bytes[..BIG_SIZE].copy_from_slice(message);
-            (all_bytes, message.len())
-        }
-        None => (vec![0u8; ECP_SIZE * 6], 0),
-    };
+pub fn make_ecp_proof_equals<B: PairingBackend>(
+    rng: &mut RAND,
+    transcript: Transcript<B>,
+    a: &B::G1,
+    b: &B::G1,
+    y: &B::G1,
+    z: &B::G1,
+    x: &B::Scalar,
+) -> ECPProof<B> {
+    let r = B::scalar_random(rng);
 
-    y.tobytes(&mut all_bytes[msg_len..], false);
-    z.tobytes(&mut all_bytes[msg_len + ECP_SIZE..], false);
-    a.tobytes(&mut all_bytes[msg_len + ECP_SIZE * 2..], false);
-    b.tobytes(&mut all_bytes[msg_len + ECP_SIZE * 3..], false);
-    ar.tobytes(&mut all_bytes[msg_len + ECP_SIZE * 4..], false);
-    br.tobytes(&mut all_bytes[msg_len + ECP_SIZE * 5..], false);
+    let ar = B::g1_mul(a, &r);
+    let br = B::g1_mul(b, &r);
 
-    let hash = hash256(&all_bytes);
+    let c = ecp_challenge_equals(transcript, y, z, a, b, &ar, &br);
+    let s = B::scalar_add(&B::scalar_mul(&c, x), &r);
 
-    let mut c = BIG::frombytes(&hash);
-    c.rmod(&CURVE_ORDER_BIG);
-    c
+    ECPProof { c, s }
+}
+
+pub fn verify_ecp_proof_equals<B: PairingBackend>(
+    transcript: Transcript<B>,
+    a: &B::G1,
+    b: &B::G1,
+    y: &B::G1,
+    z: &B::G1,
+    proof: &ECPProof<B>,
+) -> bool {
+    let cn = B::scalar_neg(&proof.c);
+
+    let r#as = B::g1_add(&B::g1_mul(a, &proof.s), &B::g1_mul(y, &cn));
+    let bs = B::g1_add(&B::g1_mul(b, &proof.s), &B::g1_mul(z, &cn));
+
+    let cc = ecp_challenge_equals(transcript, y, z, a, b, &r#as, &bs);
+
+    B::scalar_equals(&proof.c, &cc)
 }
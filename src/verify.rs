@@ -0,0 +1,137 @@
+use super::backend::{PairingBackend, BIG_SIZE};
+use super::ciphersuite::Ciphersuite;
+use super::data::{GroupPublicKey, Signature, UnlinkableSignature};
+use super::sign::{sign_transcript, unlinkable_challenge};
+use super::util::verify_ecp_proof_equals;
+use super::{CredentialError, Result};
+
+/// Checks that randomized credentials still satisfy e(A, Y) = e(B, G2) and
+/// e(A + D, X) = e(C, G2), with neither A nor B at infinity. Callers fold
+/// the terms of any certified attributes into `d`, and check the D of the
+/// credentials with `member_points_are_valid`.
+pub(crate) fn randomized_credentials_are_valid<B: PairingBackend>(
+    pub_key: &GroupPublicKey<B>,
+    a: &B::G1,
+    b: &B::G1,
+    c: &B::G1,
+    d: &B::G1,
+) -> bool {
+    if B::g1_is_infinity(a) || B::g1_is_infinity(b) {
+        return false;
+    }
+
+    let g2 = B::g2_generator();
+    B::pairing_product_is_one(&[(&pub_key.y, a), (&g2, &B::g1_neg(b))])
+        && B::pairing_product_is_one(&[(&pub_key.x, &B::g1_add(a, d)), (&g2, &B::g1_neg(c))])
+}
+
+/// Checks that D = B ** gsk and the nym, if any, are not at infinity. Both
+/// are only for gsk = 0, for which credentials with D at infinity satisfy
+/// every pairing equation without binding a member key.
+pub(crate) fn member_points_are_valid<B: PairingBackend>(d: &B::G1, nym: Option<&B::G1>) -> bool {
+    !B::g1_is_infinity(d) && !nym.is_some_and(B::g1_is_infinity)
+}
+
+/// Checks the proof that log_B(D) = log_H(bsn)(nym), bound to the message
+/// with digest `digest`, and that D and the nym are not at infinity.
+pub(crate) fn nym_proof_is_valid<B: PairingBackend>(
+    suite: Ciphersuite,
+    signature: &Signature<B>,
+    digest: &[u8; BIG_SIZE],
+    bsn: &[u8],
+) -> bool {
+    if !member_points_are_valid::<B>(&signature.d, Some(&signature.nym)) {
+        return false;
+    }
+
+    let bsn_point = suite.hash_to_g1::<B>(bsn);
+    verify_ecp_proof_equals(
+        sign_transcript(suite, digest, bsn),
+        &signature.b,
+        &bsn_point,
+        &signature.d,
+        &signature.nym,
+        &signature.proof,
+    )
+}
+
+/// Verifies a signature on `msg` for the basename `bsn`, given as raw bytes
+/// or as a structured `Basename`.
+pub fn verify<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &GroupPublicKey<B>,
+    signature: &Signature<B>,
+    msg: &[u8],
+    bsn: impl AsRef<[u8]>,
+) -> Result<()> {
+    verify_prehashed(suite, pub_key, signature, &suite.hash(msg), bsn)
+}
+
+/// Verifies a signature on the message whose digest under the suite's hash
+/// function is `digest`, e.g. from a `MessageHasher`.
+pub fn verify_prehashed<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &GroupPublicKey<B>,
+    signature: &Signature<B>,
+    digest: &[u8; BIG_SIZE],
+    bsn: impl AsRef<[u8]>,
+) -> Result<()> {
+    suite.check::<B>()?;
+    let bsn = bsn.as_ref();
+    if !randomized_credentials_are_valid(
+        pub_key,
+        &signature.a,
+        &signature.b,
+        &signature.c,
+        &signature.d,
+    ) {
+        return Err(CredentialError::SignatureValidation);
+    }
+
+    // log_B(D) = log_H(bsn)(nym) = gsk
+    if !nym_proof_is_valid(suite, signature, digest, bsn) {
+        return Err(CredentialError::SignatureValidation);
+    }
+
+    Ok(())
+}
+
+/// Verifies a signature made with `sign_unlinkable`.
+pub fn verify_unlinkable<B: PairingBackend>(
+    suite: Ciphersuite,
+    pub_key: &GroupPublicKey<B>,
+    signature: &UnlinkableSignature<B>,
+    msg: &[u8],
+) -> Result<()> {
+    suite.check::<B>()?;
+    if !randomized_credentials_are_valid(
+        pub_key,
+        &signature.a,
+        &signature.b,
+        &signature.c,
+        &signature.d,
+    ) || !member_points_are_valid::<B>(&signature.d, None)
+    {
+        return Err(CredentialError::SignatureValidation);
+    }
+
+    // B ** s - D ** c must reproduce the signer's commitment
+    let br = B::g1_add(
+        &B::g1_mul(&signature.b, &signature.proof.s),
+        &B::g1_mul(&signature.d, &B::scalar_neg(&signature.proof.c)),
+    );
+    let c = unlinkable_challenge::<B>(
+        suite,
+        msg,
+        &signature.a,
+        &signature.b,
+        &signature.c,
+        &signature.d,
+        &br,
+    );
+    if !B::scalar_equals(&signature.proof.c, &c) {
+        return Err(CredentialError::SignatureValidation);
+    }
+
+    Ok(())
+}
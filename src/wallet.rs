@@ -0,0 +1,52 @@
+//! Credentials for several groups, keyed by the id of their group public key.
+
+use alloc::vec::Vec;
+
+use super::backend::PairingBackend;
+use super::ciphersuite::Ciphersuite;
+use super::data::{CredentialBIG, CredentialMetadata, GroupKeyId, UserCredentials};
+
+pub(crate) struct WalletEntry<B: PairingBackend> {
+    pub(crate) suite: Ciphersuite,
+    pub(crate) gsk: CredentialBIG<B>,
+    pub(crate) credentials: UserCredentials<B>,
+    pub(crate) metadata: Option<CredentialMetadata>,
+}
+
+/// Entries sorted by group key id, at most one per group.
+#[derive(Default)]
+pub(crate) struct Wallet<B: PairingBackend> {
+    entries: Vec<(GroupKeyId, WalletEntry<B>)>,
+}
+
+impl<B: PairingBackend> Wallet<B> {
+    /// Stores `entry` for `group`, replacing any earlier credentials.
+    pub(crate) fn insert(&mut self, group: GroupKeyId, entry: WalletEntry<B>) {
+        match self.entries.binary_search_by_key(&group, |(id, _)| *id) {
+            Ok(i) => self.entries[i].1 = entry,
+            Err(i) => self.entries.insert(i, (group, entry)),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, group: &GroupKeyId) -> bool {
+        match self.entries.binary_search_by_key(group, |(id, _)| *id) {
+            Ok(i) => {
+                self.entries.remove(i);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub(crate) fn get(&self, group: &GroupKeyId) -> Option<&WalletEntry<B>> {
+        let i = self
+            .entries
+            .binary_search_by_key(group, |(id, _)| *id)
+            .ok()?;
+        Some(&self.entries[i].1)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &(GroupKeyId, WalletEntry<B>)> {
+        self.entries.iter()
+    }
+}
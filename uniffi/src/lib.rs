@@ -0,0 +1,276 @@
+use std::sync::{Arc, Mutex};
+
+use anonymous_credentials as ac;
+use anonymous_credentials::{
+    Ciphersuite, CredentialBIG, JoinResponse, UserCredentials, BIG_SIZE, USER_CREDENTIALS_SIZE,
+};
+
+uniffi::include_scaffolding!("anonymous_credentials");
+
+#[derive(Debug, thiserror::Error)]
+pub enum CredentialError {
+    #[error("ECP should be {0} bytes", ac::ECP_SIZE)]
+    BadECP,
+    #[error("ECP2 should be {0} bytes", ac::ECP2_COMPAT_SIZE)]
+    BadECP2,
+    #[error("BIG should be {0} bytes and below the curve order", ac::BIG_SIZE)]
+    BadBIG,
+    #[error("ECP proof should be {0} bytes", ac::ECP_PROOF_SIZE)]
+    BadECPProof,
+    #[error("User credentials should be {0} bytes", ac::USER_CREDENTIALS_SIZE)]
+    BadUserCredentials,
+    #[error("Join response should be {0} bytes", ac::JOIN_RESPONSE_SIZE)]
+    BadJoinResponse,
+    #[error("Group public key should be {0} bytes", ac::GROUP_PUBLIC_KEY_SIZE)]
+    GroupPublicKeyLength,
+    #[error("Join response validation failed")]
+    JoinResponseValidation,
+    #[error("Private key and/or credentials not set")]
+    CredentialsNotSet,
+    #[error("Group public key verification failed")]
+    BadGroupPublicKey,
+    #[error("Unknown ciphersuite")]
+    UnknownCiphersuite,
+    #[error("Signature should be {0} bytes", ac::SIGNATURE_SIZE)]
+    BadSignature,
+    #[error("Signature verification failed")]
+    SignatureValidation,
+    #[error("Join request should be {0} bytes", ac::JOIN_REQUEST_SIZE)]
+    BadJoinRequest,
+    #[error("Attributes are malformed or do not match the group public key")]
+    BadAttributes,
+    #[error("Attribute is outside of the requested range")]
+    AttributeOutOfRange,
+    #[error("Counter must be less than the limit k")]
+    CounterOutOfRange,
+    #[error("K-times signature is malformed")]
+    BadKTimesSignature,
+    #[error("Basename field is longer than 4 GiB")]
+    BadBasename,
+    #[error(
+        "Unlinkable signature should be {0} bytes",
+        ac::UNLINKABLE_SIGNATURE_SIZE
+    )]
+    BadUnlinkableSignature,
+    #[error("Credentials validation failed")]
+    CredentialsValidation,
+    #[error("Migration request should be {0} bytes", ac::MIGRATION_REQUEST_SIZE)]
+    BadMigrationRequest,
+    #[error("Migration request validation failed")]
+    MigrationValidation,
+    #[error(
+        "Credential metadata should be {0} bytes",
+        ac::CREDENTIAL_METADATA_SIZE
+    )]
+    BadCredentialMetadata,
+    #[error("Credentials have expired")]
+    CredentialsExpired,
+    #[error("Threshold issuance message is malformed")]
+    BadThresholdMessage,
+    #[error("Threshold issuance failed")]
+    ThresholdIssuance,
+    #[error("Distributed key generation failed")]
+    KeyGeneration,
+    #[error("Key log proof or entry is malformed")]
+    BadKeyLogMessage,
+    #[error("Key log verification failed")]
+    KeyLogVerification,
+    #[error("finish_join called without a pending start_join")]
+    JoinNotStarted,
+}
+
+impl From<ac::CredentialError> for CredentialError {
+    fn from(err: ac::CredentialError) -> Self {
+        match err {
+            ac::CredentialError::BadECP => Self::BadECP,
+            ac::CredentialError::BadECP2 => Self::BadECP2,
+            ac::CredentialError::BadBIG => Self::BadBIG,
+            ac::CredentialError::BadECPProof => Self::BadECPProof,
+            ac::CredentialError::BadUserCredentials => Self::BadUserCredentials,
+            ac::CredentialError::BadJoinResponse => Self::BadJoinResponse,
+            ac::CredentialError::GroupPublicKeyLength => Self::GroupPublicKeyLength,
+            ac::CredentialError::JoinResponseValidation => Self::JoinResponseValidation,
+            ac::CredentialError::CredentialsNotSet => Self::CredentialsNotSet,
+            ac::CredentialError::BadGroupPublicKey => Self::BadGroupPublicKey,
+            ac::CredentialError::UnknownCiphersuite => Self::UnknownCiphersuite,
+            ac::CredentialError::BadSignature => Self::BadSignature,
+            ac::CredentialError::SignatureValidation => Self::SignatureValidation,
+            ac::CredentialError::BadJoinRequest => Self::BadJoinRequest,
+            ac::CredentialError::BadAttributes => Self::BadAttributes,
+            ac::CredentialError::AttributeOutOfRange => Self::AttributeOutOfRange,
+            ac::CredentialError::CounterOutOfRange => Self::CounterOutOfRange,
+            ac::CredentialError::BadKTimesSignature => Self::BadKTimesSignature,
+            ac::CredentialError::BadBasename => Self::BadBasename,
+            ac::CredentialError::BadUnlinkableSignature => Self::BadUnlinkableSignature,
+            ac::CredentialError::CredentialsValidation => Self::CredentialsValidation,
+            ac::CredentialError::BadMigrationRequest => Self::BadMigrationRequest,
+            ac::CredentialError::MigrationValidation => Self::MigrationValidation,
+            ac::CredentialError::BadCredentialMetadata => Self::BadCredentialMetadata,
+            ac::CredentialError::CredentialsExpired => Self::CredentialsExpired,
+            ac::CredentialError::BadThresholdMessage => Self::BadThresholdMessage,
+            ac::CredentialError::ThresholdIssuance => Self::ThresholdIssuance,
+            ac::CredentialError::KeyGeneration => Self::KeyGeneration,
+            ac::CredentialError::BadKeyLogMessage => Self::BadKeyLogMessage,
+            ac::CredentialError::KeyLogVerification => Self::KeyLogVerification,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, CredentialError>;
+
+pub struct ExportedCredentials {
+    pub gsk: Vec<u8>,
+    pub credentials: Vec<u8>,
+}
+
+pub struct GroupPublicKey(ac::GroupPublicKey);
+
+impl GroupPublicKey {
+    pub fn new(data: Vec<u8>) -> Result<Self> {
+        Ok(Self(data.as_slice().try_into()?))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes().to_vec()
+    }
+}
+
+pub struct Signature(ac::Signature);
+
+impl Signature {
+    pub fn new(data: Vec<u8>) -> Result<Self> {
+        Ok(Self(data.as_slice().try_into()?))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes().to_vec()
+    }
+
+    pub fn nym(&self) -> Vec<u8> {
+        self.0.nym().to_vec()
+    }
+}
+
+pub fn verify(
+    ciphersuite_id: u8,
+    public_key: Arc<GroupPublicKey>,
+    signature: Arc<Signature>,
+    msg: Vec<u8>,
+    basename: Vec<u8>,
+) -> Result<()> {
+    let suite = Ciphersuite::try_from(ciphersuite_id)?;
+    Ok(ac::verify(
+        suite,
+        &public_key.0,
+        &signature.0,
+        &msg,
+        &basename,
+    )?)
+}
+
+struct ManagerState {
+    manager: ac::CredentialManager,
+    // gsk of the last start_join, until finish_join consumes it
+    pending_gsk: Option<[u8; BIG_SIZE]>,
+    // Copy of what was handed to the manager, kept for explicit export
+    installed: Option<([u8; BIG_SIZE], [u8; USER_CREDENTIALS_SIZE])>,
+}
+
+/// Keeps the gsk on the Rust side of the FFI boundary: it never crosses
+/// into managed memory except through `export_gsk_and_credentials`.
+pub struct CredentialManager {
+    state: Mutex<ManagerState>,
+}
+
+impl CredentialManager {
+    fn with_manager(manager: ac::CredentialManager) -> Self {
+        Self {
+            state: Mutex::new(ManagerState {
+                manager,
+                pending_gsk: None,
+                installed: None,
+            }),
+        }
+    }
+
+    pub fn new() -> Self {
+        Self::with_manager(ac::CredentialManager::new())
+    }
+
+    pub fn new_with_seed(entropy: Vec<u8>) -> Self {
+        Self::with_manager(ac::CredentialManager::new_with_seed(&entropy))
+    }
+
+    pub fn set_ciphersuite(&self, id: u8) -> Result<()> {
+        let suite = Ciphersuite::try_from(id)?;
+        self.state.lock().unwrap().manager.set_ciphersuite(suite)?;
+        Ok(())
+    }
+
+    pub fn start_join(&self, challenge: Vec<u8>) -> Vec<u8> {
+        let mut state = self.state.lock().unwrap();
+        let result = state.manager.start_join(&challenge);
+        state.pending_gsk = Some(result.gsk.to_bytes());
+        result.join_msg.to_bytes().to_vec()
+    }
+
+    pub fn finish_join(
+        &self,
+        public_key: Arc<GroupPublicKey>,
+        join_response: Vec<u8>,
+    ) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let gsk_bytes = state.pending_gsk.ok_or(CredentialError::JoinNotStarted)?;
+
+        let gsk: CredentialBIG = gsk_bytes.as_slice().try_into()?;
+        let join_response: JoinResponse = join_response.as_slice().try_into()?;
+        let credentials = state
+            .manager
+            .finish_join(&public_key.0, &gsk, join_response)?;
+
+        state.installed = Some((gsk_bytes, credentials.to_bytes()));
+        state.pending_gsk = None;
+        state.manager.set_gsk_and_credentials(gsk, credentials);
+        Ok(())
+    }
+
+    pub fn has_credentials(&self) -> bool {
+        self.state.lock().unwrap().installed.is_some()
+    }
+
+    pub fn sign(&self, msg: Vec<u8>, basename: Vec<u8>) -> Result<Arc<Signature>> {
+        let signature = self.state.lock().unwrap().manager.sign(&msg, &basename)?;
+        Ok(Arc::new(Signature(signature)))
+    }
+
+    pub fn export_gsk_and_credentials(&self) -> Result<ExportedCredentials> {
+        match self.state.lock().unwrap().installed {
+            Some((gsk, credentials)) => Ok(ExportedCredentials {
+                gsk: gsk.to_vec(),
+                credentials: credentials.to_vec(),
+            }),
+            None => Err(CredentialError::CredentialsNotSet),
+        }
+    }
+
+    pub fn import_gsk_and_credentials(&self, gsk: Vec<u8>, credentials: Vec<u8>) -> Result<()> {
+        let gsk_bytes: [u8; BIG_SIZE] = gsk.try_into().map_err(|_| CredentialError::BadBIG)?;
+        let credentials_bytes: [u8; USER_CREDENTIALS_SIZE] = credentials
+            .try_into()
+            .map_err(|_| CredentialError::BadUserCredentials)?;
+
+        let gsk: CredentialBIG = gsk_bytes.as_slice().try_into()?;
+        let credentials: UserCredentials = credentials_bytes.as_slice().try_into()?;
+
+        let mut state = self.state.lock().unwrap();
+        state.manager.set_gsk_and_credentials(gsk, credentials);
+        state.installed = Some((gsk_bytes, credentials_bytes));
+        Ok(())
+    }
+}
+
+impl Default for CredentialManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,106 @@
+use anonymous_credentials::{
+    Ciphersuite, CredentialBIG, CredentialError, GroupPublicKey, JoinResponse, UserCredentials,
+};
+use wasm_bindgen::prelude::*;
+
+/// Converts a `CredentialError` into a JS `Error` whose `name` is the
+/// variant name (e.g. `JoinResponseValidation`), so callers can branch on
+/// `err.name` instead of parsing messages.
+fn to_js_error(err: CredentialError) -> JsValue {
+    let js_err = js_sys::Error::new(&err.to_string());
+    js_err.set_name(&format!("{:?}", err));
+    js_err.into()
+}
+
+#[wasm_bindgen]
+pub struct StartJoinResult {
+    gsk: Vec<u8>,
+    join_msg: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl StartJoinResult {
+    #[wasm_bindgen(getter)]
+    pub fn gsk(&self) -> Vec<u8> {
+        self.gsk.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = joinMsg)]
+    pub fn join_msg(&self) -> Vec<u8> {
+        self.join_msg.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct CredentialManager {
+    inner: anonymous_credentials::CredentialManager,
+}
+
+#[wasm_bindgen]
+impl CredentialManager {
+    /// Creates a manager seeded from `crypto.getRandomValues`.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Result<CredentialManager, JsValue> {
+        let mut entropy = [0u8; 128];
+        getrandom::getrandom(&mut entropy).map_err(|e| js_sys::Error::new(&e.to_string()))?;
+        Ok(Self::new_with_seed(&entropy))
+    }
+
+    #[wasm_bindgen(js_name = newWithSeed)]
+    pub fn new_with_seed(entropy: &[u8]) -> CredentialManager {
+        Self {
+            inner: anonymous_credentials::CredentialManager::new_with_seed(entropy),
+        }
+    }
+
+    /// Selects the ciphersuite by its `Ciphersuite::id`.
+    #[wasm_bindgen(js_name = setCiphersuite)]
+    pub fn set_ciphersuite(&mut self, id: u8) -> Result<(), JsValue> {
+        let suite = Ciphersuite::try_from(id).map_err(to_js_error)?;
+        self.inner.set_ciphersuite(suite).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(js_name = startJoin)]
+    pub fn start_join(&mut self, challenge: &[u8]) -> StartJoinResult {
+        let result = self.inner.start_join(challenge);
+        StartJoinResult {
+            gsk: result.gsk.to_bytes().to_vec(),
+            join_msg: result.join_msg.to_bytes().to_vec(),
+        }
+    }
+
+    /// Validates the issuer's join response and returns the credentials.
+    #[wasm_bindgen(js_name = finishJoin)]
+    pub fn finish_join(
+        &mut self,
+        public_key: &[u8],
+        gsk: &[u8],
+        join_response: &[u8],
+    ) -> Result<Vec<u8>, JsValue> {
+        let public_key: GroupPublicKey = public_key.try_into().map_err(to_js_error)?;
+        let gsk: CredentialBIG = gsk.try_into().map_err(to_js_error)?;
+        let join_response: JoinResponse = join_response.try_into().map_err(to_js_error)?;
+        let credentials = self
+            .inner
+            .finish_join(&public_key, &gsk, join_response)
+            .map_err(to_js_error)?;
+        Ok(credentials.to_bytes().to_vec())
+    }
+
+    #[wasm_bindgen(js_name = setGskAndCredentials)]
+    pub fn set_gsk_and_credentials(
+        &mut self,
+        gsk: &[u8],
+        credentials: &[u8],
+    ) -> Result<(), JsValue> {
+        let gsk: CredentialBIG = gsk.try_into().map_err(to_js_error)?;
+        let credentials: UserCredentials = credentials.try_into().map_err(to_js_error)?;
+        self.inner.set_gsk_and_credentials(gsk, credentials);
+        Ok(())
+    }
+
+    pub fn sign(&mut self, msg: &[u8], basename: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let signature = self.inner.sign(msg, basename).map_err(to_js_error)?;
+        Ok(signature.to_bytes().to_vec())
+    }
+}
@@ -0,0 +1,121 @@
+use anonymous_credentials_wasm::CredentialManager;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+// Same fixed-seed vectors as the native tests in the parent crate
+
+const CHALLENGE: &[u8] = b"challenge";
+
+const EXPECTED_GSK: &str = "0198c86f99ede0ca2ed30b8e4ae6cac831c9b398445422a41d95abb4d3f03499";
+
+const GROUP_PUB_KEY: &str = "0477ce930400ab04a6e1caa46601dbd1b1ba5d24f0577834a960285a0512e7ed\
+     0174121707ea5d80e083d2e992236864608998a4d08cb3a41dde1fc6b7eaad5b\
+     2125310b44ca712bf63f62c39cb44917de0772fefd876e170729428142c21d4f\
+     179f72fcdc1c1ff5f13e272449ac9ff01a74e95bb011045b12bdac942b46168d\
+     0511ecbb4651d9ddd6491437a8d6b6e6e6877038ea4317a5de863e237ff64720\
+     14d22c88863b6d8de3eb1b73bb46ab12553c2765bcde905487c518936887ba83\
+     1dc42ca4862bf60b7cccf08ae579f14699fcff5ec8366af5562a2117095dc066\
+     105c17714dadae0b2110b91d0f19f062e9ab410f59e4515cb027e268435502cf\
+     12d4a2dde1c5b711619507485e54e6e6bb1b279e7f42067c47b124e7b1e044de\
+     0345f28cea642eef79e0da60dad085b9bec8b73c61a4eee59ec4f024fc83366e\
+     1efc63762b2c1c214ad151dd01f1a5f16d5a238187f1afdab361dfea2e0956be\
+     24b1bcdba7c9a6a5e0296377bd1cf1b722bc4d375ae8aa4761b7aac5a50e9871";
+
+const EXPECTED_JOIN_MSG: &str = "04185d9e3a0f0e590928568a951a70749c5f3e969b3c335f109f0e95f4c0cbab\
+     e70ddd27e6751df37fa70b906d3d246b388a2a9fb67c3972ea1b822dc8065345\
+     4e0de86a209418b4953191caa980462463ec21b07da451e7becc2d7917ef34f5\
+     150c1c0a4187182af0a43a28868c7b17b2c73704bc268071b414d99f48999014\
+     b2";
+
+const JOIN_RESPONSE: &str = "04246220e5a9d48d359178c9e0994cc10f7288b50cd059c24c5a26fc5919682e\
+     8017b66ca6185d62bf2bed7cf02503157ab93ff79d8d34ab3c48669954b7e2b6\
+     9c041d98fde59abcd8c0f22790e8d40e253c1240f3697c161d18a9d04ca24ba2\
+     b01f0d100b28b3d529939ec717f4f39e114337878f03c9066afc225033276f16\
+     2b4904248822cb548ccb8167480e23f0198134d1547b005ac84c2a7101a4d39c\
+     924ee50298022d7dd7c9f0006eab2576635a36af81e0f781437c4ee35b867251\
+     1089830401074ad73c4e9e9aed541bdc5a2df2ee815a3ac4f6297b73da35db2a\
+     646e19720475cfe50eb2465833b50758f6c8f09fdf645643a4b3ef5bd494be6a\
+     551768c81677932196184249f179d319feba43b32da42501daa355d3cde30615\
+     a08ac687188a8c6e3b8a330f76c233e900acd6ef31c50796b91929cfc16b4fca\
+     d40b5309";
+
+const EXPECTED_CREDENTIALS: &str =
+    "04246220e5a9d48d359178c9e0994cc10f7288b50cd059c24c5a26fc5919682e\
+     8017b66ca6185d62bf2bed7cf02503157ab93ff79d8d34ab3c48669954b7e2b6\
+     9c041d98fde59abcd8c0f22790e8d40e253c1240f3697c161d18a9d04ca24ba2\
+     b01f0d100b28b3d529939ec717f4f39e114337878f03c9066afc225033276f16\
+     2b4904248822cb548ccb8167480e23f0198134d1547b005ac84c2a7101a4d39c\
+     924ee50298022d7dd7c9f0006eab2576635a36af81e0f781437c4ee35b867251\
+     1089830401074ad73c4e9e9aed541bdc5a2df2ee815a3ac4f6297b73da35db2a\
+     646e19720475cfe50eb2465833b50758f6c8f09fdf645643a4b3ef5bd494be6a\
+     551768c8";
+
+const EXPECTED_SIGNATURE: &str = "0406cb022fcc3dcaef1e4c62dad349bfd263581126cf17b293d1a41e4d96f840\
+     da00ad85e4a97aad1247a19a425da6f96978fdac180136f0f486bad0fce0a9ad\
+     a20401074ad73c4e9e9aed541bdc5a2df2ee815a3ac4f6297b73da35db2a646e\
+     19720475cfe50eb2465833b50758f6c8f09fdf645643a4b3ef5bd494be6a5517\
+     68c80424a006154937bcd3b8f94f12a4672d9a9411928846adc9132737600089\
+     a65915121160cbd4e417435e4acfe6657840c50584bc8dca420544879fe7fe9c\
+     03bc0f0418ee65a71c262c5301d782b20e7f3f252e938282b98a2f86a7447e2a\
+     a424005819835a0a954d4f6dc53ae4c8bad2d192a70fcb8883403f69989e43ff\
+     66caad0104208cd8eb5e25486eb754e44f4e2f3b6f5153f5aa73d7eab8c2f5c8\
+     67a157276b0463ced7f409f86ef91a4548cf4bb519392cd657505475e585a3ea\
+     0348b6266b1fd3d4ab1253392386bf2f08afe36072abe575e07865272c3014ab\
+     067f5051181fe574571f34d278e2c9359294ca44aa33568c546082e4e8d92154\
+     1e5ccc6c81";
+
+fn manager_with_fixed_seed() -> CredentialManager {
+    CredentialManager::new_with_seed(&[0u8; 1])
+}
+
+#[wasm_bindgen_test]
+fn test_gsk_and_join_msg() {
+    let mut cm = manager_with_fixed_seed();
+    let result = cm.start_join(CHALLENGE);
+
+    assert_eq!(result.gsk(), hex::decode(EXPECTED_GSK).unwrap());
+    assert_eq!(result.join_msg(), hex::decode(EXPECTED_JOIN_MSG).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn test_finish_join_credentials() {
+    let mut cm = manager_with_fixed_seed();
+
+    let credentials = cm
+        .finish_join(
+            &hex::decode(GROUP_PUB_KEY).unwrap(),
+            &hex::decode(EXPECTED_GSK).unwrap(),
+            &hex::decode(JOIN_RESPONSE).unwrap(),
+        )
+        .unwrap();
+
+    assert_eq!(credentials, hex::decode(EXPECTED_CREDENTIALS).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn test_signature() {
+    let mut cm = manager_with_fixed_seed();
+    cm.set_gsk_and_credentials(
+        &hex::decode(EXPECTED_GSK).unwrap(),
+        &hex::decode(EXPECTED_CREDENTIALS).unwrap(),
+    )
+    .unwrap();
+
+    let signature = cm.sign(b"message", b"basename").unwrap();
+
+    assert_eq!(signature, hex::decode(EXPECTED_SIGNATURE).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn test_errors_carry_variant_name() {
+    let mut cm = manager_with_fixed_seed();
+
+    let err = cm.sign(b"message", b"basename").unwrap_err();
+    let err: js_sys::Error = err.into();
+    assert_eq!(err.name(), "CredentialsNotSet");
+
+    let err = cm.set_gsk_and_credentials(&[0u8; 3], &[]).unwrap_err();
+    let err: js_sys::Error = err.into();
+    assert_eq!(err.name(), "BadBIG");
+}